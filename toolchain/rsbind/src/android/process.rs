@@ -28,6 +28,7 @@ pub(crate) struct AndroidProcess<'a> {
     host_crate_name: &'a str,
     ast_result: &'a AstResult,
     config: Option<Android>,
+    executor: Option<String>,
 }
 
 impl<'a> AndroidProcess<'a> {
@@ -38,6 +39,7 @@ impl<'a> AndroidProcess<'a> {
         host_crate_name: &'a str,
         ast_result: &'a AstResult,
         config: Option<Android>,
+        executor: Option<String>,
     ) -> Self {
         AndroidProcess {
             origin_prj_path,
@@ -46,6 +48,7 @@ impl<'a> AndroidProcess<'a> {
             host_crate_name,
             ast_result,
             config,
+            executor,
         }
     }
 }
@@ -81,6 +84,8 @@ impl<'a> BuildProcess for AndroidProcess<'a> {
                 host_crate: self.host_crate_name,
                 buf,
                 features: &self.config().features(),
+                has_stream: self.ast_result.has_stream(),
+                has_cancellation: self.ast_result.has_cancellation(),
             };
 
             unpack.unpack()?;
@@ -95,6 +100,9 @@ impl<'a> BuildProcess for AndroidProcess<'a> {
             namespace: self.config().namespace(),
             so_name: self.config().so_name(),
             ext_libs: self.config().ext_libs(),
+            unsigned: self.config().unsigned(),
+            panic: self.config().panic(),
+            executor: self.executor.clone(),
         }
         .gen_bridge(&bridge_c_src_path)?;
 
@@ -240,6 +248,9 @@ impl<'a> BuildProcess for AndroidProcess<'a> {
             namespace: self.config().namespace(),
             so_name: self.config().so_name(),
             ext_libs: self.config().ext_libs(),
+            unsigned: self.config().unsigned(),
+            panic: self.config().panic(),
+            executor: self.executor.clone(),
         }
         .gen_native(&java_gen_path)?;
 
@@ -1,9 +1,13 @@
+use heck::ToUpperCamelCase;
+
 use super::super::types::*;
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
 pub(crate) struct ArgDesc {
     pub name: String,
     pub ty: AstType,
+    /// References like `&str`, `&[u8]` or `&Struct`, `ty` is the owned type like `String`.
+    pub borrowed: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
@@ -12,6 +16,33 @@ pub(crate) struct MethodDesc {
     pub return_type: AstType,
     pub args: Vec<ArgDesc>,
     pub swallow_self: bool,
+    /// Methods taking `&mut self`, only supported by instance services.
+    pub mut_self: bool,
+    /// Methods without `self`, which are called on the implementation type.
+    pub is_static: bool,
+    /// The supertrait declaring the method, for methods inherited by the trait.
+    pub supertrait: Option<String>,
+    /// Methods with a default body, which callbacks implemented by native code can leave out.
+    pub has_default: bool,
+    /// `async fn` of normal traits, whose result is passed to a completion callback by the bridge.
+    pub is_async: bool,
+    /// Methods returning `Box<dyn Iterator<Item = T> + Send>` or a stream, the return type is the object
+    /// pulling the items, which is declared by the bridge.
+    pub sequence: Option<SequenceKind>,
+    /// The dispatcher registered by native code which runs the callback method, set by the `dispatch`
+    /// config. `None` runs it inline on the rust thread calling it.
+    pub dispatch: Option<String>,
+}
+
+///
+/// The kind of the items pulled by native code from an object declared by the bridge.
+///
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Copy)]
+pub(crate) enum SequenceKind {
+    /// `Iterator`, the items are pulled by blocking calls.
+    Iterator,
+    /// `Stream` of `futures`, the items are pulled by async calls.
+    Stream,
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
@@ -22,6 +53,18 @@ pub(crate) struct TraitDesc {
     pub mod_path: String,
     pub crate_name: String,
     pub is_callback: bool,
+    /// Traits implemented by rust and returned as `Box<dyn Trait>`, or instance services with constructors,
+    /// exported as classes holding the handle.
+    pub is_object: bool,
+    /// Callbacks receiving the result of an async method, which are declared by the bridge
+    /// instead of the contract.
+    pub is_completion: bool,
+    /// Objects pulling the items of an iterator or a stream returned by a method, which are declared
+    /// by the bridge. They have one method `next_item` returning `Option<T>`, async for streams.
+    pub sequence: Option<SequenceKind>,
+    /// Contract traits in the same file the trait inherits, beside `Send` and `Sync`.
+    pub supertraits: Vec<String>,
+    /// The methods of the trait, followed by the methods inherited from the supertraits.
     pub methods: Vec<MethodDesc>,
 }
 
@@ -32,5 +75,151 @@ pub(crate) struct StructDesc {
     pub mod_name: String,
     pub mod_path: String,
     pub crate_name: String,
+    pub is_tuple: bool,
+    pub fields: Vec<ArgDesc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub(crate) struct EnumDesc {
+    pub name: String,
+    pub ty: String,
+    pub mod_name: String,
+    pub mod_path: String,
+    pub crate_name: String,
+    pub variants: Vec<VariantDesc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub(crate) struct VariantDesc {
+    pub name: String,
+    pub value: i32,
+    pub is_tuple: bool,
     pub fields: Vec<ArgDesc>,
 }
+
+///
+/// A `pub const` of a primitive type or `&str`, whose value is copied into the native code.
+///
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub(crate) struct ConstDesc {
+    pub name: String,
+    pub ty: AstType,
+    pub mod_name: String,
+    pub mod_path: String,
+    pub crate_name: String,
+    pub value: ConstValue,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub(crate) enum ConstValue {
+    /// Wide enough for all the values of `i64` and `u64`.
+    Int(i128),
+    Float(f64),
+    Bool(bool),
+    String(String),
+}
+
+impl TraitDesc {
+    ///
+    /// Whether the trait is a service, whose methods are called on the implementation type.
+    ///
+    pub(crate) fn is_service(&self) -> bool {
+        !self.is_callback && !self.is_object
+    }
+
+    ///
+    /// Whether the method is a constructor like `fn new(config: Config) -> Self`.
+    ///
+    pub(crate) fn is_constructor(&self, method: &MethodDesc) -> bool {
+        method.is_static
+            && matches!(method.return_type, AstType::Object(ref origin) if origin.origin == self.name)
+    }
+
+    ///
+    /// Whether the trait is an instance service, whose handle holds the implementation created by constructors.
+    ///
+    pub(crate) fn has_constructor(&self) -> bool {
+        self.methods
+            .iter()
+            .any(|method| self.is_constructor(method))
+    }
+
+    ///
+    /// The item type of an object pulling the items of an iterator or a stream.
+    ///
+    pub(crate) fn sequence_item(&self) -> Option<AstType> {
+        self.sequence?;
+        match self.methods.first()?.return_type {
+            AstType::Optional(ref item) => Some(AstType::from(item.clone())),
+            _ => None,
+        }
+    }
+}
+
+impl MethodDesc {
+    ///
+    /// The trait declaring the method, which is `desc` itself or one of its supertraits.
+    ///
+    pub(crate) fn trait_name<'a>(&'a self, desc: &'a TraitDesc) -> &'a str {
+        self.supertrait.as_deref().unwrap_or(&desc.name)
+    }
+
+    ///
+    /// The name of the completion callback of an async method of `desc`.
+    ///
+    pub(crate) fn completion_name(&self, desc: &TraitDesc) -> String {
+        format!(
+            "{}{}Completion",
+            &desc.name,
+            self.name.to_upper_camel_case()
+        )
+    }
+
+    ///
+    /// The name of the object pulling the items of the iterator or the stream returned by the method
+    /// of `trait_name`.
+    ///
+    pub(crate) fn sequence_name(&self, trait_name: &str) -> String {
+        let suffix = match self.sequence {
+            Some(SequenceKind::Stream) => "Stream",
+            _ => "Iterator",
+        };
+        format!(
+            "{}{}{}",
+            trait_name,
+            self.name.to_upper_camel_case(),
+            suffix
+        )
+    }
+
+    ///
+    /// The method called through the bridge. An async method returns nothing there,
+    /// its result is passed to the completion callback in the last argument.
+    ///
+    pub(crate) fn to_bridge(&self, desc: &TraitDesc) -> MethodDesc {
+        let mut method = self.clone();
+        if self.is_async {
+            method.return_type = AstType::Void;
+            method.args.push(ArgDesc {
+                name: "completion".to_string(),
+                ty: AstType::Callback(CustomType {
+                    mod_name: desc.mod_name.clone(),
+                    origin: self.completion_name(desc),
+                }),
+                borrowed: false,
+            });
+        }
+        method
+    }
+}
+
+impl EnumDesc {
+    ///
+    /// Whether all the variants are fieldless, enums with data are transferred like structs.
+    ///
+    pub(crate) fn is_c_like(&self) -> bool {
+        self.variants
+            .iter()
+            .all(|variant| variant.fields.is_empty())
+    }
+}
@@ -1,3 +1,4 @@
 pub(crate) mod desc;
 pub(crate) mod parser;
+pub(crate) mod symbol;
 mod test;
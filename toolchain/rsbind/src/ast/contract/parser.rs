@@ -12,10 +12,13 @@ use crate::errors::*;
 
 use super::super::types::*;
 use super::desc::*;
+use super::symbol::SymbolTable;
 
 pub(crate) struct ContractResult {
     pub(crate) traits: Vec<TraitDesc>,
     pub(crate) structs: Vec<StructDesc>,
+    pub(crate) enums: Vec<EnumDesc>,
+    pub(crate) consts: Vec<ConstDesc>,
 }
 
 impl Default for ContractResult {
@@ -23,6 +26,8 @@ impl Default for ContractResult {
         ContractResult {
             traits: vec![],
             structs: vec![],
+            enums: vec![],
+            consts: vec![],
         }
     }
 }
@@ -64,10 +69,16 @@ pub(crate) struct ParseContext {
 }
 
 pub(crate) fn parse_from_str(ctx: &ParseContext, src: &str) -> Result<ContractResult> {
-    let syn_file = syn::parse_file(src).map_err(|e| ParseError(e.to_string()))?;
+    let mut syn_file = syn::parse_file(src).map_err(|e| ParseError(e.to_string()))?;
+
+    // Types are referenced by the bare names of their definitions after resolving.
+    SymbolTable::new(ctx, &syn_file.items)?.resolve_items(&mut syn_file.items)?;
 
     let mut trait_descs = vec![];
     let mut struct_descs = vec![];
+    let mut enum_descs = vec![];
+    let mut const_descs = vec![];
+    let mut newtypes = vec![];
 
     // loop all the trait
     for item in syn_file.items.iter() {
@@ -78,15 +89,17 @@ pub(crate) fn parse_from_str(ctx: &ParseContext, src: &str) -> Result<ContractRe
 
                 let mut send_derived = false;
                 let mut sync_derived = false;
-                for supertraits in trait_inner.supertraits.iter() {
-                    if let TypeParamBound::Trait(trait_bound) = supertraits {
+                let mut supertraits = vec![];
+                for supertrait in trait_inner.supertraits.iter() {
+                    if let TypeParamBound::Trait(trait_bound) = supertrait {
                         let segments = &(trait_bound.path.segments);
                         let ident = (&segments[segments.len() - 1].ident).to_string();
                         if ident == "Send" {
                             send_derived = true;
-                        }
-                        if ident == "Sync" {
+                        } else if ident == "Sync" {
                             sync_derived = true;
+                        } else {
+                            supertraits.push(ident);
                         }
                     }
                 }
@@ -103,6 +116,10 @@ pub(crate) fn parse_from_str(ctx: &ParseContext, src: &str) -> Result<ContractRe
                     mod_path: ctx.mod_path.clone(),
                     crate_name: ctx.crate_name.clone(),
                     is_callback: methods.1,
+                    is_object: false,
+                    is_completion: false,
+                    sequence: None,
+                    supertraits,
                     methods: methods.0,
                 };
 
@@ -111,62 +128,107 @@ pub(crate) fn parse_from_str(ctx: &ParseContext, src: &str) -> Result<ContractRe
             syn::Item::Struct(ref struct_inner) => {
                 println!("found struct => {}", &struct_inner.ident);
                 let stuct_name = struct_inner.ident.to_string();
-
-                let mut field_descs = vec![];
-                let fields = &struct_inner.fields;
-                for field in fields.iter() {
-                    let field_name = match field.ident {
-                        Some(ref value) => value.to_owned().to_string(),
-                        _ => "".to_owned(),
-                    };
-
-                    let field_ty;
-                    match field.ty {
-                        syn::Type::Path(ref type_path) => {
-                            let segments = &(type_path.path.segments);
-                            let ident = (&segments[segments.len() - 1].ident).to_string();
-                            if ident == "Box" {
-                                println!("found Box argument.");
-                                field_ty = Some(parse_boxed_ast(ctx, type_path));
-                            } else if ident == "Vec" {
-                                println!("found Vec argument.");
-                                field_ty = Some(parse_vec_ast(ctx, type_path));
-                            } else {
-                                // normal arguments
-                                field_ty = Some(AstType::new(&ident, &ident, ctx));
-                                println!("found args type => {:?}", ident);
-                            }
-                        }
-                        _ => {
-                            field_ty = Some(AstType::Void);
-                        }
-                    };
-                    let field_desc = ArgDesc {
-                        name: field_name,
-                        ty: field_ty.unwrap(),
-                    };
-                    field_descs.push(field_desc);
+                let is_tuple = matches!(struct_inner.fields, syn::Fields::Unnamed(_));
+                if is_tuple && is_transparent(&struct_inner.attrs) {
+                    newtypes.push((stuct_name, parse_newtype(ctx, struct_inner)?));
+                    continue;
                 }
 
+                let field_descs = parse_fields(ctx, &struct_inner.fields)?;
+
                 let struct_desc = StructDesc {
                     name: stuct_name,
                     ty: "struct".to_string(),
                     mod_name: ctx.mod_name.clone(),
                     mod_path: ctx.mod_path.clone(),
                     crate_name: ctx.crate_name.clone(),
+                    is_tuple,
                     fields: field_descs,
                 };
                 struct_descs.push(struct_desc);
             }
+            syn::Item::Enum(ref enum_inner) => {
+                println!("found enum => {}", &enum_inner.ident);
+                enum_descs.push(parse_enum(ctx, enum_inner)?);
+            }
+            syn::Item::Const(ref const_inner)
+                if matches!(const_inner.vis, syn::Visibility::Public(_)) =>
+            {
+                println!("found const => {}", &const_inner.ident);
+                match parse_const(ctx, const_inner) {
+                    Ok(const_desc) => const_descs.push(const_desc),
+                    Err(err) => println!("warning: {}", err),
+                }
+            }
             _ => (),
         }
     }
 
-    if !trait_descs.is_empty() || !struct_descs.is_empty() {
+    resolve_supertraits(&mut trait_descs)?;
+
+    // We can only know which types are enums after the whole file is parsed.
+    // Enums with data are kept as structs.
+    let enums = enum_descs
+        .iter()
+        .filter(|desc| desc.is_c_like())
+        .map(|desc| desc.name.clone())
+        .collect::<Vec<String>>();
+    // Transparent newtypes are replaced by their inner types when transferred.
+    for (_, inner) in newtypes.iter_mut() {
+        *inner = inner.clone().resolve_enum(&enums);
+    }
+    for trait_desc in trait_descs.iter_mut() {
+        for method in trait_desc.methods.iter_mut() {
+            method.return_type = method
+                .return_type
+                .clone()
+                .resolve_enum(&enums)
+                .resolve_newtype(&newtypes)?;
+            for arg in method.args.iter_mut() {
+                arg.ty = arg
+                    .ty
+                    .clone()
+                    .resolve_enum(&enums)
+                    .resolve_newtype(&newtypes)?;
+            }
+        }
+    }
+    for struct_desc in struct_descs.iter_mut() {
+        for field in struct_desc.fields.iter_mut() {
+            field.ty = field
+                .ty
+                .clone()
+                .resolve_enum(&enums)
+                .resolve_newtype(&newtypes)?;
+        }
+    }
+    for enum_desc in enum_descs.iter_mut() {
+        for variant in enum_desc.variants.iter_mut() {
+            for field in variant.fields.iter_mut() {
+                field.ty = field
+                    .ty
+                    .clone()
+                    .resolve_enum(&enums)
+                    .resolve_newtype(&newtypes)?;
+            }
+        }
+    }
+
+    resolve_objects(&mut trait_descs)?;
+    resolve_sequences(&mut trait_descs)?;
+    resolve_async(&mut trait_descs)?;
+
+    if !trait_descs.is_empty()
+        || !struct_descs.is_empty()
+        || !enum_descs.is_empty()
+        || !const_descs.is_empty()
+    {
         println!("final trait desc => {:#?}", trait_descs);
         Ok(ContractResult {
             traits: trait_descs,
             structs: struct_descs,
+            enums: enum_descs,
+            consts: const_descs,
         })
     } else {
         println!("Err: Can't find invalid trait and struct.");
@@ -174,6 +236,709 @@ pub(crate) fn parse_from_str(ctx: &ParseContext, src: &str) -> Result<ContractRe
     }
 }
 
+///
+/// Append the methods of the supertraits to the traits inheriting them, the supertraits of
+/// supertraits included. A method inherited through several paths is appended once.
+///
+fn resolve_supertraits(trait_descs: &mut [TraitDesc]) -> Result<()> {
+    let origins = trait_descs.to_vec();
+    let find = |name: &str| origins.iter().find(|desc| desc.name == name);
+    for desc in trait_descs.iter_mut() {
+        let mut pending = desc.supertraits.clone();
+        let mut visited = vec![];
+        while let Some(name) = pending.pop() {
+            if name == desc.name {
+                return Err(ParseError(format!(
+                    "Trait '{}' inherits itself through its supertraits.",
+                    &desc.name
+                ))
+                .into());
+            }
+            if visited.contains(&name) {
+                continue;
+            }
+            let supertrait = find(&name).ok_or_else(|| {
+                ParseError(format!(
+                    "Supertrait '{}' of trait '{}' should be a contract trait in the same file.",
+                    &name, &desc.name
+                ))
+            })?;
+            // Native code implements the supertraits through the inherited methods.
+            if supertrait.methods.is_empty() {
+                return Err(ParseError(format!(
+                    "Supertrait '{}' of trait '{}' should have methods.",
+                    &name, &desc.name
+                ))
+                .into());
+            }
+            for method in supertrait.methods.iter() {
+                let mut method = method.clone();
+                // `Self` of the supertrait is the trait inheriting it.
+                if let AstType::Struct(ref mut origin) = method.return_type {
+                    if origin.origin == supertrait.name {
+                        origin.origin = desc.name.clone();
+                    }
+                }
+                method.supertrait = Some(supertrait.name.clone());
+                desc.is_callback = desc.is_callback || !method.is_static;
+                desc.methods.push(method);
+            }
+            pending.extend(supertrait.supertraits.iter().rev().cloned());
+            visited.push(name);
+        }
+    }
+    Ok(())
+}
+
+///
+/// Traits with `self` methods are implemented by rust if they are only returned from traits implemented
+/// by rust, they are exported as objects. Otherwise they are callbacks implemented by native code,
+/// which are passed as arguments, or not referenced by the file at all.
+///
+fn resolve_objects(trait_descs: &mut [TraitDesc]) -> Result<()> {
+    // Traits with constructors returning `Self` are instance services, created by native code.
+    for desc in trait_descs.iter_mut() {
+        let name = desc.name.clone();
+        let returns_self = |method: &MethodDesc| matches!(method.return_type, AstType::Struct(ref origin) if origin.origin == name);
+        if let Some(method) = desc
+            .methods
+            .iter()
+            .find(|method| !method.is_static && returns_self(method))
+        {
+            return Err(ParseError(format!(
+                "Self is only supported in the return types of constructors, found in method '{}'.",
+                &method.name
+            ))
+            .into());
+        }
+        for method in desc.methods.iter_mut() {
+            if let AstType::Struct(origin) = method.return_type.clone() {
+                if origin.origin == name {
+                    method.return_type = AstType::Object(origin);
+                    desc.is_callback = false;
+                    desc.is_object = true;
+                }
+            }
+        }
+    }
+
+    let callback_names = |ty: &AstType| match ty {
+        AstType::Callback(ref origin) => Some(origin.origin.clone()),
+        _ => None,
+    };
+    let mut objects = trait_descs
+        .iter()
+        .filter(|desc| desc.is_callback)
+        .map(|desc| desc.name.clone())
+        .filter(|name| {
+            trait_descs.iter().all(|desc| {
+                desc.methods.iter().all(|method| {
+                    method
+                        .args
+                        .iter()
+                        .all(|arg| callback_names(&arg.ty).as_ref() != Some(name))
+                })
+            })
+        })
+        .filter(|name| {
+            trait_descs.iter().any(|desc| {
+                desc.methods
+                    .iter()
+                    .any(|method| callback_names(&method.return_type).as_ref() == Some(name))
+            })
+        })
+        .collect::<Vec<String>>();
+
+    // Objects returned from callbacks are implemented by native code, which makes them callbacks too.
+    loop {
+        let callbacks = trait_descs
+            .iter()
+            .filter(|desc| desc.is_callback && !objects.contains(&desc.name))
+            .collect::<Vec<&TraitDesc>>();
+        let count = objects.len();
+        objects.retain(|name| {
+            callbacks.iter().all(|desc| {
+                desc.methods
+                    .iter()
+                    .all(|method| callback_names(&method.return_type).as_ref() != Some(name))
+            })
+        });
+        if objects.len() == count {
+            break;
+        }
+    }
+
+    for desc in trait_descs.iter_mut() {
+        if objects.contains(&desc.name) {
+            desc.is_callback = false;
+            desc.is_object = true;
+        }
+        for method in desc.methods.iter_mut() {
+            method.return_type = method.return_type.clone().resolve_object(&objects);
+        }
+    }
+
+    for desc in trait_descs.iter() {
+        if desc.is_object {
+            check_object(desc)?;
+        } else if desc.is_callback {
+            check_callback(desc)?;
+        }
+        check_supertraits(desc, trait_descs)?;
+    }
+    Ok(())
+}
+
+///
+/// Add the objects pulling the items of the iterators and streams returned by methods. They are
+/// declared by the bridge with one method `next_item(&self) -> Option<T>`, which is async for streams,
+/// and the methods return them instead.
+///
+fn resolve_sequences(trait_descs: &mut Vec<TraitDesc>) -> Result<()> {
+    let mut sequences = vec![];
+    for desc in trait_descs.iter_mut() {
+        let (mod_name, mod_path, crate_name) = (
+            desc.mod_name.clone(),
+            desc.mod_path.clone(),
+            desc.crate_name.clone(),
+        );
+        let trait_name = desc.name.clone();
+        let is_callback = desc.is_callback;
+        for method in desc.methods.iter_mut() {
+            let kind = match method.sequence {
+                Some(kind) => kind,
+                None => continue,
+            };
+            if is_callback || method.is_async {
+                return Err(ParseError(format!(
+                    "Iterator and Stream are only supported in the return types of services and objects, found in method '{}' of trait '{}'.",
+                    &method.name, &trait_name
+                ))
+                .into());
+            }
+
+            let name = method.sequence_name(&trait_name);
+            sequences.push(TraitDesc {
+                name: name.clone(),
+                ty: "trait".to_string(),
+                mod_name: mod_name.clone(),
+                mod_path: mod_path.clone(),
+                crate_name: crate_name.clone(),
+                is_callback: false,
+                is_object: true,
+                is_completion: false,
+                sequence: Some(kind),
+                supertraits: vec![],
+                methods: vec![MethodDesc {
+                    name: "next_item".to_string(),
+                    return_type: method.return_type.clone(),
+                    args: vec![],
+                    swallow_self: false,
+                    mut_self: false,
+                    is_static: false,
+                    supertrait: None,
+                    has_default: false,
+                    is_async: kind == SequenceKind::Stream,
+                    sequence: None,
+                    dispatch: None,
+                }],
+            });
+            method.return_type = AstType::Object(CustomType {
+                mod_name: mod_name.clone(),
+                origin: name,
+            });
+        }
+    }
+
+    for sequence in sequences.iter() {
+        if trait_descs.iter().any(|desc| desc.name == sequence.name) {
+            return Err(ParseError(format!(
+                "Trait '{}' conflicts with the object pulling the items of a method returning Iterator or Stream.",
+                &sequence.name
+            ))
+            .into());
+        }
+    }
+    trait_descs.extend(sequences);
+    Ok(())
+}
+
+///
+/// Add the completion callbacks of async methods, which receive the result by `on_complete`,
+/// the error by `on_error` for `Result` returns, and the message by `on_panic` if the future panics,
+/// so every call completes. The future is run by the executor of the bridge,
+/// so only static methods of normal traits with owned arguments can be async.
+///
+fn resolve_async(trait_descs: &mut Vec<TraitDesc>) -> Result<()> {
+    let mut completions = vec![];
+    for desc in trait_descs.iter() {
+        for method in desc.methods.iter().filter(|method| method.is_async) {
+            // `next_item` of the objects pulling streams is the only async method of objects.
+            if (desc.is_callback || desc.is_object || !method.is_static) && desc.sequence.is_none()
+            {
+                return Err(ParseError(format!(
+                    "Async method '{}' of trait '{}' is not supported, only static methods of normal traits can be async.",
+                    &method.name, &desc.name
+                ))
+                .into());
+            }
+            if method.args.iter().any(|arg| arg.borrowed) {
+                return Err(ParseError(format!(
+                    "Borrowed argument is not supported in async method '{}', the future should own its arguments.",
+                    &method.name
+                ))
+                .into());
+            }
+            let ok_type = method.return_type.ok_type();
+            if matches!(
+                ok_type,
+                AstType::Callback(_) | AstType::Object(_) | AstType::Buffer(_)
+            ) {
+                return Err(ParseError(format!(
+                    "{} is not supported in the return type of async method '{}'.",
+                    ok_type.origin(),
+                    &method.name
+                ))
+                .into());
+            }
+
+            let name = method.completion_name(desc);
+            if trait_descs.iter().any(|each| each.name == name) {
+                return Err(ParseError(format!(
+                    "Trait '{}' conflicts with the completion callback of async method '{}'.",
+                    &name, &method.name
+                ))
+                .into());
+            }
+            let callback_method = |name: &str, args: Vec<ArgDesc>| MethodDesc {
+                name: name.to_string(),
+                return_type: AstType::Void,
+                args,
+                swallow_self: false,
+                mut_self: false,
+                is_static: false,
+                supertrait: None,
+                has_default: false,
+                is_async: false,
+                sequence: None,
+                dispatch: None,
+            };
+            let mut methods = vec![];
+            let value = match ok_type {
+                AstType::Void => vec![],
+                ty => vec![ArgDesc {
+                    name: "value".to_string(),
+                    ty,
+                    borrowed: false,
+                }],
+            };
+            methods.push(callback_method("on_complete", value));
+            if let Some(err) = method.return_type.err_type() {
+                let error = ArgDesc {
+                    name: "error".to_string(),
+                    ty: AstType::from(err),
+                    borrowed: false,
+                };
+                methods.push(callback_method("on_error", vec![error]));
+            }
+            let message = ArgDesc {
+                name: "message".to_string(),
+                ty: AstType::String,
+                borrowed: false,
+            };
+            methods.push(callback_method("on_panic", vec![message]));
+            completions.push(TraitDesc {
+                name,
+                ty: "trait".to_string(),
+                mod_name: desc.mod_name.clone(),
+                mod_path: desc.mod_path.clone(),
+                crate_name: desc.crate_name.clone(),
+                is_callback: true,
+                is_object: false,
+                is_completion: true,
+                sequence: None,
+                supertraits: vec![],
+                methods,
+            });
+        }
+    }
+    trait_descs.extend(completions);
+    Ok(())
+}
+
+///
+/// Java interfaces and Swift protocols of services and callbacks extend the ones of their supertraits,
+/// so the supertraits should be of the same kind. Objects only have the inherited methods.
+///
+fn check_supertraits(desc: &TraitDesc, trait_descs: &[TraitDesc]) -> Result<()> {
+    if desc.is_object {
+        return Ok(());
+    }
+    let kind = if desc.is_callback {
+        "callback"
+    } else {
+        "service"
+    };
+    for supertrait in trait_descs
+        .iter()
+        .filter(|each| desc.supertraits.contains(&each.name))
+    {
+        if supertrait.is_object || supertrait.is_callback != desc.is_callback {
+            return Err(ParseError(format!(
+                "Supertrait '{}' of {} '{}' should be a {} too.",
+                &supertrait.name, kind, &desc.name, kind
+            ))
+            .into());
+        }
+    }
+    Ok(())
+}
+
+///
+/// Objects are held by native code through handles, every method is called on the handle.
+/// Instance services also have constructors, and their methods can take `&mut self`.
+///
+fn check_object(desc: &TraitDesc) -> Result<()> {
+    if desc.has_constructor() {
+        if let Some(method) = desc.methods.iter().find(|method| {
+            (method.is_static && !desc.is_constructor(method)) || method.swallow_self
+        }) {
+            return Err(ParseError(format!(
+                "Method '{}' of instance service '{}' should take &self or &mut self, or return Self as a constructor.",
+                &method.name, &desc.name
+            ))
+            .into());
+        }
+        return Ok(());
+    }
+
+    if let Some(method) = desc
+        .methods
+        .iter()
+        .find(|method| method.is_static || method.swallow_self || method.mut_self)
+    {
+        return Err(ParseError(format!(
+            "Method '{}' of object trait '{}' should take &self.",
+            &method.name, &desc.name
+        ))
+        .into());
+    }
+    Ok(())
+}
+
+fn check_callback(desc: &TraitDesc) -> Result<()> {
+    // Buffers are backed by rust memory, so they can only be passed from rust to native.
+    if let Some(method) = desc
+        .methods
+        .iter()
+        .find(|method| matches!(method.return_type, AstType::Buffer(_)))
+    {
+        return Err(ParseError(format!(
+            "Bytes is only supported in return types of traits, found in method '{}'.",
+            &method.name
+        ))
+        .into());
+    }
+
+    // Cancellation tokens are created by native code, so they can only be passed from native to rust.
+    if let Some(method) = desc.methods.iter().find(|method| {
+        method
+            .args
+            .iter()
+            .any(|arg| matches!(arg.ty, AstType::Cancellation(_)))
+    }) {
+        return Err(ParseError(format!(
+            "CancellationToken is only supported in arguments of traits implemented by rust, found in method '{}'.",
+            &method.name
+        ))
+        .into());
+    }
+
+    if let Some(method) = desc
+        .methods
+        .iter()
+        .find(|method| method.return_type.err_type().is_some())
+    {
+        return Err(ParseError(format!(
+            "Result is not supported in callback method '{}'.",
+            &method.name
+        ))
+        .into());
+    }
+
+    // Default methods are run by rust for the native callback, which can't hand a callback back.
+    if let Some(method) = desc
+        .methods
+        .iter()
+        .find(|method| method.has_default && matches!(method.return_type, AstType::Callback(_)))
+    {
+        return Err(ParseError(format!(
+            "Callback is not supported in the return type of default method '{}'.",
+            &method.name
+        ))
+        .into());
+    }
+
+    // Arguments of callbacks are copied to the native side, so they should be owned.
+    if let Some(method) = desc
+        .methods
+        .iter()
+        .find(|method| method.args.iter().any(|arg| arg.borrowed))
+    {
+        return Err(ParseError(format!(
+            "Borrowed argument is not supported in callback method '{}'.",
+            &method.name
+        ))
+        .into());
+    }
+    Ok(())
+}
+
+///
+/// Parse enums, the discriminant is the previous one plus 1 if not specified.
+/// For enums with data, the discriminant is used as the tag of the variant.
+///
+fn parse_enum(ctx: &ParseContext, enum_inner: &syn::ItemEnum) -> Result<EnumDesc> {
+    let enum_name = enum_inner.ident.to_string();
+    let mut variants = vec![];
+    let mut next_value = 0;
+    for variant in enum_inner.variants.iter() {
+        let value = match variant.discriminant {
+            Some((_, ref expr)) => parse_discriminant(expr).ok_or_else(|| {
+                ParseError(format!(
+                    "Only integer discriminant is supported in {}::{}.",
+                    &enum_name, &variant.ident
+                ))
+            })?,
+            None => next_value,
+        };
+        next_value = value.wrapping_add(1);
+
+        variants.push(VariantDesc {
+            name: variant.ident.to_string(),
+            value,
+            is_tuple: matches!(variant.fields, syn::Fields::Unnamed(_)),
+            fields: parse_fields(ctx, &variant.fields)?,
+        });
+    }
+
+    Ok(EnumDesc {
+        name: enum_name,
+        ty: "enum".to_string(),
+        mod_name: ctx.mod_name.clone(),
+        mod_path: ctx.mod_path.clone(),
+        crate_name: ctx.crate_name.clone(),
+        variants,
+    })
+}
+
+///
+/// Whether the struct has `#[repr(transparent)]`.
+///
+fn is_transparent(attrs: &[syn::Attribute]) -> bool {
+    attrs.iter().any(|attr| match attr.parse_meta() {
+        Ok(syn::Meta::List(ref list)) if list.path.is_ident("repr") => {
+            list.nested.iter().any(|nested| match nested {
+                syn::NestedMeta::Meta(syn::Meta::Path(ref path)) => path.is_ident("transparent"),
+                _ => false,
+            })
+        }
+        _ => false,
+    })
+}
+
+///
+/// Parse the inner type of a transparent newtype like `struct UserId(i64)`.
+///
+fn parse_newtype(ctx: &ParseContext, struct_inner: &syn::ItemStruct) -> Result<AstBaseType> {
+    let fields = &struct_inner.fields;
+    if let Some(syn::Type::Path(ref type_path)) = fields.iter().next().map(|field| &field.ty) {
+        match parse_base_ast(ctx, type_path) {
+            AstBaseType::Callback(_) | AstBaseType::Void => {}
+            inner if fields.len() == 1 => return Ok(inner),
+            _ => {}
+        }
+    }
+
+    Err(ParseError(format!(
+        "Transparent newtype {} should have exactly one field of a supported type.",
+        &struct_inner.ident
+    ))
+    .into())
+}
+
+///
+/// Parse the fields of a struct or an enum variant, unnamed fields are named by their index like `_0`.
+///
+fn parse_fields(ctx: &ParseContext, fields: &syn::Fields) -> Result<Vec<ArgDesc>> {
+    let mut field_descs = vec![];
+    for (index, field) in fields.iter().enumerate() {
+        let field_name = match field.ident {
+            Some(ref value) => value.to_owned().to_string(),
+            _ => format!("_{}", index),
+        };
+
+        let field_ty;
+        match field.ty {
+            syn::Type::Path(ref type_path) => {
+                let segments = &(type_path.path.segments);
+                let ident = (&segments[segments.len() - 1].ident).to_string();
+                if ident == "Box" {
+                    println!("found Box argument.");
+                    field_ty = Some(parse_boxed_ast(ctx, type_path));
+                } else if ident == "Vec" {
+                    println!("found Vec argument.");
+                    field_ty = Some(parse_vec_ast(ctx, type_path));
+                } else if ident == "Option" {
+                    println!("found Option argument.");
+                    field_ty = Some(parse_option_ast(ctx, type_path)?);
+                } else if ident == "HashMap" || ident == "BTreeMap" {
+                    println!("found Map argument.");
+                    field_ty = Some(parse_map_ast(ctx, type_path)?);
+                } else {
+                    // normal arguments
+                    field_ty = Some(AstType::new(&ident, &ident, ctx));
+                    println!("found args type => {:?}", ident);
+                }
+            }
+            syn::Type::Tuple(ref tuple) if !tuple.elems.is_empty() => {
+                return Err(ParseError(format!(
+                    "Tuple field {} is not supported, use a struct instead.",
+                    &field_name
+                ))
+                .into());
+            }
+            _ => {
+                field_ty = Some(AstType::Void);
+            }
+        };
+        if let Some(AstType::Buffer(ref origin)) = field_ty {
+            return Err(ParseError(format!(
+                "{} field {} is not supported, it is only supported in return types.",
+                origin, &field_name
+            ))
+            .into());
+        }
+        if let Some(AstType::Cancellation(ref origin)) = field_ty {
+            return Err(ParseError(format!(
+                "{} field {} is not supported, it is only supported in arguments.",
+                origin, &field_name
+            ))
+            .into());
+        }
+        let field_desc = ArgDesc {
+            name: field_name,
+            ty: field_ty.unwrap(),
+            borrowed: false,
+        };
+        field_descs.push(field_desc);
+    }
+    Ok(field_descs)
+}
+
+///
+/// Parse a `pub const` whose value is a literal, negative numbers included.
+/// The unsupported ones are skipped by the caller with a warning.
+///
+fn parse_const(ctx: &ParseContext, const_inner: &syn::ItemConst) -> Result<ConstDesc> {
+    let name = const_inner.ident.to_string();
+    let unsupported = || {
+        ParseError(format!(
+            "Constant {} is skipped, only literals of integers, floats, bool and &str and the MIN and MAX of integers can be exported.",
+            &name
+        ))
+    };
+
+    let ty = match *const_inner.ty {
+        syn::Type::Path(ref type_path) if type_path.path.segments.len() == 1 => {
+            let ident = type_path.path.segments[0].ident.to_string();
+            AstType::new(&ident, &ident, ctx)
+        }
+        syn::Type::Reference(ref reference) => match *reference.elem {
+            syn::Type::Path(ref type_path) if type_path.path.is_ident("str") => AstType::String,
+            _ => return Err(unsupported().into()),
+        },
+        _ => return Err(unsupported().into()),
+    };
+
+    let value = match (&ty, parse_const_value(&const_inner.expr)) {
+        (
+            AstType::Byte(_) | AstType::Short(_) | AstType::Int(_) | AstType::Long(_),
+            Some(value @ ConstValue::Int(_)),
+        )
+        | (AstType::Float(_) | AstType::Double(_), Some(value @ ConstValue::Float(_)))
+        | (AstType::Boolean, Some(value @ ConstValue::Bool(_)))
+        | (AstType::String, Some(value @ ConstValue::String(_))) => value,
+        _ => return Err(unsupported().into()),
+    };
+
+    Ok(ConstDesc {
+        name,
+        ty,
+        mod_name: ctx.mod_name.clone(),
+        mod_path: ctx.mod_path.clone(),
+        crate_name: ctx.crate_name.clone(),
+        value,
+    })
+}
+
+///
+/// Evaluate a literal, a negated one or the MIN and MAX of integer types like `i32::MIN`.
+///
+fn parse_const_value(expr: &syn::Expr) -> Option<ConstValue> {
+    match expr {
+        syn::Expr::Lit(syn::ExprLit { ref lit, .. }) => match lit {
+            syn::Lit::Int(ref value) => value.base10_parse::<i128>().ok().map(ConstValue::Int),
+            syn::Lit::Float(ref value) => value.base10_parse::<f64>().ok().map(ConstValue::Float),
+            syn::Lit::Bool(ref value) => Some(ConstValue::Bool(value.value)),
+            syn::Lit::Str(ref value) => Some(ConstValue::String(value.value())),
+            _ => None,
+        },
+        syn::Expr::Unary(syn::ExprUnary {
+            op: syn::UnOp::Neg(_),
+            ref expr,
+            ..
+        }) => match parse_const_value(expr) {
+            Some(ConstValue::Int(value)) => Some(ConstValue::Int(-value)),
+            Some(ConstValue::Float(value)) => Some(ConstValue::Float(-value)),
+            _ => None,
+        },
+        syn::Expr::Paren(syn::ExprParen { ref expr, .. }) => parse_const_value(expr),
+        syn::Expr::Path(syn::ExprPath {
+            qself: None,
+            ref path,
+            ..
+        }) if path.segments.len() == 2 => {
+            let ty = path.segments[0].ident.to_string();
+            let bound = path.segments[1].ident.to_string();
+            let (min, max) = match ty.as_ref() {
+                "i8" => (i8::MIN as i128, i8::MAX as i128),
+                "i16" => (i16::MIN as i128, i16::MAX as i128),
+                "i32" => (i32::MIN as i128, i32::MAX as i128),
+                "i64" | "isize" => (i64::MIN as i128, i64::MAX as i128),
+                "u8" => (0, u8::MAX as i128),
+                "u16" => (0, u16::MAX as i128),
+                "u32" => (0, u32::MAX as i128),
+                "u64" | "usize" => (0, u64::MAX as i128),
+                _ => return None,
+            };
+            match bound.as_ref() {
+                "MIN" => Some(ConstValue::Int(min)),
+                "MAX" => Some(ConstValue::Int(max)),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+fn parse_discriminant(expr: &syn::Expr) -> Option<i32> {
+    match parse_const_value(expr) {
+        Some(ConstValue::Int(value)) => i32::try_from(value).ok(),
+        _ => None,
+    }
+}
+
 ///
 /// Loop all the methods
 ///
@@ -187,14 +952,23 @@ fn parse_methods(ctx: &ParseContext, items: &[syn::TraitItem]) -> Result<(Vec<Me
 
             println!("found method => {}", method_inner.sig.ident);
 
-            let return_type = parse_return_type(ctx, &method_inner.sig.output)?;
+            // The object pulling the items of a sequence returns them as `Option<T>`, see `resolve_sequences`.
+            let sequence = parse_sequence(ctx, &method_name, &method_inner.sig.output)?;
+            let return_type = match sequence {
+                Some((_, ref item)) => AstType::Optional(item.clone()),
+                None => parse_return_type(ctx, &method_inner.sig.output)?,
+            };
 
             // arguments
             let mut swallow_self = false;
+            let mut mut_self = false;
+            let mut is_static = true;
             for input in method_inner.sig.inputs.iter() {
                 match input {
                     syn::FnArg::Receiver(ref arg) => {
                         swallow_self = arg.reference.is_none();
+                        mut_self = arg.reference.is_some() && arg.mutability.is_some();
+                        is_static = false;
                         is_callback = true;
                         continue;
                     }
@@ -210,11 +984,44 @@ fn parse_methods(ctx: &ParseContext, items: &[syn::TraitItem]) -> Result<(Vec<Me
                 return_type,
                 args,
                 swallow_self,
+                mut_self,
+                is_static,
+                supertrait: None,
+                has_default: method_inner.default.is_some(),
+                is_async: method_inner.sig.asyncness.is_some(),
+                sequence: sequence.map(|(kind, _)| kind),
+                dispatch: None,
             };
             method_descs.push(method_desc);
         }
     }
 
+    // Buffers are backed by rust memory, so they can only be passed from rust to native.
+    if let Some(method) = method_descs.iter().find(|method| {
+        method
+            .args
+            .iter()
+            .any(|arg| matches!(arg.ty, AstType::Buffer(_)))
+    }) {
+        return Err(ParseError(format!(
+            "Bytes is only supported in return types of traits, found in method '{}'.",
+            &method.name
+        ))
+        .into());
+    }
+
+    // Cancellation tokens are created by native code, so they can only be passed from native to rust.
+    if let Some(method) = method_descs
+        .iter()
+        .find(|method| matches!(method.return_type, AstType::Cancellation(_)))
+    {
+        return Err(ParseError(format!(
+            "CancellationToken is only supported in arguments of traits implemented by rust, found in method '{}'.",
+            &method.name
+        ))
+        .into());
+    }
+
     if !method_descs.is_empty() {
         Ok((method_descs, is_callback))
     } else {
@@ -222,6 +1029,113 @@ fn parse_methods(ctx: &ParseContext, items: &[syn::TraitItem]) -> Result<(Vec<Me
     }
 }
 
+///
+/// Parse the return types like `Box<dyn Iterator<Item = T> + Send>`, `Box<dyn Stream<Item = T> + Send>`
+/// and `Pin<Box<dyn Stream<Item = T> + Send>>`, whose items are pulled by native code.
+///
+fn parse_sequence(
+    ctx: &ParseContext,
+    method_name: &str,
+    output: &syn::ReturnType,
+) -> Result<Option<(SequenceKind, AstBaseType)>> {
+    let mut ty = match output {
+        syn::ReturnType::Type(_, ref boxed) => &**boxed,
+        syn::ReturnType::Default => return Ok(None),
+    };
+    if let syn::Type::Path(ref type_path) = ty {
+        if parse_ident_in_path(ctx, type_path) == "Pin" {
+            if let Some(inner) = parse_generic_type(type_path) {
+                ty = inner;
+            }
+        }
+    }
+    let trait_obj = match ty {
+        syn::Type::Path(ref type_path) if parse_ident_in_path(ctx, type_path) == "Box" => {
+            match parse_generic_type(type_path) {
+                Some(syn::Type::TraitObject(ref trait_obj)) => trait_obj,
+                _ => return Ok(None),
+            }
+        }
+        _ => return Ok(None),
+    };
+
+    let mut sequence = None;
+    let mut send = false;
+    for bound in trait_obj.bounds.iter() {
+        if let TypeParamBound::Trait(ref trait_bound) = bound {
+            let segments = &trait_bound.path.segments;
+            let segment = &segments[segments.len() - 1];
+            let kind = match segment.ident.to_string().as_str() {
+                "Iterator" => SequenceKind::Iterator,
+                "Stream" => SequenceKind::Stream,
+                "Send" => {
+                    send = true;
+                    continue;
+                }
+                _ => return Ok(None),
+            };
+            let item = match segment.arguments {
+                syn::PathArguments::AngleBracketed(ref generics) => {
+                    generics.args.iter().find_map(|arg| match arg {
+                        syn::GenericArgument::Binding(ref binding) if binding.ident == "Item" => {
+                            Some(&binding.ty)
+                        }
+                        _ => None,
+                    })
+                }
+                _ => None,
+            };
+            let item = match item {
+                Some(syn::Type::Path(ref type_path)) => parse_base_ast(ctx, type_path),
+                _ => {
+                    return Err(ParseError(format!(
+                        "Can't parse the item type of {:?} returned by method '{}'.",
+                        kind, method_name
+                    ))
+                    .into())
+                }
+            };
+            // `None` is the end of the items, so they can't be optional.
+            if matches!(
+                item,
+                AstBaseType::Optional(_) | AstBaseType::Callback(_) | AstBaseType::Void
+            ) {
+                return Err(ParseError(format!(
+                    "{} is not supported in the items of {:?} returned by method '{}'.",
+                    item.origin(),
+                    kind,
+                    method_name
+                ))
+                .into());
+            }
+            sequence = Some((kind, item));
+        }
+    }
+
+    match sequence {
+        Some((kind, _)) if !send => Err(ParseError(format!(
+            "{:?} returned by method '{}' is pulled from other threads, it should be Send like Box<dyn {:?}<Item = T> + Send>.",
+            kind, method_name, kind
+        ))
+        .into()),
+        _ => Ok(sequence),
+    }
+}
+
+///
+/// The first generic argument of a path, like `Box<dyn Trait>` in `Pin<Box<dyn Trait>>`.
+///
+fn parse_generic_type(type_path: &TypePath) -> Option<&syn::Type> {
+    let segments = &(type_path.path.segments);
+    if let syn::PathArguments::AngleBracketed(ref generics) = segments[segments.len() - 1].arguments
+    {
+        if let Some(syn::GenericArgument::Type(ref ty)) = generics.args.first() {
+            return Some(ty);
+        }
+    }
+    None
+}
+
 ///
 /// parse return type
 ///
@@ -242,11 +1156,24 @@ fn parse_return_type(ctx: &ParseContext, output: &syn::ReturnType) -> Result<Ast
                 } else if *ident == "Box" {
                     println!("found Box return type.");
                     Ok(parse_boxed_ast(ctx, type_path))
+                } else if *ident == "Option" {
+                    println!("found Option return type.");
+                    parse_option_ast(ctx, type_path)
+                } else if *ident == "Result" {
+                    println!("found Result return type.");
+                    parse_result_ast(ctx, type_path)
+                } else if *ident == "HashMap" || *ident == "BTreeMap" {
+                    println!("found Map return type.");
+                    parse_map_ast(ctx, type_path)
                 } else {
                     let origin = ident.to_string();
                     Ok(AstType::new(&origin, &origin, ctx))
                 };
             }
+            if let syn::Type::Tuple(ref tuple) = path {
+                println!("found Tuple return type.");
+                return parse_tuple_ast(ctx, tuple);
+            }
         }
         syn::ReturnType::Default => return Ok(AstType::Void),
     }
@@ -259,14 +1186,30 @@ fn parse_return_type(ctx: &ParseContext, output: &syn::ReturnType) -> Result<Ast
 ///
 fn parse_one_arg(ctx: &ParseContext, input: &syn::FnArg) -> Result<ArgDesc> {
     let mut arg_name: Option<String> = Some("".to_owned());
-    let mut arg_type: Option<AstType> = Some(AstType::Void);
+    let mut arg_type: Option<AstType> = None;
+    let mut borrowed = false;
     if let syn::FnArg::Typed(ref arg) = input {
         if let syn::Pat::Ident(ref pat_ident) = *(arg.pat) {
             arg_name = Some(pat_ident.ident.to_string());
             println!("found arg pat = {:?}", pat_ident.ident.to_string());
         }
 
-        if let syn::Type::Path(ref type_path) = *(arg.ty) {
+        // Borrowed arguments are parsed as the owned types, `&str` as `String` and `&[T]` as `Vec<T>`.
+        let mut ty = &*(arg.ty);
+        if let syn::Type::Reference(ref reference) = ty {
+            if reference.mutability.is_some() {
+                return Err(ParseError(format!(
+                    "Mutable reference argument {} is not supported.",
+                    arg_name.unwrap_or_default()
+                ))
+                .into());
+            }
+            println!("found borrowed argument.");
+            borrowed = true;
+            ty = &*(reference.elem);
+        }
+
+        if let syn::Type::Path(ref type_path) = ty {
             let segments = &(type_path.path.segments);
             let ident = (&segments[segments.len() - 1].ident).to_string();
             if ident == "Box" {
@@ -275,37 +1218,236 @@ fn parse_one_arg(ctx: &ParseContext, input: &syn::FnArg) -> Result<ArgDesc> {
             } else if ident == "Vec" {
                 println!("found Vec argument.");
                 arg_type = Some(parse_vec_ast(ctx, type_path));
+            } else if ident == "Option" {
+                println!("found Option argument.");
+                arg_type = Some(parse_option_ast(ctx, type_path)?);
+            } else if ident == "HashMap" || ident == "BTreeMap" {
+                println!("found Map argument.");
+                arg_type = Some(parse_map_ast(ctx, type_path)?);
             } else {
                 // normal arguments
                 arg_type = Some(AstType::new(&ident, &ident, ctx));
                 println!("found args type => {:?}", ident);
             }
         }
+
+        if let syn::Type::Slice(ref slice) = ty {
+            match *(slice.elem) {
+                syn::Type::Path(ref type_path) => {
+                    println!("found slice argument.");
+                    arg_type = Some(AstType::Vec(parse_base_ast(ctx, type_path)));
+                }
+                ref elem => {
+                    return Err(ParseError(format!(
+                        "Slice of {} in argument {} is not supported.",
+                        quote::ToTokens::to_token_stream(elem),
+                        arg_name.unwrap_or_default()
+                    ))
+                    .into())
+                }
+            }
+        }
+
+        if let syn::Type::Tuple(ref tuple) = ty {
+            println!("found Tuple argument.");
+            arg_type = Some(parse_tuple_ast(ctx, tuple)?);
+        }
     }
 
     match (arg_name, arg_type) {
         (Some(arg_name), Some(arg_type)) => Ok(ArgDesc {
             name: arg_name,
             ty: arg_type,
+            borrowed,
         }),
+        (Some(arg_name), None) => Err(ParseError(format!(
+            "The type of argument {} is not supported.",
+            arg_name
+        ))
+        .into()),
         _ => Err(ParseError("parse argments error!".to_string()).into()),
     }
 }
 
 fn parse_vec_ast(ctx: &ParseContext, type_path: &TypePath) -> AstType {
-    let mut arg_type: AstType = AstType::Void;
+    match parse_generic_base(ctx, type_path) {
+        Some(base) => {
+            println!("found vec types = {:?})", base);
+            AstType::Vec(base)
+        }
+        None => AstType::Void,
+    }
+}
+
+fn parse_option_ast(ctx: &ParseContext, type_path: &TypePath) -> Result<AstType> {
+    match parse_generic_base(ctx, type_path) {
+        // `Some(None)` can't be told from `None` in json and in the native languages.
+        Some(AstBaseType::Optional(_)) => Err(ParseError(format!(
+            "Option directly inside Option is not supported: {:?}",
+            type_path
+        ))
+        .into()),
+        Some(base) => {
+            println!("found option types = {:?})", base);
+            Ok(AstType::Optional(base))
+        }
+        None => Ok(AstType::Void),
+    }
+}
+
+///
+/// Parse the first generic argument, like `T` in `Vec<T>`.
+///
+fn parse_generic_base(ctx: &ParseContext, type_path: &TypePath) -> Option<AstBaseType> {
+    let segments = &(type_path.path.segments);
+    let angle_bracketed = &segments[segments.len() - 1].arguments;
+    if let syn::PathArguments::AngleBracketed(t) = angle_bracketed {
+        if let syn::GenericArgument::Type(syn::Type::Path(ref type_path)) = &t.args[0] {
+            return Some(parse_base_ast(ctx, type_path));
+        }
+    }
+
+    None
+}
+
+///
+/// Parse the type inside a collection, `Vec` and `Option` can be nested in any depth.
+///
+fn parse_base_ast(ctx: &ParseContext, type_path: &TypePath) -> AstBaseType {
+    let ident = parse_ident_in_path(ctx, type_path);
+    let inner = if ident == "Vec" || ident == "Option" {
+        parse_generic_base(ctx, type_path)
+    } else {
+        None
+    };
+
+    match (ident.as_str(), inner) {
+        ("Vec", Some(inner)) => AstBaseType::Vec(Box::new(inner)),
+        ("Option", Some(inner)) => AstBaseType::Optional(Box::new(inner)),
+        _ => AstBaseType::new(&ident, &ident, ctx),
+    }
+}
+
+///
+/// Parse tuples like `(i32, String)`, the empty tuple is void.
+///
+fn parse_tuple_ast(ctx: &ParseContext, tuple: &syn::TypeTuple) -> Result<AstType> {
+    let mut elems = vec![];
+    for elem in tuple.elems.iter() {
+        match elem {
+            syn::Type::Path(ref type_path) => match parse_base_ast(ctx, type_path) {
+                AstBaseType::Callback(_) | AstBaseType::Void => break,
+                base => elems.push(base),
+            },
+            _ => break,
+        }
+    }
+
+    if elems.len() != tuple.elems.len() {
+        return Err(ParseError(format!(
+            "can't parse tuple type, callbacks and nested tuples are not supported: {:?}",
+            tuple
+        ))
+        .into());
+    }
+
+    println!("found tuple types = {:?}", elems);
+    if elems.is_empty() {
+        Ok(AstType::Void)
+    } else {
+        Ok(AstType::Tuple(elems))
+    }
+}
+
+fn parse_result_ast(ctx: &ParseContext, type_path: &TypePath) -> Result<AstType> {
     let segments = &(type_path.path.segments);
     let angle_bracketed = &segments[segments.len() - 1].arguments;
     if let syn::PathArguments::AngleBracketed(t) = angle_bracketed {
-        let arg = &t.args[0];
-        if let syn::GenericArgument::Type(syn::Type::Path(ref type_path)) = arg {
-            println!("found vec types = {:?})", type_path);
-            let ident = parse_ident_in_path(ctx, type_path);
-            arg_type = AstType::Vec(AstBaseType::new(&ident, &ident.to_string(), ctx));
+        if t.args.len() == 2 {
+            let ok_type = match &t.args[0] {
+                syn::GenericArgument::Type(syn::Type::Tuple(ref tuple))
+                    if tuple.elems.is_empty() =>
+                {
+                    Some(AstBaseType::Void)
+                }
+                syn::GenericArgument::Type(syn::Type::Path(ref type_path)) => {
+                    Some(parse_base_ast(ctx, type_path))
+                }
+                _ => None,
+            };
+
+            let err_type = match &t.args[1] {
+                syn::GenericArgument::Type(syn::Type::Path(ref type_path)) => {
+                    let ident = parse_ident_in_path(ctx, type_path);
+                    match AstBaseType::new(&ident, &ident, ctx) {
+                        err @ AstBaseType::String | err @ AstBaseType::Struct(_) => Some(err),
+                        _ => None,
+                    }
+                }
+                _ => None,
+            };
+
+            println!("found result types = {:?}, {:?}", ok_type, err_type);
+            if let (Some(ok_type), Some(err_type)) = (ok_type, err_type) {
+                return Ok(AstType::Result(ok_type, err_type));
+            }
+        }
+    }
+
+    Err(ParseError(format!(
+        "can't parse result type, the error type should be String or a struct: {:?}",
+        type_path
+    ))
+    .into())
+}
+
+///
+/// Parse `HashMap<K, V>` and `BTreeMap<K, V>`, keys can only be integers, bool or String.
+///
+fn parse_map_ast(ctx: &ParseContext, type_path: &TypePath) -> Result<AstType> {
+    let segments = &(type_path.path.segments);
+    let map_ident = segments[segments.len() - 1].ident.to_string();
+    let angle_bracketed = &segments[segments.len() - 1].arguments;
+    if let syn::PathArguments::AngleBracketed(t) = angle_bracketed {
+        if t.args.len() == 2 {
+            let key_type = match &t.args[0] {
+                syn::GenericArgument::Type(syn::Type::Path(ref type_path)) => {
+                    let ident = parse_ident_in_path(ctx, type_path);
+                    match AstBaseType::new(&ident, &ident, ctx) {
+                        key @ AstBaseType::Byte(_)
+                        | key @ AstBaseType::Short(_)
+                        | key @ AstBaseType::Int(_)
+                        | key @ AstBaseType::Long(_)
+                        | key @ AstBaseType::Boolean
+                        | key @ AstBaseType::String => Some(key),
+                        _ => None,
+                    }
+                }
+                _ => None,
+            };
+
+            let value_type = match &t.args[1] {
+                syn::GenericArgument::Type(syn::Type::Path(ref type_path)) => {
+                    match parse_base_ast(ctx, type_path) {
+                        AstBaseType::Callback(_) => None,
+                        value => Some(value),
+                    }
+                }
+                _ => None,
+            };
+
+            println!("found map types = {:?}, {:?}", key_type, value_type);
+            if let (Some(key_type), Some(value_type)) = (key_type, value_type) {
+                return Ok(AstType::Map(map_ident, key_type, value_type));
+            }
         }
     }
 
-    arg_type
+    Err(ParseError(format!(
+        "can't parse map type, the key type should be an integer, bool or String: {:?}",
+        type_path
+    ))
+    .into())
 }
 
 fn parse_boxed_ast(ctx: &ParseContext, type_path: &TypePath) -> AstType {
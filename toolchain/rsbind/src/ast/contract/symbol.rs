@@ -0,0 +1,441 @@
+//!
+//! Resolve the types referenced in a contract module to their canonical definitions.
+//!
+use std::collections::HashMap;
+
+use crate::errors::ErrorKind::*;
+use crate::errors::*;
+
+use super::parser::ParseContext;
+
+/// Names which can be used without importing them, the supported types in the std prelude and more.
+const BUILTINS: &[&str] = &[
+    "u8", "i8", "u16", "i16", "u32", "i32", "u64", "i64", "usize", "isize", "f32", "f64", "bool",
+    "str", "String", "Vec", "Option", "Box", "Result", "HashMap", "BTreeMap", "Bytes", "Iterator",
+];
+
+/// The full paths of the supported types defined outside of the contract crate.
+const EXTERNAL_PATHS: &[(&str, &str)] = &[
+    ("std::string::String", "String"),
+    ("std::vec::Vec", "Vec"),
+    ("std::option::Option", "Option"),
+    ("std::boxed::Box", "Box"),
+    ("std::result::Result", "Result"),
+    ("std::collections::HashMap", "HashMap"),
+    ("std::collections::hash_map::HashMap", "HashMap"),
+    ("std::collections::BTreeMap", "BTreeMap"),
+    ("std::collections::btree_map::BTreeMap", "BTreeMap"),
+    ("bytes::Bytes", "Bytes"),
+    ("std::iter::Iterator", "Iterator"),
+    ("std::pin::Pin", "Pin"),
+    ("futures_core::Stream", "Stream"),
+    ("futures_core::stream::Stream", "Stream"),
+    ("futures::Stream", "Stream"),
+    ("futures::stream::Stream", "Stream"),
+    ("tokio_util::sync::CancellationToken", "CancellationToken"),
+];
+
+/// Traits allowed beside the callback in `Box<dyn Callback + Send + Sync>`.
+const MARKER_TRAITS: &[&str] = &["Send", "Sync"];
+
+///
+/// The names visible in a contract module, built from `use` items, `type` aliases and the definitions.
+/// Types are rewritten to the bare names of their canonical definitions, so the parser only looks at
+/// the last segment of a path.
+///
+pub(crate) struct SymbolTable {
+    /// The path of the module in the crate, like `contract::demo`.
+    mod_path: Vec<String>,
+    /// Imported names to their full paths, like `Map` to `std::collections::HashMap`.
+    imports: HashMap<String, Vec<String>>,
+    /// Glob imports like `use super::*`, the names from them can't be checked.
+    has_glob: bool,
+    /// Type aliases like `type UserList = Vec<User>`.
+    aliases: HashMap<String, syn::ItemType>,
+    /// The structs, enums and traits defined in the module.
+    defines: Vec<String>,
+    /// The bare names of resolved types to their full paths, two paths can't share a name.
+    canonical: HashMap<String, String>,
+}
+
+impl SymbolTable {
+    pub(crate) fn new(ctx: &ParseContext, items: &[syn::Item]) -> Result<SymbolTable> {
+        let mut table = SymbolTable {
+            mod_path: ctx.mod_path.split("::").map(|seg| seg.to_owned()).collect(),
+            imports: HashMap::new(),
+            has_glob: false,
+            aliases: HashMap::new(),
+            defines: vec![],
+            canonical: HashMap::new(),
+        };
+
+        for item in items.iter() {
+            match item {
+                syn::Item::Use(ref item_use) => table.add_use_tree(vec![], &item_use.tree)?,
+                syn::Item::Type(ref item_type) => {
+                    let name = item_type.ident.to_string();
+                    table.check_unique(&name)?;
+                    table.aliases.insert(name, item_type.clone());
+                }
+                syn::Item::Struct(ref item) => table.defines.push(item.ident.to_string()),
+                syn::Item::Enum(ref item) => table.defines.push(item.ident.to_string()),
+                syn::Item::Trait(ref item) => table.defines.push(item.ident.to_string()),
+                _ => (),
+            }
+        }
+
+        for name in table.defines.iter() {
+            if table.imports.contains_key(name) || table.aliases.contains_key(name) {
+                return Err(ParseError(format!(
+                    "{} is ambiguous, it is defined and also imported or aliased.",
+                    name
+                ))
+                .into());
+            }
+        }
+
+        Ok(table)
+    }
+
+    ///
+    /// Rewrite the types in the methods of traits and the fields of structs and enums.
+    ///
+    pub(crate) fn resolve_items(&mut self, items: &mut [syn::Item]) -> Result<()> {
+        for item in items.iter_mut() {
+            match item {
+                syn::Item::Trait(ref mut item_trait) => {
+                    let name = item_trait.ident.to_string();
+                    for trait_item in item_trait.items.iter_mut() {
+                        if let syn::TraitItem::Method(ref mut method) = trait_item {
+                            for input in method.sig.inputs.iter_mut() {
+                                if let syn::FnArg::Typed(ref mut arg) = input {
+                                    *arg.ty = self.resolve(&arg.ty, None)?;
+                                }
+                            }
+                            if let syn::ReturnType::Type(_, ref mut ty) = method.sig.output {
+                                **ty = self.resolve(ty, Some(&name))?;
+                            }
+                        }
+                    }
+                }
+                syn::Item::Struct(ref mut item_struct) => {
+                    let name = item_struct.ident.to_string();
+                    for field in item_struct.fields.iter_mut() {
+                        field.ty = self.resolve(&field.ty, Some(&name))?;
+                    }
+                }
+                syn::Item::Enum(ref mut item_enum) => {
+                    let name = item_enum.ident.to_string();
+                    for variant in item_enum.variants.iter_mut() {
+                        for field in variant.fields.iter_mut() {
+                            field.ty = self.resolve(&field.ty, Some(&name))?;
+                        }
+                    }
+                }
+                _ => (),
+            }
+        }
+        Ok(())
+    }
+
+    ///
+    /// Resolve a type, `Self` is the struct or enum which the field belongs to,
+    /// or the trait whose constructor returns it.
+    ///
+    fn resolve(&mut self, ty: &syn::Type, self_name: Option<&str>) -> Result<syn::Type> {
+        self.resolve_type(ty, self_name, &mut vec![])
+    }
+
+    fn resolve_type(
+        &mut self,
+        ty: &syn::Type,
+        self_name: Option<&str>,
+        aliasing: &mut Vec<String>,
+    ) -> Result<syn::Type> {
+        let mut ty = ty.clone();
+        match ty {
+            syn::Type::Path(ref mut type_path) => {
+                if type_path.qself.is_some() {
+                    return Err(ParseError(format!(
+                        "Qualified type {} is not supported.",
+                        path_str(&type_path.path)
+                    ))
+                    .into());
+                }
+
+                let segments = &type_path.path.segments;
+                if segments.len() == 1 && type_path.path.leading_colon.is_none() {
+                    let name = segments[0].ident.to_string();
+                    if let Some(alias) = self.aliases.get(&name).cloned() {
+                        return self.resolve_alias(&alias, &segments[0], self_name, aliasing);
+                    }
+                }
+
+                type_path.path = self.resolve_path(&type_path.path, self_name, aliasing)?;
+            }
+            syn::Type::Reference(ref mut reference) => {
+                *reference.elem = self.resolve_type(&reference.elem, self_name, aliasing)?;
+            }
+            syn::Type::Slice(ref mut slice) => {
+                *slice.elem = self.resolve_type(&slice.elem, self_name, aliasing)?;
+            }
+            syn::Type::Paren(ref mut paren) => {
+                *paren.elem = self.resolve_type(&paren.elem, self_name, aliasing)?;
+            }
+            syn::Type::Group(ref mut group) => {
+                *group.elem = self.resolve_type(&group.elem, self_name, aliasing)?;
+            }
+            syn::Type::Tuple(ref mut tuple) => {
+                for elem in tuple.elems.iter_mut() {
+                    *elem = self.resolve_type(elem, self_name, aliasing)?;
+                }
+            }
+            syn::Type::TraitObject(ref mut trait_obj) => {
+                for bound in trait_obj.bounds.iter_mut() {
+                    if let syn::TypeParamBound::Trait(ref mut trait_bound) = bound {
+                        let path = &trait_bound.path;
+                        if !MARKER_TRAITS.iter().any(|marker| path.is_ident(marker)) {
+                            trait_bound.path = self.resolve_path(path, self_name, aliasing)?;
+                        }
+                    }
+                }
+            }
+            _ => (),
+        }
+        Ok(ty)
+    }
+
+    ///
+    /// Replace an alias with the aliased type, generic aliases are not supported.
+    ///
+    fn resolve_alias(
+        &mut self,
+        alias: &syn::ItemType,
+        segment: &syn::PathSegment,
+        self_name: Option<&str>,
+        aliasing: &mut Vec<String>,
+    ) -> Result<syn::Type> {
+        let name = alias.ident.to_string();
+        if !alias.generics.params.is_empty() || !segment.arguments.is_empty() {
+            return Err(
+                ParseError(format!("Generic type alias {} is not supported.", &name)).into(),
+            );
+        }
+        if aliasing.contains(&name) {
+            return Err(ParseError(format!("Type alias {} refers to itself.", &name)).into());
+        }
+
+        aliasing.push(name);
+        let ty = self.resolve_type(&alias.ty, self_name, aliasing);
+        aliasing.pop();
+        ty
+    }
+
+    ///
+    /// Resolve a path to a bare name, the generic arguments are resolved too.
+    ///
+    fn resolve_path(
+        &mut self,
+        path: &syn::Path,
+        self_name: Option<&str>,
+        aliasing: &mut Vec<String>,
+    ) -> Result<syn::Path> {
+        let mut segments = path.segments.clone();
+        let last = segments.len() - 1;
+        if let syn::PathArguments::AngleBracketed(ref mut generics) = segments[last].arguments {
+            for arg in generics.args.iter_mut() {
+                match arg {
+                    syn::GenericArgument::Type(ref mut ty) => {
+                        *ty = self.resolve_type(ty, self_name, aliasing)?;
+                    }
+                    // The item of `Iterator<Item = T>`.
+                    syn::GenericArgument::Binding(ref mut binding) => {
+                        binding.ty = self.resolve_type(&binding.ty, self_name, aliasing)?;
+                    }
+                    _ => (),
+                }
+            }
+        }
+
+        let idents = segments
+            .iter()
+            .map(|segment| segment.ident.to_string())
+            .collect::<Vec<String>>();
+        let name = if path.leading_colon.is_some() {
+            self.resolve_full_path(&idents)?
+        } else if idents.len() == 1 {
+            self.resolve_name(&idents[0], self_name)?
+        } else if let Some(import) = self.imports.get(&idents[0]).cloned() {
+            // A path from an imported module, like `collections::HashMap`.
+            let full = import.into_iter().chain(idents[1..].iter().cloned());
+            self.resolve_full_path(&full.collect::<Vec<String>>())?
+        } else {
+            self.resolve_full_path(&idents)?
+        };
+
+        let mut segment = segments[last].clone();
+        segment.ident = syn::Ident::new(&name, segment.ident.span());
+        Ok(syn::Path {
+            leading_colon: None,
+            segments: std::iter::once(segment).collect(),
+        })
+    }
+
+    fn resolve_name(&mut self, name: &str, self_name: Option<&str>) -> Result<String> {
+        if name == "Self" {
+            return self_name.map(|name| name.to_owned()).ok_or_else(|| {
+                ParseError("Self is only supported in the fields of structs and enums and the return types of constructors.".to_owned())
+                    .into()
+            });
+        }
+        if let Some(import) = self.imports.get(name).cloned() {
+            return self.resolve_full_path(&import);
+        }
+        if self.defines.iter().any(|define| define == name)
+            || BUILTINS.contains(&name)
+            || self.has_glob
+        {
+            return Ok(name.to_owned());
+        }
+
+        Err(ParseError(format!(
+            "Unknown type {}, it should be defined or imported in the contract.",
+            name
+        ))
+        .into())
+    }
+
+    ///
+    /// Resolve a full path like `std::collections::HashMap` or `crate::contract::model::User`.
+    /// Types from the crate keep their names, which can't be shared by different paths.
+    ///
+    fn resolve_full_path(&mut self, idents: &[String]) -> Result<String> {
+        let mut full = idents.to_vec();
+        if full[0] == "core" || full[0] == "alloc" {
+            full[0] = "std".to_owned();
+        }
+        let joined = full.join("::");
+        if let Some((_, name)) = EXTERNAL_PATHS.iter().find(|(path, _)| *path == joined) {
+            return Ok(name.to_string());
+        }
+
+        let mut canonical = match full[0].as_str() {
+            "crate" => vec![],
+            "self" => self.mod_path.clone(),
+            "super" => self.mod_path[..self.mod_path.len().saturating_sub(1)].to_vec(),
+            _ => {
+                return Err(ParseError(format!(
+                    "Unknown type {}, only the supported types of std and the types in this crate can be used.",
+                    joined
+                ))
+                .into())
+            }
+        };
+        canonical.extend(full[1..].iter().cloned());
+        let name = full[full.len() - 1].clone();
+
+        let is_local = canonical[..canonical.len() - 1] == self.mod_path[..];
+        let is_defined = self.defines.contains(&name);
+        if is_local && !is_defined {
+            return Err(ParseError(format!(
+                "Unknown type {}, it is not defined in this module.",
+                joined
+            ))
+            .into());
+        }
+        let canonical = canonical.join("::");
+        let same_name = match self.canonical.get(&name) {
+            Some(prev) => prev != &canonical,
+            None => is_defined && !is_local,
+        };
+        if same_name {
+            return Err(ParseError(format!(
+                "{} is ambiguous, it is the name of different types in this module.",
+                name
+            ))
+            .into());
+        }
+        self.canonical.insert(name.clone(), canonical);
+        Ok(name)
+    }
+
+    ///
+    /// Collect the imported names, like `use a::{b, c as d}`.
+    ///
+    fn add_use_tree(&mut self, prefix: Vec<String>, tree: &syn::UseTree) -> Result<()> {
+        match tree {
+            syn::UseTree::Path(ref use_path) => {
+                let mut prefix = prefix;
+                prefix.push(use_path.ident.to_string());
+                self.add_use_tree(prefix, &use_path.tree)?;
+            }
+            syn::UseTree::Name(ref use_name) => {
+                let name = use_name.ident.to_string();
+                if name == "self" {
+                    if let Some(last) = prefix.last().cloned() {
+                        self.add_import(last, prefix)?;
+                    }
+                } else {
+                    let mut path = prefix;
+                    path.push(name.clone());
+                    self.add_import(name, path)?;
+                }
+            }
+            syn::UseTree::Rename(ref use_rename) => {
+                let rename = use_rename.rename.to_string();
+                let mut path = prefix;
+                if use_rename.ident != "self" {
+                    path.push(use_rename.ident.to_string());
+                }
+                // `use Trait as _` only brings the methods into scope.
+                if rename != "_" {
+                    self.add_import(rename, path)?;
+                }
+            }
+            syn::UseTree::Glob(_) => self.has_glob = true,
+            syn::UseTree::Group(ref group) => {
+                for item in group.items.iter() {
+                    self.add_use_tree(prefix.clone(), item)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn add_import(&mut self, name: String, path: Vec<String>) -> Result<()> {
+        match self.imports.get(&name) {
+            Some(prev) if prev == &path => return Ok(()),
+            Some(prev) => {
+                return Err(ParseError(format!(
+                    "{} is ambiguous, it is imported from {} and {}.",
+                    &name,
+                    prev.join("::"),
+                    path.join("::")
+                ))
+                .into())
+            }
+            None => self.check_unique(&name)?,
+        }
+        self.imports.insert(name, path);
+        Ok(())
+    }
+
+    fn check_unique(&self, name: &str) -> Result<()> {
+        if self.imports.contains_key(name) || self.aliases.contains_key(name) {
+            return Err(ParseError(format!(
+                "{} is ambiguous, it is imported or aliased more than once.",
+                name
+            ))
+            .into());
+        }
+        Ok(())
+    }
+}
+
+fn path_str(path: &syn::Path) -> String {
+    path.segments
+        .iter()
+        .map(|segment| segment.ident.to_string())
+        .collect::<Vec<String>>()
+        .join("::")
+}
@@ -1,7 +1,11 @@
 #[cfg(test)]
 mod tests {
+    use crate::ast::contract::desc::{ConstValue, SequenceKind};
     use crate::ast::contract::parser::{parse_from_str, ContractResult, ParseContext};
+    use crate::ast::imp::desc::ImpDesc;
     use crate::ast::types::{AstBaseType, AstType, CustomType};
+    use crate::ast::AstResult;
+    use std::collections::HashMap;
 
     #[test]
     fn parse_contract_works() {
@@ -42,7 +46,9 @@ mod tests {
                 fn test_self(self);
             }
         ";
-        let ContractResult { traits, structs } = parse_from_str(
+        let ContractResult {
+            traits, structs, ..
+        } = parse_from_str(
             &ParseContext {
                 crate_name: "demo_crate".to_string(),
                 mod_name: "demo_mod".to_string(),
@@ -179,4 +185,1238 @@ mod tests {
         );
         assert_eq!(trait_desc[1].methods[1].name, "test_self")
     }
+
+    #[test]
+    fn parse_option_works() {
+        let contract_str = "
+            pub struct OptionStruct {
+                pub arg1: Option<i32>,
+                pub arg2: Option<String>,
+            }
+
+            pub trait OptionContract : Send + Sync {
+                fn arg_option(arg1: Option<i64>, arg2: Option<OptionStruct>) -> Option<bool>;
+            }
+        ";
+        let ContractResult {
+            traits, structs, ..
+        } = parse_from_str(
+            &ParseContext {
+                crate_name: "demo_crate".to_string(),
+                mod_name: "demo_mod".to_string(),
+                mod_path: "contract".to_string(),
+            },
+            contract_str,
+        )
+        .unwrap();
+
+        assert_eq!(structs[0].fields[0].ty.origin(), "Option<i32>");
+        assert_eq!(
+            structs[0].fields[0].ty,
+            AstType::Optional(AstBaseType::Int("i32".to_string()))
+        );
+        assert_eq!(
+            structs[0].fields[1].ty,
+            AstType::Optional(AstBaseType::String)
+        );
+
+        assert_eq!(
+            traits[0].methods[0].args[0].ty,
+            AstType::Optional(AstBaseType::Long("i64".to_string()))
+        );
+        assert_eq!(
+            traits[0].methods[0].args[1].ty,
+            AstType::Optional(AstBaseType::Struct(CustomType {
+                mod_name: "demo_mod".to_string(),
+                origin: "OptionStruct".to_string()
+            }))
+        );
+        assert_eq!(
+            traits[0].methods[0].return_type,
+            AstType::Optional(AstBaseType::Boolean)
+        );
+    }
+
+    #[test]
+    fn parse_result_works() {
+        let contract_str = "
+            pub struct ResultError {
+                pub code: i32,
+            }
+
+            pub trait ResultContract : Send + Sync {
+                fn return_result(arg: i32) -> Result<i32, String>;
+                fn return_void_result() -> Result<(), ResultError>;
+            }
+        ";
+        let ContractResult { traits, .. } = parse_from_str(
+            &ParseContext {
+                crate_name: "demo_crate".to_string(),
+                mod_name: "demo_mod".to_string(),
+                mod_path: "contract".to_string(),
+            },
+            contract_str,
+        )
+        .unwrap();
+
+        assert_eq!(
+            traits[0].methods[0].return_type,
+            AstType::Result(AstBaseType::Int("i32".to_string()), AstBaseType::String)
+        );
+        assert_eq!(
+            traits[0].methods[1].return_type.origin(),
+            "Result<(), ResultError>"
+        );
+        assert_eq!(traits[0].methods[1].return_type.ok_type(), AstType::Void);
+
+        let callback_str = "
+            pub trait ResultCallback : Send + Sync {
+                fn on_result(&self) -> Result<i32, String>;
+            }
+        ";
+        assert!(parse_from_str(
+            &ParseContext {
+                crate_name: "demo_crate".to_string(),
+                mod_name: "demo_mod".to_string(),
+                mod_path: "contract".to_string(),
+            },
+            callback_str,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn parse_enum_works() {
+        let contract_str = "
+            pub enum EnumColor {
+                Red,
+                Green = 5,
+                Blue,
+            }
+
+            pub struct EnumStruct {
+                pub color: EnumColor,
+                pub colors: Vec<EnumColor>,
+            }
+
+            pub trait EnumContract : Send + Sync {
+                fn arg_enum(arg: EnumColor, args: Option<EnumColor>) -> Vec<EnumColor>;
+                fn return_struct() -> EnumStruct;
+            }
+        ";
+        let ContractResult {
+            traits,
+            structs,
+            enums,
+            ..
+        } = parse_from_str(
+            &ParseContext {
+                crate_name: "demo_crate".to_string(),
+                mod_name: "demo_mod".to_string(),
+                mod_path: "contract".to_string(),
+            },
+            contract_str,
+        )
+        .unwrap();
+
+        let color = CustomType {
+            mod_name: "demo_mod".to_string(),
+            origin: "EnumColor".to_string(),
+        };
+        assert_eq!(enums[0].name, "EnumColor");
+        assert_eq!(
+            enums[0]
+                .variants
+                .iter()
+                .map(|variant| (variant.name.as_str(), variant.value))
+                .collect::<Vec<(&str, i32)>>(),
+            vec![("Red", 0), ("Green", 5), ("Blue", 6)]
+        );
+
+        assert_eq!(structs[0].fields[0].ty, AstType::Enum(color.clone()));
+        assert_eq!(
+            structs[0].fields[1].ty,
+            AstType::Vec(AstBaseType::Enum(color.clone()))
+        );
+        assert_eq!(
+            traits[0].methods[0].args[0].ty,
+            AstType::Enum(color.clone())
+        );
+        assert_eq!(
+            traits[0].methods[0].args[1].ty,
+            AstType::Optional(AstBaseType::Enum(color.clone()))
+        );
+        assert_eq!(
+            traits[0].methods[0].return_type,
+            AstType::Vec(AstBaseType::Enum(color))
+        );
+        assert_eq!(
+            traits[0].methods[1].return_type,
+            AstType::Struct(CustomType {
+                mod_name: "demo_mod".to_string(),
+                origin: "EnumStruct".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn parse_data_enum_works() {
+        let contract_str = "
+            pub enum DataColor {
+                Red,
+                Green,
+            }
+
+            pub enum DataLevel {
+                Lowest = i32::MIN,
+                Low,
+                High = -(-3),
+            }
+
+            pub enum DataEvent {
+                Connected { id: i64, color: DataColor },
+                Failed(String, Option<DataColor>),
+                Closed,
+            }
+
+            pub trait DataContract : Send + Sync {
+                fn arg_event(arg: DataEvent) -> Vec<DataEvent>;
+            }
+        ";
+        let ContractResult { traits, enums, .. } = parse_from_str(
+            &ParseContext {
+                crate_name: "demo_crate".to_string(),
+                mod_name: "demo_mod".to_string(),
+                mod_path: "contract".to_string(),
+            },
+            contract_str,
+        )
+        .unwrap();
+
+        let color = CustomType {
+            mod_name: "demo_mod".to_string(),
+            origin: "DataColor".to_string(),
+        };
+        let event = CustomType {
+            mod_name: "demo_mod".to_string(),
+            origin: "DataEvent".to_string(),
+        };
+        assert!(enums[0].is_c_like());
+        assert!(!enums[2].is_c_like());
+
+        // Discriminants can be the bounds of integer types.
+        let levels = &enums[1].variants;
+        assert_eq!(levels[0].value, i32::MIN);
+        assert_eq!(levels[1].value, i32::MIN + 1);
+        assert_eq!(levels[2].value, 3);
+
+        let variants = &enums[2].variants;
+        assert_eq!(variants.len(), 3);
+        assert!(!variants[0].is_tuple);
+        assert_eq!(variants[0].fields[0].name, "id");
+        assert_eq!(variants[0].fields[0].ty, AstType::Long("i64".to_string()));
+        assert_eq!(variants[0].fields[1].ty, AstType::Enum(color.clone()));
+        assert!(variants[1].is_tuple);
+        assert_eq!(variants[1].value, 1);
+        assert_eq!(variants[1].fields[0].name, "_0");
+        assert_eq!(
+            variants[1].fields[1].ty,
+            AstType::Optional(AstBaseType::Enum(color))
+        );
+        assert!(variants[2].fields.is_empty());
+
+        // Enums with data are transferred like structs.
+        assert_eq!(
+            traits[0].methods[0].args[0].ty,
+            AstType::Struct(event.clone())
+        );
+        assert_eq!(
+            traits[0].methods[0].return_type,
+            AstType::Vec(AstBaseType::Struct(event))
+        );
+    }
+
+    #[test]
+    fn parse_map_works() {
+        let contract_str = "
+            pub enum MapColor {
+                Red,
+                Green,
+            }
+
+            pub struct MapStruct {
+                pub tags: HashMap<String, MapColor>,
+            }
+
+            pub trait MapContract : Send + Sync {
+                fn arg_map(arg: HashMap<i32, String>) -> BTreeMap<i64, MapStruct>;
+            }
+        ";
+        let ContractResult {
+            traits, structs, ..
+        } = parse_from_str(
+            &ParseContext {
+                crate_name: "demo_crate".to_string(),
+                mod_name: "demo_mod".to_string(),
+                mod_path: "contract".to_string(),
+            },
+            contract_str,
+        )
+        .unwrap();
+
+        let color = CustomType {
+            mod_name: "demo_mod".to_string(),
+            origin: "MapColor".to_string(),
+        };
+        assert_eq!(
+            structs[0].fields[0].ty,
+            AstType::Map(
+                "HashMap".to_string(),
+                AstBaseType::String,
+                AstBaseType::Enum(color)
+            )
+        );
+        assert_eq!(
+            traits[0].methods[0].args[0].ty,
+            AstType::Map(
+                "HashMap".to_string(),
+                AstBaseType::Int("i32".to_string()),
+                AstBaseType::String
+            )
+        );
+        assert_eq!(
+            traits[0].methods[0].return_type.origin(),
+            "BTreeMap<i64, MapStruct>"
+        );
+
+        let invalid_str = "
+            pub trait InvalidMapContract : Send + Sync {
+                fn arg_map(arg: HashMap<f64, String>);
+            }
+        ";
+        assert!(parse_from_str(
+            &ParseContext {
+                crate_name: "demo_crate".to_string(),
+                mod_name: "demo_mod".to_string(),
+                mod_path: "contract".to_string(),
+            },
+            invalid_str,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn parse_nested_collection_works() {
+        let contract_str = "
+            pub enum NestedColor {
+                Red,
+                Green,
+            }
+
+            pub struct NestedStruct {
+                pub grid: Vec<Vec<u8>>,
+            }
+
+            pub trait NestedContract : Send + Sync {
+                fn arg_nested(arg: Vec<Option<NestedColor>>) -> Option<Vec<Vec<String>>>;
+            }
+        ";
+        let ContractResult {
+            traits, structs, ..
+        } = parse_from_str(
+            &ParseContext {
+                crate_name: "demo_crate".to_string(),
+                mod_name: "demo_mod".to_string(),
+                mod_path: "contract".to_string(),
+            },
+            contract_str,
+        )
+        .unwrap();
+
+        let color = CustomType {
+            mod_name: "demo_mod".to_string(),
+            origin: "NestedColor".to_string(),
+        };
+        assert_eq!(
+            structs[0].fields[0].ty,
+            AstType::Vec(AstBaseType::Vec(Box::new(AstBaseType::Byte(
+                "u8".to_string()
+            ))))
+        );
+        assert_eq!(
+            traits[0].methods[0].args[0].ty,
+            AstType::Vec(AstBaseType::Optional(Box::new(AstBaseType::Enum(color))))
+        );
+        assert_eq!(
+            traits[0].methods[0].return_type.origin(),
+            "Option<Vec<Vec<String>>>"
+        );
+
+        let invalid_str = "
+            pub trait InvalidNestedContract : Send + Sync {
+                fn arg_nested(arg: Option<Option<i32>>);
+            }
+        ";
+        assert!(parse_from_str(
+            &ParseContext {
+                crate_name: "demo_crate".to_string(),
+                mod_name: "demo_mod".to_string(),
+                mod_path: "contract".to_string(),
+            },
+            invalid_str,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn parse_tuple_and_newtype_works() {
+        let contract_str = "
+            #[repr(transparent)]
+            pub struct UserId(pub i64);
+
+            pub struct Point(pub i32, pub i32);
+
+            pub trait TupleContract : Send + Sync {
+                fn tuple(arg: (i32, String)) -> (UserId, Vec<Point>);
+                fn user(id: UserId) -> Point;
+            }
+        ";
+        let ContractResult {
+            traits, structs, ..
+        } = parse_from_str(
+            &ParseContext {
+                crate_name: "demo_crate".to_string(),
+                mod_name: "demo_mod".to_string(),
+                mod_path: "contract".to_string(),
+            },
+            &contract_str.replace("(UserId, Vec<Point>)", "(i64, Vec<Point>)"),
+        )
+        .unwrap();
+
+        assert_eq!(structs.len(), 1);
+        assert!(structs[0].is_tuple);
+        assert_eq!(structs[0].fields[1].name, "_1");
+        assert_eq!(
+            traits[0].methods[0].args[0].ty,
+            AstType::Tuple(vec![
+                AstBaseType::Int("i32".to_string()),
+                AstBaseType::String
+            ])
+        );
+        assert_eq!(
+            traits[0].methods[0].return_type.origin(),
+            "(i64, Vec<Point>)"
+        );
+        assert_eq!(
+            traits[0].methods[1].args[0].ty,
+            AstType::Newtype(
+                CustomType {
+                    mod_name: "demo_mod".to_string(),
+                    origin: "UserId".to_string(),
+                },
+                AstBaseType::Long("i64".to_string())
+            )
+        );
+
+        // Newtypes can't be put in tuples or collections.
+        assert!(parse_from_str(
+            &ParseContext {
+                crate_name: "demo_crate".to_string(),
+                mod_name: "demo_mod".to_string(),
+                mod_path: "contract".to_string(),
+            },
+            contract_str,
+        )
+        .is_err());
+        for ty in [
+            "Vec<UserId>",
+            "Option<Vec<UserId>>",
+            "HashMap<String, UserId>",
+            "Result<UserId, String>",
+        ] {
+            assert!(parse_from_str(
+                &ParseContext {
+                    crate_name: "demo_crate".to_string(),
+                    mod_name: "demo_mod".to_string(),
+                    mod_path: "contract".to_string(),
+                },
+                &contract_str.replace("(UserId, Vec<Point>)", ty),
+            )
+            .is_err());
+        }
+    }
+
+    #[test]
+    fn parse_unsigned_works() {
+        let contract_str = "
+            pub trait UnsignedContract : Send + Sync {
+                fn unsigned(a: u16, b: u32, c: usize, d: isize, e: Vec<u64>) -> u64;
+            }
+        ";
+        let ContractResult { traits, .. } = parse_from_str(
+            &ParseContext {
+                crate_name: "demo_crate".to_string(),
+                mod_name: "demo_mod".to_string(),
+                mod_path: "contract".to_string(),
+            },
+            contract_str,
+        )
+        .unwrap();
+
+        let method = &traits[0].methods[0];
+        assert_eq!(method.args[2].ty, AstType::Long("usize".to_string()));
+        assert_eq!(method.args[3].ty, AstType::Long("isize".to_string()));
+        assert_eq!(method.args[2].ty.to_java_sig(), "J");
+
+        // Unsigned integers are widened for java.
+        let widen = |ty: &AstType| ty.clone().widen_unsigned();
+        assert_eq!(widen(&method.args[0].ty), AstType::Int("u16".to_string()));
+        assert_eq!(widen(&method.args[1].ty), AstType::Long("u32".to_string()));
+        assert_eq!(
+            widen(&method.args[4].ty),
+            AstType::Vec(AstBaseType::BigInt("u64".to_string()))
+        );
+        assert_eq!(
+            widen(&method.return_type),
+            AstType::BigInt("u64".to_string())
+        );
+    }
+
+    #[test]
+    fn parse_borrowed_arg_works() {
+        let ctx = ParseContext {
+            crate_name: "demo_crate".to_string(),
+            mod_name: "demo_mod".to_string(),
+            mod_path: "contract".to_string(),
+        };
+        let contract_str = "
+            pub trait BorrowedContract : Send + Sync {
+                fn hash(data: &[u8], name: &str, nums: &[i32], s: &StructSimple, owned: String) -> i64;
+            }
+
+            pub struct StructSimple {
+                pub arg1: i32,
+            }
+        ";
+        let ContractResult { traits, .. } = parse_from_str(&ctx, contract_str).unwrap();
+
+        let args = &traits[0].methods[0].args;
+        assert_eq!(
+            args[0].ty,
+            AstType::Vec(AstBaseType::Byte("u8".to_string()))
+        );
+        assert_eq!(args[1].ty, AstType::String);
+        assert_eq!(
+            args[2].ty,
+            AstType::Vec(AstBaseType::Int("i32".to_string()))
+        );
+        assert_eq!(args[3].ty.origin(), "StructSimple");
+        assert!(args[..4].iter().all(|arg| arg.borrowed));
+        assert!(!args[4].borrowed);
+
+        // Mutable references and borrowed arguments in callbacks are not supported.
+        let mutable_str = "
+            pub trait BorrowedContract : Send + Sync {
+                fn fill(data: &mut [u8]);
+            }
+        ";
+        assert!(parse_from_str(&ctx, mutable_str).is_err());
+        let callback_str = "
+            pub trait BorrowedCallback : Send + Sync {
+                fn on_data(&self, data: &[u8]);
+            }
+        ";
+        assert!(parse_from_str(&ctx, callback_str).is_err());
+
+        // Slices of tuples, arrays and references are errors instead of void arguments.
+        let errors = [
+            "pub trait S : Send + Sync { fn f(points: &[(i32, i32)]); }",
+            "pub trait S : Send + Sync { fn f(blocks: &[[u8; 4]]); }",
+            "pub trait S : Send + Sync { fn f(names: &[&str]); }",
+            "pub trait S : Send + Sync { fn f(block: [u8; 4]); }",
+        ];
+        for error in errors.iter() {
+            assert!(parse_from_str(&ctx, error).is_err(), "{}", error);
+        }
+    }
+
+    #[test]
+    fn parse_buffer_works() {
+        let ctx = ParseContext {
+            crate_name: "demo_crate".to_string(),
+            mod_name: "demo_mod".to_string(),
+            mod_path: "contract".to_string(),
+        };
+        let contract_str = "
+            pub trait BufferContract : Send + Sync {
+                fn frame(index: i32) -> Bytes;
+            }
+        ";
+        let ContractResult { traits, .. } = parse_from_str(&ctx, contract_str).unwrap();
+        assert_eq!(
+            traits[0].methods[0].return_type,
+            AstType::Buffer("Bytes".to_string())
+        );
+
+        // Buffers are backed by rust memory, so they can't be passed from native.
+        let arg_str = "
+            pub trait BufferContract : Send + Sync {
+                fn write(data: Bytes);
+            }
+        ";
+        assert!(parse_from_str(&ctx, arg_str).is_err());
+        let callback_str = "
+            pub trait BufferCallback : Send + Sync {
+                fn on_frame(&self) -> Bytes;
+            }
+        ";
+        assert!(parse_from_str(&ctx, callback_str).is_err());
+        let field_str = "
+            pub struct Frame {
+                pub data: Bytes,
+            }
+        ";
+        assert!(parse_from_str(&ctx, field_str).is_err());
+    }
+
+    #[test]
+    fn parse_type_path_and_alias_works() {
+        let ctx = ParseContext {
+            crate_name: "demo_crate".to_string(),
+            mod_name: "demo_mod".to_string(),
+            mod_path: "contract::demo_mod".to_string(),
+        };
+        let contract_str = "
+            use std::collections::BTreeMap as Ordered;
+            use crate::contract::model::{self, User};
+
+            type UserList = Vec<User>;
+            type Names = UserList;
+
+            pub struct TreeNode {
+                pub children: Vec<Self>,
+                pub owner: model::Account,
+            }
+
+            pub trait PathContract : Send + Sync {
+                fn users(arg: std::collections::HashMap<String, crate::contract::model::User>) -> Names;
+                fn ordered(arg: Ordered<i32, self::TreeNode>) -> Option<UserList>;
+            }
+        ";
+        let ContractResult {
+            traits, structs, ..
+        } = parse_from_str(&ctx, contract_str).unwrap();
+
+        let users = &traits[0].methods[0];
+        assert_eq!(users.args[0].ty.origin(), "HashMap<String, User>");
+        assert_eq!(
+            users.return_type,
+            AstType::Vec(AstBaseType::Struct(CustomType {
+                mod_name: "demo_mod".to_string(),
+                origin: "User".to_string(),
+            }))
+        );
+        let ordered = &traits[0].methods[1];
+        assert_eq!(ordered.args[0].ty.origin(), "BTreeMap<i32, TreeNode>");
+        assert_eq!(ordered.return_type.origin(), "Option<Vec<User>>");
+        assert_eq!(structs[0].fields[0].ty.origin(), "Vec<TreeNode>");
+        assert_eq!(structs[0].fields[1].ty.origin(), "Account");
+
+        // Unknown and ambiguous names are errors.
+        let errors = [
+            "pub trait C : Send + Sync { fn f(arg: Missing); }",
+            "pub trait C : Send + Sync { fn f(arg: serde_json::Value); }",
+            "pub trait C : Send + Sync { fn f(arg: Self); }",
+            "use crate::a::User; use crate::b::User; pub trait C : Send + Sync { fn f(arg: User); }",
+            "pub trait C : Send + Sync { fn f(a: crate::a::User, b: crate::b::User); }",
+            "pub struct User { pub id: i32 } pub trait C : Send + Sync { fn f(a: crate::a::User); }",
+            "type A = B; type B = A; pub trait C : Send + Sync { fn f(arg: A); }",
+        ];
+        for error in errors.iter() {
+            assert!(parse_from_str(&ctx, error).is_err(), "{}", error);
+        }
+    }
+
+    #[test]
+    fn parse_object_works() {
+        let ctx = ParseContext {
+            crate_name: "demo_crate".to_string(),
+            mod_name: "demo_mod".to_string(),
+            mod_path: "contract::demo_mod".to_string(),
+        };
+        let contract_str = "
+            pub trait LoginService : Send + Sync {
+                fn login(name: String) -> Box<dyn Session>;
+                fn watch(callback: Box<dyn LoginCallback>);
+            }
+
+            pub trait Session : Send + Sync {
+                fn user(&self) -> Result<String, String>;
+                fn child(&self) -> Box<dyn Session>;
+            }
+
+            pub trait LoginCallback : Send + Sync {
+                fn on_login(&self, user: String) -> Box<dyn Token>;
+            }
+
+            pub trait Token : Send + Sync {
+                fn value(&self) -> String;
+            }
+        ";
+        let ContractResult { traits, .. } = parse_from_str(&ctx, contract_str).unwrap();
+
+        // Traits only returned from rust are objects, the others are callbacks.
+        assert!(traits[0].is_service());
+        assert!(traits[1].is_object && !traits[1].is_callback);
+        assert!(traits[2].is_callback && !traits[2].is_object);
+        assert!(traits[3].is_callback && !traits[3].is_object);
+        let session = CustomType {
+            mod_name: "demo_mod".to_string(),
+            origin: "Session".to_string(),
+        };
+        assert_eq!(
+            traits[0].methods[0].return_type,
+            AstType::Object(session.clone())
+        );
+        assert_eq!(traits[1].methods[1].return_type, AstType::Object(session));
+        assert_eq!(
+            traits[1].methods[0].return_type.origin(),
+            "Result<String, String>"
+        );
+
+        // Methods of objects are called on the handle.
+        let errors = [
+            "pub trait S : Send + Sync { fn f() -> Box<dyn O>; } pub trait O : Send + Sync { fn a(&self); fn b(); }",
+            "pub trait S : Send + Sync { fn f() -> Box<dyn O>; } pub trait O : Send + Sync { fn a(self); }",
+        ];
+        for error in errors.iter() {
+            assert!(parse_from_str(&ctx, error).is_err(), "{}", error);
+        }
+    }
+
+    #[test]
+    fn parse_instance_service_works() {
+        let ctx = ParseContext {
+            crate_name: "demo_crate".to_string(),
+            mod_name: "demo_mod".to_string(),
+            mod_path: "contract::demo_mod".to_string(),
+        };
+        let contract_str = "
+            pub struct Config {
+                pub name: String,
+            }
+
+            pub trait Counter : Send + Sync {
+                fn new(config: Config) -> Self;
+                fn value(&self) -> i64;
+                fn add(&mut self, step: i64) -> i64;
+            }
+        ";
+        let ContractResult { traits, .. } = parse_from_str(&ctx, contract_str).unwrap();
+
+        // Traits with constructors returning Self are held by handles like objects.
+        let counter = &traits[0];
+        assert!(counter.is_object && !counter.is_callback);
+        assert!(counter.has_constructor());
+        assert!(counter.is_constructor(&counter.methods[0]));
+        assert_eq!(
+            counter.methods[0].return_type,
+            AstType::Object(CustomType {
+                mod_name: "demo_mod".to_string(),
+                origin: "Counter".to_string(),
+            })
+        );
+        assert!(!counter.methods[1].mut_self);
+        assert!(counter.methods[2].mut_self);
+
+        let errors = [
+            "pub trait C : Send + Sync { fn new() -> Self; fn f(); }",
+            "pub trait C : Send + Sync { fn new() -> Self; fn f(self); }",
+            "pub trait C : Send + Sync { fn new() -> Self; fn f(&self) -> Self; }",
+            "pub trait S : Send + Sync { fn f() -> Box<dyn O>; } pub trait O : Send + Sync { fn a(&mut self); }",
+        ];
+        for error in errors.iter() {
+            assert!(parse_from_str(&ctx, error).is_err(), "{}", error);
+        }
+    }
+
+    #[test]
+    fn parse_supertrait_works() {
+        let ctx = ParseContext {
+            crate_name: "demo_crate".to_string(),
+            mod_name: "demo_mod".to_string(),
+            mod_path: "contract::demo_mod".to_string(),
+        };
+        let contract_str = "
+            pub trait UserService : Send + Sync {
+                fn user_name(id: i64) -> String;
+            }
+
+            pub trait AdminService : UserService + Send + Sync {
+                fn ban(id: i64) -> bool;
+                fn watch(cb: Box<dyn AdminCallback>);
+            }
+
+            pub trait UserCallback : Send + Sync {
+                fn on_user(&self, name: String);
+            }
+
+            pub trait AdminCallback : Send + Sync + UserCallback {
+                fn on_ban(&self, id: i64) -> bool;
+            }
+
+            pub trait Named : Send + Sync {
+                fn new(name: String) -> Self;
+                fn name(&self) -> String;
+            }
+
+            pub trait Admin : Named + Send + Sync {
+                fn level(&self) -> i32;
+            }
+        ";
+        let ContractResult { traits, .. } = parse_from_str(&ctx, contract_str).unwrap();
+
+        // Inherited methods follow the methods of the trait.
+        let admin_service = &traits[1];
+        assert!(admin_service.is_service());
+        assert_eq!(admin_service.supertraits, vec!["UserService".to_string()]);
+        let names = admin_service
+            .methods
+            .iter()
+            .map(|method| method.name.as_str())
+            .collect::<Vec<&str>>();
+        assert_eq!(names, vec!["ban", "watch", "user_name"]);
+        assert_eq!(admin_service.methods[0].supertrait, None);
+        assert_eq!(
+            admin_service.methods[2].supertrait,
+            Some("UserService".to_string())
+        );
+        assert_eq!(
+            admin_service.methods[2].trait_name(admin_service),
+            "UserService"
+        );
+
+        let admin_callback = &traits[3];
+        assert!(admin_callback.is_callback);
+        assert_eq!(admin_callback.supertraits, vec!["UserCallback".to_string()]);
+        assert_eq!(admin_callback.methods.len(), 2);
+
+        // Self of the supertrait is the trait inheriting it.
+        let admin = &traits[5];
+        assert!(admin.is_object && admin.has_constructor());
+        assert_eq!(
+            admin.methods[1].return_type,
+            AstType::Object(CustomType {
+                mod_name: "demo_mod".to_string(),
+                origin: "Admin".to_string(),
+            })
+        );
+
+        let errors = [
+            "pub trait A : Send + Sync + Debug { fn f(); }",
+            "pub trait A : B + Send + Sync { fn f(); } pub trait B : Send + Sync {}",
+            "pub trait A : B + Send + Sync { fn f(); } pub trait B : A + Send + Sync { fn g(); }",
+            "pub trait A : B + Send + Sync { fn f(&self); } pub trait B : Send + Sync { fn g(); }",
+        ];
+        for error in errors.iter() {
+            assert!(parse_from_str(&ctx, error).is_err(), "{}", error);
+        }
+    }
+
+    #[test]
+    fn find_imp_of_supertrait_works() {
+        let ctx = ParseContext {
+            crate_name: "demo_crate".to_string(),
+            mod_name: "demo_mod".to_string(),
+            mod_path: "contract::demo_mod".to_string(),
+        };
+        let contract_str = "
+            pub trait UserService : Send + Sync {
+                fn user_name(id: i64) -> String;
+            }
+
+            pub trait AdminService : UserService + Send + Sync {
+                fn ban(id: i64) -> bool;
+            }
+
+            pub trait RootService : AdminService + Send + Sync {
+                fn reset();
+            }
+        ";
+        let ContractResult { traits, .. } = parse_from_str(&ctx, contract_str).unwrap();
+        let imp = |name: &str, contract: &str| ImpDesc {
+            name: name.to_string(),
+            contract: contract.to_string(),
+            mod_name: "demo_imp".to_string(),
+            mod_path: "imp::demo_imp".to_string(),
+        };
+        let ast = AstResult {
+            traits: HashMap::from([("demo_mod".to_string(), traits.clone())]),
+            imps: vec![
+                imp("UserServiceImp", "UserService"),
+                imp("AdminServiceImp", "UserService"),
+                imp("AdminServiceImp", "AdminService"),
+                imp("RootServiceImp", "UserService"),
+                imp("RootServiceImp", "AdminService"),
+                imp("RootServiceImp", "RootService"),
+            ],
+            ..AstResult::default()
+        };
+
+        // The implementations of the subtraits are skipped for the supertraits.
+        let names = traits
+            .iter()
+            .map(|desc| ast.find_imp(desc).unwrap().unwrap().name.as_str())
+            .collect::<Vec<&str>>();
+        assert_eq!(
+            names,
+            vec!["UserServiceImp", "AdminServiceImp", "RootServiceImp"]
+        );
+
+        // The implementation of a subtrait is the only one of the supertrait.
+        let ast_without_user = AstResult {
+            imps: ast.imps[1..3].to_vec(),
+            ..ast.clone()
+        };
+        assert_eq!(
+            ast_without_user.find_imp(&traits[0]).unwrap().unwrap().name,
+            "AdminServiceImp"
+        );
+        assert!(AstResult::default().find_imp(&traits[0]).unwrap().is_none());
+
+        // Two own implementations are ambiguous.
+        let mut ambiguous = ast.clone();
+        ambiguous
+            .imps
+            .push(imp("OtherUserServiceImp", "UserService"));
+        assert!(ambiguous.find_imp(&traits[0]).is_err());
+        assert!(ambiguous.find_imp(&traits[1]).is_ok());
+    }
+
+    #[test]
+    fn parse_default_method_works() {
+        let ctx = ParseContext {
+            crate_name: "demo_crate".to_string(),
+            mod_name: "demo_mod".to_string(),
+            mod_path: "contract::demo_mod".to_string(),
+        };
+        let contract_str = "
+            pub trait Listener : Send + Sync {
+                fn on_event(&self, name: String);
+                fn on_error(&self, code: i32) -> bool {
+                    self.on_event(code.to_string());
+                    false
+                }
+            }
+        ";
+        let ContractResult { traits, .. } = parse_from_str(&ctx, contract_str).unwrap();
+
+        let listener = &traits[0];
+        assert!(listener.is_callback);
+        assert!(!listener.methods[0].has_default);
+        assert!(listener.methods[1].has_default);
+
+        let errors = [
+            "pub trait S : Send + Sync { fn f(cb: Box<dyn C>); } pub trait C : Send + Sync { fn g(&self) -> Box<dyn C> { todo!() } }",
+        ];
+        for error in errors.iter() {
+            assert!(parse_from_str(&ctx, error).is_err(), "{}", error);
+        }
+    }
+
+    #[test]
+    fn parse_const_works() {
+        let ctx = ParseContext {
+            crate_name: "demo_crate".to_string(),
+            mod_name: "demo_mod".to_string(),
+            mod_path: "contract::demo_mod".to_string(),
+        };
+        let contract_str = r#"
+            pub const PROTOCOL_VERSION: u32 = 3;
+            pub const MIN_OFFSET: i64 = -10;
+            pub const RATIO: f64 = 0.5;
+            pub const ENABLED: bool = true;
+            pub const VENDOR: &'static str = "rs\"bind";
+            const PRIVATE: i32 = 1;
+            pub const LOWEST: i32 = i32::MIN;
+            pub const HIGHEST: u64 = (u64::MAX);
+            pub const SHIFTED: i32 = 1 << 4;
+            pub const TABLE: [u8; 2] = [1, 2];
+            pub const PREFIX: &[u8] = b"a";
+        "#;
+        let ContractResult { consts, .. } = parse_from_str(&ctx, contract_str).unwrap();
+
+        assert_eq!(consts.len(), 7);
+        assert_eq!(consts[0].name, "PROTOCOL_VERSION");
+        assert_eq!(consts[0].ty, AstType::Int("u32".to_string()));
+        assert_eq!(consts[0].value, ConstValue::Int(3));
+        assert_eq!(consts[1].value, ConstValue::Int(-10));
+        assert_eq!(consts[2].ty, AstType::Double("f64".to_string()));
+        assert_eq!(consts[2].value, ConstValue::Float(0.5));
+        assert_eq!(consts[3].value, ConstValue::Bool(true));
+        assert_eq!(consts[4].ty, AstType::String);
+        assert_eq!(consts[4].value, ConstValue::String("rs\"bind".to_string()));
+        assert_eq!(consts[5].value, ConstValue::Int(i32::MIN as i128));
+        assert_eq!(consts[6].value, ConstValue::Int(u64::MAX as i128));
+
+        // Only literals of primitive types and `&str` are supported, the others are skipped.
+        let skipped = [
+            "pub const A: i32 = B;",
+            "pub const A: i32 = i32::BITS;",
+            "pub const A: Vec<u8> = Vec::new();",
+            "pub const A: i32 = \"1\";",
+        ];
+        for source in skipped.iter() {
+            let ContractResult { consts, .. } = parse_from_str(&ctx, source).unwrap();
+            assert!(consts.is_empty(), "{}", source);
+        }
+    }
+
+    #[test]
+    fn parse_async_works() {
+        let ctx = ParseContext {
+            crate_name: "demo_crate".to_string(),
+            mod_name: "demo_mod".to_string(),
+            mod_path: "contract::demo_mod".to_string(),
+        };
+        let contract_str = r#"
+            pub struct DemoError {
+                pub code: i32,
+            }
+
+            pub trait DemoTrait: Send + Sync {
+                async fn fetch(name: String) -> Result<i64, DemoError>;
+                async fn ping();
+                fn sync_call(a: i32) -> i32;
+            }
+        "#;
+        let ContractResult { traits, .. } = parse_from_str(&ctx, contract_str).unwrap();
+
+        assert_eq!(traits.len(), 3);
+        let methods = &traits[0].methods;
+        assert!(methods[0].is_async);
+        assert!(methods[1].is_async);
+        assert!(!methods[2].is_async);
+
+        // Each async method has a completion callback receiving its result.
+        let fetch = &traits[1];
+        assert_eq!(fetch.name, "DemoTraitFetchCompletion");
+        assert!(fetch.is_callback);
+        assert!(fetch.is_completion);
+        assert_eq!(fetch.methods.len(), 3);
+        assert_eq!(fetch.methods[0].name, "on_complete");
+        assert_eq!(
+            fetch.methods[0].args[0].ty,
+            AstType::Long("i64".to_string())
+        );
+        assert_eq!(fetch.methods[1].name, "on_error");
+        assert_eq!(
+            fetch.methods[1].args[0].ty,
+            AstType::Struct(CustomType {
+                mod_name: "demo_mod".to_string(),
+                origin: "DemoError".to_string(),
+            })
+        );
+        let ping = &traits[2];
+        assert_eq!(ping.name, "DemoTraitPingCompletion");
+        assert_eq!(ping.methods.len(), 2);
+        assert!(ping.methods[0].args.is_empty());
+
+        // A panic of the future completes the call with the panic message.
+        for completion in [fetch, ping] {
+            let on_panic = completion.methods.last().unwrap();
+            assert_eq!(on_panic.name, "on_panic");
+            assert_eq!(on_panic.args[0].name, "message");
+            assert_eq!(on_panic.args[0].ty, AstType::String);
+        }
+
+        // The bridge passes the completion callback instead of returning the result.
+        let bridge = methods[0].to_bridge(&traits[0]);
+        assert_eq!(bridge.return_type, AstType::Void);
+        assert_eq!(bridge.args.len(), 2);
+        assert_eq!(
+            bridge.args[1].ty,
+            AstType::Callback(CustomType {
+                mod_name: "demo_mod".to_string(),
+                origin: "DemoTraitFetchCompletion".to_string(),
+            })
+        );
+
+        let errors = [
+            "pub trait Cb: Send + Sync { async fn on_event(&self, a: i32); }",
+            "pub trait Obj: Send + Sync { fn new() -> Self; async fn get(&self) -> i32; }",
+            "pub trait Svc: Send + Sync { async fn get(name: &str) -> i32; }",
+            "pub trait Svc: Send + Sync { async fn get() -> Box<dyn Cb>; } pub trait Cb: Send + Sync { fn on(&self); }",
+            "pub trait Svc: Send + Sync { async fn get() -> i32; } pub trait SvcGetCompletion: Send + Sync { fn on(&self); }",
+        ];
+        for error in errors.iter() {
+            assert!(parse_from_str(&ctx, error).is_err(), "{}", error);
+        }
+    }
+
+    #[test]
+    fn parse_sequence_works() {
+        let ctx = ParseContext {
+            crate_name: "demo_crate".to_string(),
+            mod_name: "demo_mod".to_string(),
+            mod_path: "contract::demo_mod".to_string(),
+        };
+        let contract_str = r#"
+            use std::pin::Pin;
+            use futures_core::Stream;
+
+            pub trait Feed: Send + Sync {
+                fn hits(limit: i32) -> Box<dyn Iterator<Item = String> + Send>;
+                fn ticks() -> Pin<Box<dyn Stream<Item = i64> + Send>>;
+            }
+        "#;
+        let ContractResult { traits, .. } = parse_from_str(&ctx, contract_str).unwrap();
+
+        assert_eq!(traits.len(), 4);
+        let methods = &traits[0].methods;
+        assert_eq!(methods[0].sequence, Some(SequenceKind::Iterator));
+        assert_eq!(methods[1].sequence, Some(SequenceKind::Stream));
+
+        // The methods return the objects pulling the items, which are declared by the bridge.
+        assert_eq!(
+            methods[0].return_type,
+            AstType::Object(CustomType {
+                mod_name: "demo_mod".to_string(),
+                origin: "FeedHitsIterator".to_string(),
+            })
+        );
+        let hits = &traits[1];
+        assert_eq!(hits.name, "FeedHitsIterator");
+        assert!(hits.is_object);
+        assert_eq!(hits.sequence, Some(SequenceKind::Iterator));
+        assert_eq!(hits.methods[0].name, "next_item");
+        assert!(!hits.methods[0].is_async);
+        assert_eq!(hits.sequence_item(), Some(AstType::String));
+
+        // Streams pull the next item asynchronously, with a completion callback.
+        let ticks = &traits[2];
+        assert_eq!(ticks.name, "FeedTicksStream");
+        assert_eq!(ticks.sequence, Some(SequenceKind::Stream));
+        assert!(ticks.methods[0].is_async);
+        assert_eq!(
+            ticks.sequence_item(),
+            Some(AstType::Long("i64".to_string()))
+        );
+        assert_eq!(traits[3].name, "FeedTicksStreamNextItemCompletion");
+        assert!(traits[3].is_completion);
+
+        let errors = [
+            "pub trait Svc: Send + Sync { fn all() -> Box<dyn Iterator<Item = i32>>; }",
+            "pub trait Svc: Send + Sync { fn all() -> Box<dyn Iterator<Item = Option<i32>> + Send>; }",
+            "pub trait Cb: Send + Sync { fn on_all(&self) -> Box<dyn Iterator<Item = i32> + Send>; }",
+            "pub trait Svc: Send + Sync { async fn all() -> Box<dyn Iterator<Item = i32> + Send>; }",
+            "pub trait Svc: Send + Sync { fn all() -> Box<dyn Iterator<Item = i32> + Send>; } pub trait SvcAllIterator: Send + Sync { fn on(&self); }",
+        ];
+        for error in errors.iter() {
+            assert!(parse_from_str(&ctx, error).is_err(), "{}", error);
+        }
+    }
+
+    #[test]
+    fn parse_cancellation_works() {
+        let ctx = ParseContext {
+            crate_name: "demo_crate".to_string(),
+            mod_name: "demo_mod".to_string(),
+            mod_path: "contract::demo_mod".to_string(),
+        };
+        let contract_str = r#"
+            use tokio_util::sync::CancellationToken;
+
+            pub trait Downloader: Send + Sync {
+                fn download(url: String, token: CancellationToken) -> bool;
+                async fn fetch(url: String, token: CancellationToken) -> i64;
+                fn check(token: &CancellationToken) -> bool;
+            }
+        "#;
+        let ContractResult { traits, .. } = parse_from_str(&ctx, contract_str).unwrap();
+
+        let methods = &traits[0].methods;
+        assert_eq!(
+            methods[0].args[1].ty,
+            AstType::Cancellation("CancellationToken".to_string())
+        );
+        assert!(methods[1].is_async);
+        assert_eq!(
+            methods[1].args[1].ty,
+            AstType::Cancellation("CancellationToken".to_string())
+        );
+        assert!(methods[2].args[0].borrowed);
+
+        let errors = [
+            "pub trait Svc: Send + Sync { fn token() -> CancellationToken; }",
+            "pub trait Cb: Send + Sync { fn on_start(&self, token: CancellationToken); }",
+            "pub struct Job { pub token: CancellationToken }",
+        ];
+        for error in errors.iter() {
+            assert!(parse_from_str(&ctx, error).is_err(), "{}", error);
+        }
+    }
+
+    #[test]
+    fn dispatch_config_works() {
+        let ctx = ParseContext {
+            crate_name: "demo_crate".to_string(),
+            mod_name: "demo_mod".to_string(),
+            mod_path: "contract::demo_mod".to_string(),
+        };
+        let contract_str = r#"
+            pub trait Listener: Send + Sync {
+                fn on_progress(&self, value: i32);
+                fn on_done(&self);
+                fn on_check(&self) -> bool;
+            }
+            pub trait Svc: Send + Sync {
+                fn listen(listener: Box<dyn Listener>);
+            }
+        "#;
+        let ContractResult { traits, .. } = parse_from_str(&ctx, contract_str).unwrap();
+        let ast = AstResult {
+            traits: HashMap::from([("demo_mod".to_string(), traits)]),
+            ..AstResult::default()
+        };
+        let policies = |pairs: &[(&str, &str)]| {
+            pairs
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect::<HashMap<String, String>>()
+        };
+
+        // The trait policy applies to the methods without return value, methods override it.
+        let dispatched = ast
+            .clone()
+            .with_dispatch(&policies(&[
+                ("Listener", "main"),
+                ("Listener.on_done", "inline"),
+            ]))
+            .unwrap();
+        let methods = &dispatched.traits["demo_mod"][0].methods;
+        assert_eq!(methods[0].dispatch, Some("main".to_string()));
+        assert_eq!(methods[1].dispatch, None);
+        assert_eq!(methods[2].dispatch, None);
+        assert!(dispatched.has_dispatch());
+        assert!(!ast.has_dispatch());
+
+        let errors = [
+            ("Unknown", "main"),
+            ("Svc", "main"),
+            ("Listener.on_unknown", "main"),
+            ("Listener.on_check", "main"),
+            ("Listener", ""),
+            ("Listener", "main\""),
+        ];
+        for (key, value) in errors.iter() {
+            assert!(
+                ast.clone()
+                    .with_dispatch(&policies(&[(key, value)]))
+                    .is_err(),
+                "{}",
+                key
+            );
+        }
+    }
 }
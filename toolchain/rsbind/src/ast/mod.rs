@@ -11,6 +11,7 @@ use crate::errors::*;
 
 use self::contract::desc::*;
 use self::imp::desc::*;
+use self::types::AstType;
 
 pub(crate) mod contract;
 pub(crate) mod imp;
@@ -27,6 +28,10 @@ pub(crate) struct AstResult {
     pub traits: HashMap<String, Vec<TraitDesc>>,
     /// All the structs, key is mod name , value is all structs.
     pub structs: HashMap<String, Vec<StructDesc>>,
+    /// All the enums, key is mod name , value is all enums.
+    pub enums: HashMap<String, Vec<EnumDesc>>,
+    /// All the constants, key is mod name , value is all constants.
+    pub consts: HashMap<String, Vec<ConstDesc>>,
     /// All the implementations.
     pub imps: Vec<ImpDesc>,
 }
@@ -36,6 +41,8 @@ impl Default for AstResult {
         AstResult {
             traits: HashMap::default(),
             structs: HashMap::default(),
+            enums: HashMap::default(),
+            consts: HashMap::default(),
             imps: vec![],
         }
     }
@@ -46,6 +53,10 @@ struct IndexedContract {
     pub traits: HashMap<String, Vec<TraitDesc>>,
     /// All the structs, key is mod name , value is all structs.
     pub structs: HashMap<String, Vec<StructDesc>>,
+    /// All the enums, key is mod name , value is all enums.
+    pub enums: HashMap<String, Vec<EnumDesc>>,
+    /// All the constants, key is mod name , value is all constants.
+    pub consts: HashMap<String, Vec<ConstDesc>>,
 }
 
 impl AstHandler {
@@ -154,9 +165,12 @@ impl AstHandler {
         let imp_file = origin_prj_path.join(imp_file);
         let rsbind_file = origin_prj_path.join(rsbind_file);
 
-        let IndexedContract { traits, structs } = if contract_dir_path.is_dir()
-            && contract_dir_path.exists()
-        {
+        let IndexedContract {
+            traits,
+            structs,
+            enums,
+            consts,
+        } = if contract_dir_path.is_dir() && contract_dir_path.exists() {
             self.parse_contract_from_dir(&contract_dir_path, &contract_str)?
         }
         // contract.rs
@@ -186,11 +200,18 @@ impl AstHandler {
             return Ok(AstResult::default())
         };
 
-        Ok(AstResult {
+        let dispatch = config
+            .as_ref()
+            .map(|config| config.dispatch())
+            .unwrap_or_default();
+        AstResult {
             traits,
             structs,
+            enums,
+            consts,
             imps,
-        })
+        }
+        .with_dispatch(&dispatch)
     }
 
     fn parse_contract_from_dir(
@@ -200,6 +221,8 @@ impl AstHandler {
     ) -> Result<IndexedContract> {
         let mut traits = HashMap::new();
         let mut structs = HashMap::new();
+        let mut enums = HashMap::new();
+        let mut consts = HashMap::new();
 
         let contract_dir = fs::read_dir(&contract_dir_path)?;
         for file in contract_dir {
@@ -218,28 +241,316 @@ impl AstHandler {
                 .unwrap()
                 .to_string();
             let mod_path = format!("{}::{}", contract_name, &mod_name);
-            let results =
-                contract::parser::parse(self.crate_name.clone(), &path, &mod_path).unwrap();
+            let results = contract::parser::parse(self.crate_name.clone(), &path, &mod_path)?;
             traits.insert(mod_name.to_owned(), results.traits);
             structs.insert(mod_name.to_owned(), results.structs);
+            enums.insert(mod_name.to_owned(), results.enums);
+            consts.insert(mod_name.to_owned(), results.consts);
         }
 
-        Ok(IndexedContract { traits, structs })
+        Ok(IndexedContract {
+            traits,
+            structs,
+            enums,
+            consts,
+        })
     }
 
     fn parse_from_file(&self, contract_file: &Path, mod_mame: &str) -> Result<IndexedContract> {
         let mut traits = HashMap::new();
         let mut structs = HashMap::new();
+        let mut enums = HashMap::new();
+        let mut consts = HashMap::new();
 
         let results = contract::parser::parse(self.crate_name.clone(), &contract_file, mod_mame)?;
         traits.insert(mod_mame.to_owned(), results.traits);
         structs.insert(mod_mame.to_owned(), results.structs);
+        enums.insert(mod_mame.to_owned(), results.enums);
+        consts.insert(mod_mame.to_owned(), results.consts);
 
-        Ok(IndexedContract { traits, structs })
+        Ok(IndexedContract {
+            traits,
+            structs,
+            enums,
+            consts,
+        })
     }
 }
 
 impl AstResult {
+    ///
+    /// The numbers of elements of all the tuples in arguments and return types, sorted.
+    ///
+    pub(crate) fn tuple_arities(&self) -> Vec<usize> {
+        let mut arities = vec![];
+        for method in self
+            .traits
+            .values()
+            .flat_map(|descs| descs.iter())
+            .flat_map(|desc| desc.methods.iter())
+        {
+            let tys = method.args.iter().map(|arg| &arg.ty);
+            for ty in tys.chain(std::iter::once(&method.return_type)) {
+                if let AstType::Tuple(ref elems) = ty {
+                    if !arities.contains(&elems.len()) {
+                        arities.push(elems.len());
+                    }
+                }
+            }
+        }
+        arities.sort_unstable();
+        arities
+    }
+
+    ///
+    /// The implementation of a service, none if it has no implementation.
+    /// Types implementing a subtrait of the service, like the implementation of `AdminService: UserService`,
+    /// implement the service as well, so they are skipped when the service has other implementations.
+    ///
+    pub(crate) fn find_imp(&self, trait_: &TraitDesc) -> Result<Option<&ImpDesc>> {
+        let imps = self
+            .imps
+            .iter()
+            .filter(|imp| imp.contract == trait_.name)
+            .collect::<Vec<&ImpDesc>>();
+        if imps.is_empty() {
+            println!("No impl found for trait {}", trait_.name);
+        }
+        if imps.len() <= 1 {
+            return Ok(imps.first().copied());
+        }
+
+        let own_imps = imps
+            .iter()
+            .filter(|imp| !self.implements_subtrait(imp, &trait_.name))
+            .copied()
+            .collect::<Vec<&ImpDesc>>();
+        match own_imps.as_slice() {
+            [imp] => Ok(Some(imp)),
+            _ => Err(GenerateError(format!(
+                "You have more than one impl for trait {}: {}.",
+                trait_.name,
+                imps.iter()
+                    .map(|imp| imp.name.as_str())
+                    .collect::<Vec<&str>>()
+                    .join(", ")
+            ))
+            .into()),
+        }
+    }
+
+    ///
+    /// Whether the type of `imp` also implements a trait inheriting `name`.
+    ///
+    fn implements_subtrait(&self, imp: &ImpDesc, name: &str) -> bool {
+        self.imps
+            .iter()
+            .filter(|other| other.name == imp.name && other.mod_path == imp.mod_path)
+            .any(|other| other.contract != name && self.inherits(&other.contract, name))
+    }
+
+    ///
+    /// Whether the trait `sub` inherits `name`, through the supertraits of supertraits as well.
+    ///
+    fn inherits(&self, sub: &str, name: &str) -> bool {
+        let mut visited: Vec<&str> = vec![];
+        let mut pending = vec![sub];
+        while let Some(current) = pending.pop() {
+            if visited.contains(&current) {
+                continue;
+            }
+            visited.push(current);
+            let desc = self
+                .traits
+                .values()
+                .flat_map(|descs| descs.iter())
+                .find(|desc| desc.name == current);
+            if let Some(desc) = desc {
+                if desc.supertraits.iter().any(|supertrait| supertrait == name) {
+                    return true;
+                }
+                pending.extend(
+                    desc.supertraits
+                        .iter()
+                        .map(|supertrait| supertrait.as_str()),
+                );
+            }
+        }
+        false
+    }
+
+    ///
+    /// Whether any method takes a cancellation token, the bridge depends on `tokio-util` for it.
+    ///
+    pub(crate) fn has_cancellation(&self) -> bool {
+        self.traits
+            .values()
+            .flat_map(|descs| descs.iter())
+            .flat_map(|desc| desc.methods.iter())
+            .flat_map(|method| method.args.iter())
+            .any(|arg| matches!(arg.ty, AstType::Cancellation(_)))
+    }
+
+    ///
+    /// Whether any method returns a buffer backed by rust memory.
+    ///
+    pub(crate) fn has_buffer(&self) -> bool {
+        self.traits
+            .values()
+            .flat_map(|descs| descs.iter())
+            .flat_map(|desc| desc.methods.iter())
+            .any(|method| matches!(method.return_type, AstType::Buffer(_)))
+    }
+
+    ///
+    /// Whether any method returns a stream, which needs the `Stream` trait of `futures-core` in the bridge.
+    ///
+    pub(crate) fn has_stream(&self) -> bool {
+        self.traits
+            .values()
+            .flat_map(|descs| descs.iter())
+            .any(|desc| desc.sequence == Some(SequenceKind::Stream))
+    }
+
+    ///
+    /// Transparent newtypes are the same as their inner types in native code.
+    ///
+    pub(crate) fn without_newtypes(&self) -> Self {
+        self.map_types(|ty| ty.unwrap_newtype())
+    }
+
+    ///
+    /// Unsigned integers are widened for languages without unsigned types.
+    ///
+    pub(crate) fn widen_unsigned(&self) -> Self {
+        self.map_types(|ty| ty.clone().widen_unsigned())
+    }
+
+    ///
+    /// Whether any callback method is run by a dispatcher registered by native code.
+    ///
+    pub(crate) fn has_dispatch(&self) -> bool {
+        self.traits
+            .values()
+            .flat_map(|descs| descs.iter())
+            .flat_map(|desc| desc.methods.iter())
+            .any(|method| method.dispatch.is_some())
+    }
+
+    ///
+    /// Set the dispatchers of the callback methods from the `dispatch` config, keyed by the trait name
+    /// or `Trait.method`. A trait policy applies to the methods without return value, which don't
+    /// block rust, and the policies of methods override it. `inline` runs the method on the rust thread.
+    ///
+    pub(crate) fn with_dispatch(mut self, policies: &HashMap<String, String>) -> Result<Self> {
+        let mut keys = policies.keys().collect::<Vec<&String>>();
+        // Trait policies are applied before the policies of their methods.
+        keys.sort_by_key(|key| (key.contains('.'), key.to_string()));
+        for key in keys.into_iter() {
+            let policy = &policies[key];
+            if policy.is_empty()
+                || !policy
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.')
+            {
+                return Err(ParseError(format!(
+                    "Invalid dispatch policy '{}' of '{}', it should be inline or a dispatcher name.",
+                    policy, key
+                ))
+                .into());
+            }
+            let dispatch = match policy.as_str() {
+                "inline" => None,
+                name => Some(name.to_string()),
+            };
+
+            let (trait_name, method_name) = match key.split_once('.') {
+                Some((trait_name, method_name)) => (trait_name, Some(method_name)),
+                None => (key.as_str(), None),
+            };
+            let desc = self
+                .traits
+                .values_mut()
+                .flat_map(|descs| descs.iter_mut())
+                .find(|desc| desc.is_callback && desc.name == trait_name)
+                .ok_or_else(|| {
+                    ParseError(format!(
+                        "Unknown callback '{}' in the dispatch config.",
+                        trait_name
+                    ))
+                })?;
+            match method_name {
+                Some(method_name) => {
+                    let method = desc
+                        .methods
+                        .iter_mut()
+                        .find(|method| method.name == method_name)
+                        .ok_or_else(|| {
+                            ParseError(format!(
+                                "Unknown method '{}' of callback '{}' in the dispatch config.",
+                                method_name, trait_name
+                            ))
+                        })?;
+                    if method.return_type != AstType::Void {
+                        return Err(ParseError(format!(
+                            "Callback method '{}' returns a value, only methods without return value can be dispatched.",
+                            key
+                        ))
+                        .into());
+                    }
+                    method.dispatch = dispatch;
+                }
+                None => {
+                    for method in desc
+                        .methods
+                        .iter_mut()
+                        .filter(|method| method.return_type == AstType::Void)
+                    {
+                        method.dispatch = dispatch.clone();
+                    }
+                }
+            }
+        }
+        Ok(self)
+    }
+
+    fn map_types<F>(&self, f: F) -> Self
+    where
+        F: Fn(&AstType) -> AstType,
+    {
+        let map_args = |args: &mut Vec<ArgDesc>| {
+            for arg in args.iter_mut() {
+                arg.ty = f(&arg.ty);
+            }
+        };
+
+        let mut ast = self.clone();
+        for method in ast
+            .traits
+            .values_mut()
+            .flat_map(|descs| descs.iter_mut())
+            .flat_map(|desc| desc.methods.iter_mut())
+        {
+            method.return_type = f(&method.return_type);
+            map_args(&mut method.args);
+        }
+        for desc in ast.structs.values_mut().flat_map(|descs| descs.iter_mut()) {
+            map_args(&mut desc.fields);
+        }
+        for variant in ast
+            .enums
+            .values_mut()
+            .flat_map(|descs| descs.iter_mut())
+            .flat_map(|desc| desc.variants.iter_mut())
+        {
+            map_args(&mut variant.fields);
+        }
+        for desc in ast.consts.values_mut().flat_map(|descs| descs.iter_mut()) {
+            desc.ty = f(&desc.ty);
+        }
+        ast
+    }
+
     pub(crate) fn flush(self, ast_dir: &Path) -> Result<Self> {
         for each_mod in self.traits.iter() {
             let trait_desc_list = each_mod.1;
@@ -271,6 +582,33 @@ impl AstResult {
             }
         }
 
+        for each_mod in self.enums.iter() {
+            let enum_desc_list = each_mod.1;
+            for enum_desc in enum_desc_list {
+                let json =
+                    serde_json::to_string(enum_desc).map_err(|e| GenerateError(e.to_string()))?;
+
+                let file_name =
+                    ast_dir.join(&format!("{}_{}.json", &enum_desc.mod_name, &enum_desc.name));
+                let mut ast_file = fs::File::create(&file_name)?;
+                ast_file.write_all(&json.into_bytes())?;
+            }
+        }
+
+        for each_mod in self.consts.iter() {
+            for const_desc in each_mod.1 {
+                let json =
+                    serde_json::to_string(const_desc).map_err(|e| GenerateError(e.to_string()))?;
+
+                let file_name = ast_dir.join(&format!(
+                    "{}_{}.json",
+                    &const_desc.mod_name, &const_desc.name
+                ));
+                let mut ast_file = fs::File::create(&file_name)?;
+                ast_file.write_all(&json.into_bytes())?;
+            }
+        }
+
         Ok(self)
     }
 }
@@ -1,4 +1,6 @@
 use crate::ast::contract::parser::ParseContext;
+use crate::errors::ErrorKind::*;
+use crate::errors::*;
 use std::convert::From;
 
 ///
@@ -13,10 +15,16 @@ pub(crate) enum AstBaseType {
     Long(String),
     Float(String),
     Double(String),
+    /// `u64` widened for languages without unsigned types, like `BigInteger` in java.
+    BigInt(String),
     Boolean,
     String,
     Callback(CustomType),
     Struct(CustomType),
+    Enum(CustomType),
+    /// Collections can be nested, like `Vec<Vec<u8>>` or `Vec<Option<i32>>`.
+    Vec(Box<AstBaseType>),
+    Optional(Box<AstBaseType>),
 }
 
 impl<'a> AstBaseType {
@@ -25,10 +33,11 @@ impl<'a> AstBaseType {
         match ident {
             "u8" | "i8" => AstBaseType::Byte(origin),
             "u16" | "i16" => AstBaseType::Short(origin),
-            "u32" | "i32" | "isize" | "usize" => AstBaseType::Int(origin),
+            "u32" | "i32" => AstBaseType::Int(origin),
             "f32" => AstBaseType::Float(origin),
             "f64" => AstBaseType::Double(origin),
-            "u64" | "i64" => AstBaseType::Long(origin),
+            // Pointer-sized integers are always transferred with 64 bits.
+            "u64" | "i64" | "usize" | "isize" => AstBaseType::Long(origin),
             "str" | "String" => AstBaseType::String,
             "bool" => AstBaseType::Boolean,
             // Right now, all callbacks are wrapped with Box
@@ -37,7 +46,7 @@ impl<'a> AstBaseType {
                 origin: sub.to_string(),
             }),
             // If the ident can't recognized, we assume it is a struct,
-            // but if we add enum support, it should be changed.
+            // enums are resolved after the whole contract file is parsed.
             _ => AstBaseType::Struct(CustomType {
                 mod_name: ctx.mod_name.clone(),
                 origin: sub.to_string(),
@@ -53,11 +62,46 @@ impl<'a> AstBaseType {
             AstBaseType::Long(origin) => origin.clone(),
             AstBaseType::Float(origin) => origin.clone(),
             AstBaseType::Double(origin) => origin.clone(),
+            AstBaseType::BigInt(origin) => origin.clone(),
             AstBaseType::Boolean => "bool".to_owned(),
             AstBaseType::String => "String".to_owned(),
             AstBaseType::Callback(origin) => origin.origin.clone(),
             AstBaseType::Struct(origin) => origin.origin.clone(),
+            AstBaseType::Enum(origin) => origin.origin.clone(),
             AstBaseType::Short(origin) => origin.clone(),
+            AstBaseType::Vec(base) => format!("Vec<{}>", &base.origin()),
+            AstBaseType::Optional(base) => format!("Option<{}>", &base.origin()),
+        }
+    }
+
+    ///
+    /// Turn the struct type into enum type if its name is one of the enums.
+    ///
+    pub(crate) fn resolve_enum(self, enums: &[String]) -> Self {
+        match self {
+            AstBaseType::Struct(origin) if enums.contains(&origin.origin) => {
+                AstBaseType::Enum(origin)
+            }
+            AstBaseType::Vec(base) => AstBaseType::Vec(Box::new(base.resolve_enum(enums))),
+            AstBaseType::Optional(base) => {
+                AstBaseType::Optional(Box::new(base.resolve_enum(enums)))
+            }
+            _ => self,
+        }
+    }
+
+    ///
+    /// Widen the unsigned integers to the next signed type, so values above the signed maximum
+    /// are not wrapped. `u8` is kept as a byte, `u64` becomes a big integer.
+    ///
+    pub(crate) fn widen_unsigned(self) -> Self {
+        match self {
+            AstBaseType::Short(origin) if origin == "u16" => AstBaseType::Int(origin),
+            AstBaseType::Int(origin) if origin == "u32" => AstBaseType::Long(origin),
+            AstBaseType::Long(origin) if origin == "u64" => AstBaseType::BigInt(origin),
+            AstBaseType::Vec(base) => AstBaseType::Vec(Box::new(base.widen_unsigned())),
+            AstBaseType::Optional(base) => AstBaseType::Optional(Box::new(base.widen_unsigned())),
+            _ => self,
         }
     }
 }
@@ -77,11 +121,32 @@ pub(crate) enum AstType {
     Long(String),
     Float(String),
     Double(String),
+    /// `u64` widened for languages without unsigned types, like `BigInteger` in java.
+    BigInt(String),
     Boolean,
     String,
     Vec(AstBaseType),
+    Optional(AstBaseType),
+    Result(AstBaseType, AstBaseType),
+    /// `HashMap` or `BTreeMap`, with the key type and the value type.
+    Map(String, AstBaseType, AstBaseType),
     Callback(CustomType),
     Struct(CustomType),
+    Enum(CustomType),
+    /// Tuples like `(i32, String)`, only supported in arguments and return types.
+    Tuple(Vec<AstBaseType>),
+    /// Tuple structs with `#[repr(transparent)]` like `struct UserId(i64)`,
+    /// they are exported as the inner type.
+    Newtype(CustomType, AstBaseType),
+    /// `bytes::Bytes` exported as a buffer backed by rust memory without copying,
+    /// only supported in return types.
+    Buffer(String),
+    /// `tokio_util::sync::CancellationToken` created and cancelled by native code,
+    /// only supported in arguments.
+    Cancellation(String),
+    /// `Box<dyn Trait>` implemented by rust, exported as a class holding the handle,
+    /// only supported in return types.
+    Object(CustomType),
 }
 
 ///
@@ -93,19 +158,22 @@ impl<'a> AstType {
         match ident {
             "u8" | "i8" => AstType::Byte(origin),
             "u16" | "i16" => AstType::Short(origin),
-            "u32" | "i32" | "isize" | "usize" => AstType::Int(origin),
+            "u32" | "i32" => AstType::Int(origin),
             "f32" => AstType::Float(origin),
             "f64" => AstType::Double(origin),
-            "u64" | "i64" => AstType::Long(origin),
+            // Pointer-sized integers are always transferred with 64 bits.
+            "u64" | "i64" | "usize" | "isize" => AstType::Long(origin),
             "str" | "String" => AstType::String,
             "bool" => AstType::Boolean,
+            "Bytes" => AstType::Buffer(origin),
+            "CancellationToken" => AstType::Cancellation(origin),
             // Right now, all callbacks are wrapped with Box
             "Box" => AstType::Callback(CustomType {
                 mod_name: ctx.mod_name.clone(),
                 origin: sub.to_string(),
             }),
             // If the ident can't recognized, we assume it is a struct,
-            // but if we add enum support, it should be changed.
+            // enums are resolved after the whole contract file is parsed.
             _ => AstType::Struct(CustomType {
                 mod_name: ctx.mod_name.clone(),
                 origin: sub.to_string(),
@@ -122,11 +190,31 @@ impl<'a> AstType {
             AstType::Long(origin) => origin.clone(),
             AstType::Float(origin) => origin.clone(),
             AstType::Double(origin) => origin.clone(),
+            AstType::BigInt(origin) => origin.clone(),
             AstType::Boolean => "bool".to_owned(),
             AstType::String => "String".to_owned(),
             AstType::Vec(base) => format!("Vec<{}>", &base.origin()),
+            AstType::Optional(base) => format!("Option<{}>", &base.origin()),
+            AstType::Result(AstBaseType::Void, err) => format!("Result<(), {}>", &err.origin()),
+            AstType::Result(ok, err) => format!("Result<{}, {}>", &ok.origin(), &err.origin()),
+            AstType::Map(map, key, value) => {
+                format!("{}<{}, {}>", map, &key.origin(), &value.origin())
+            }
             AstType::Callback(origin) => origin.origin.clone(),
             AstType::Struct(origin) => origin.origin.clone(),
+            AstType::Enum(origin) => origin.origin.clone(),
+            AstType::Tuple(elems) => format!(
+                "({})",
+                elems
+                    .iter()
+                    .map(|elem| elem.origin())
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+            AstType::Newtype(origin, _) => origin.origin.clone(),
+            AstType::Buffer(origin) => origin.clone(),
+            AstType::Cancellation(origin) => origin.clone(),
+            AstType::Object(origin) => origin.origin.clone(),
         }
     }
 }
@@ -141,10 +229,14 @@ impl From<AstBaseType> for AstType {
             AstBaseType::Long(origin) => AstType::Long(origin),
             AstBaseType::Float(origin) => AstType::Float(origin),
             AstBaseType::Double(origin) => AstType::Double(origin),
+            AstBaseType::BigInt(origin) => AstType::BigInt(origin),
             AstBaseType::Boolean => AstType::Boolean,
             AstBaseType::String => AstType::String,
             AstBaseType::Callback(origin) => AstType::Callback(origin),
             AstBaseType::Struct(origin) => AstType::Struct(origin),
+            AstBaseType::Enum(origin) => AstType::Enum(origin),
+            AstBaseType::Vec(base) => AstType::Vec(*base),
+            AstBaseType::Optional(base) => AstType::Optional(*base),
         }
     }
 }
@@ -159,12 +251,161 @@ impl AstType {
             AstType::Long(_) => "J".to_owned(),
             AstType::Float(_) => "F".to_owned(),
             AstType::Double(_) => "D".to_owned(),
+            // Big integers are transferred as the bits of a long.
+            AstType::BigInt(_) => "J".to_owned(),
             AstType::Boolean => "I".to_owned(),
             AstType::String => "Ljava/lang/String;".to_owned(),
             AstType::Callback(_) => "Ljava/lang/String;".to_owned(),
             AstType::Struct(_) => "Ljava/lang/String;".to_owned(),
+            AstType::Enum(_) => "I".to_owned(),
             AstType::Vec(AstBaseType::Byte(_)) => "[B".to_owned(),
             AstType::Vec(_) => "Ljava/lang/String;".to_owned(),
+            AstType::Optional(_) => "Ljava/lang/String;".to_owned(),
+            AstType::Map(_, _, _) => "Ljava/lang/String;".to_owned(),
+            AstType::Result(_, _) => self.ok_type().to_java_sig(),
+            AstType::Tuple(_) => "Ljava/lang/String;".to_owned(),
+            AstType::Newtype(_, _) => self.unwrap_newtype().to_java_sig(),
+            // Buffers are transferred as the handle of rust memory.
+            AstType::Buffer(_) => "J".to_owned(),
+            // Tokens are transferred as the handle held by the java class.
+            AstType::Cancellation(_) => "J".to_owned(),
+            // Objects are transferred as the handle of the boxed trait object.
+            AstType::Object(_) => "J".to_owned(),
+        }
+    }
+}
+
+impl AstType {
+    ///
+    /// The type of the value on success, `T` for `Result<T, E>` and the type itself otherwise.
+    ///
+    pub(crate) fn ok_type(&self) -> AstType {
+        match self {
+            AstType::Result(ok, _) => AstType::from(ok.clone()),
+            _ => self.clone(),
+        }
+    }
+
+    ///
+    /// The error type `E` for `Result<T, E>`.
+    ///
+    pub(crate) fn err_type(&self) -> Option<AstBaseType> {
+        match self {
+            AstType::Result(_, err) => Some(err.clone()),
+            _ => None,
+        }
+    }
+
+    ///
+    /// Turn the struct types inside into enum types if their names are one of the enums.
+    ///
+    pub(crate) fn resolve_enum(self, enums: &[String]) -> Self {
+        match self {
+            AstType::Struct(origin) if enums.contains(&origin.origin) => AstType::Enum(origin),
+            AstType::Vec(base) => AstType::Vec(base.resolve_enum(enums)),
+            AstType::Optional(base) => AstType::Optional(base.resolve_enum(enums)),
+            AstType::Map(map, key, value) => AstType::Map(map, key, value.resolve_enum(enums)),
+            AstType::Result(ok, err) => {
+                AstType::Result(ok.resolve_enum(enums), err.resolve_enum(enums))
+            }
+            AstType::Tuple(elems) => AstType::Tuple(
+                elems
+                    .into_iter()
+                    .map(|elem| elem.resolve_enum(enums))
+                    .collect(),
+            ),
+            _ => self,
+        }
+    }
+
+    ///
+    /// Turn the callback type into object type if its name is one of the objects.
+    ///
+    pub(crate) fn resolve_object(self, objects: &[String]) -> Self {
+        match self {
+            AstType::Callback(origin) if objects.contains(&origin.origin) => {
+                AstType::Object(origin)
+            }
+            _ => self,
+        }
+    }
+
+    ///
+    /// Turn the struct types into newtypes if their names are one of the transparent newtypes,
+    /// newtypes can't be put in other types.
+    ///
+    pub(crate) fn resolve_newtype(self, newtypes: &[(String, AstBaseType)]) -> Result<Self> {
+        let find = |origin: &CustomType| {
+            newtypes
+                .iter()
+                .find(|(name, _)| name == &origin.origin)
+                .map(|(_, inner)| inner.clone())
+        };
+
+        let bases = match self {
+            AstType::Struct(ref origin) => {
+                return Ok(match find(origin) {
+                    Some(inner) => AstType::Newtype(origin.clone(), inner),
+                    None => self,
+                })
+            }
+            AstType::Vec(ref base) | AstType::Optional(ref base) => vec![base.clone()],
+            AstType::Result(ref ok, ref err) => vec![ok.clone(), err.clone()],
+            AstType::Map(_, _, ref value) => vec![value.clone()],
+            AstType::Tuple(ref elems) => elems.clone(),
+            _ => vec![],
+        };
+        for base in bases.iter() {
+            let mut base = base;
+            while let AstBaseType::Vec(ref inner) | AstBaseType::Optional(ref inner) = base {
+                base = inner.as_ref();
+            }
+            if let AstBaseType::Struct(ref origin) = base {
+                if find(origin).is_some() {
+                    return Err(ParseError(format!(
+                        "Transparent newtype {} is not supported in {}, use it directly or remove #[repr(transparent)].",
+                        &origin.origin,
+                        self.origin()
+                    ))
+                    .into());
+                }
+            }
+        }
+        Ok(self)
+    }
+
+    ///
+    /// The inner type of a transparent newtype, and the type itself otherwise.
+    ///
+    pub(crate) fn unwrap_newtype(&self) -> AstType {
+        match self {
+            AstType::Newtype(_, inner) => AstType::from(inner.clone()),
+            _ => self.clone(),
+        }
+    }
+
+    ///
+    /// Widen the unsigned integers inside, see `AstBaseType::widen_unsigned`.
+    ///
+    pub(crate) fn widen_unsigned(self) -> Self {
+        match self {
+            AstType::Short(origin) if origin == "u16" => AstType::Int(origin),
+            AstType::Int(origin) if origin == "u32" => AstType::Long(origin),
+            AstType::Long(origin) if origin == "u64" => AstType::BigInt(origin),
+            AstType::Vec(base) => AstType::Vec(base.widen_unsigned()),
+            AstType::Optional(base) => AstType::Optional(base.widen_unsigned()),
+            AstType::Result(ok, err) => AstType::Result(ok.widen_unsigned(), err.widen_unsigned()),
+            AstType::Map(map, key, value) => {
+                AstType::Map(map, key.widen_unsigned(), value.widen_unsigned())
+            }
+            AstType::Tuple(elems) => AstType::Tuple(
+                elems
+                    .into_iter()
+                    .map(|elem| elem.widen_unsigned())
+                    .collect(),
+            ),
+            AstType::Newtype(origin, inner) => AstType::Newtype(origin, inner.widen_unsigned()),
+            _ => self,
         }
     }
 }
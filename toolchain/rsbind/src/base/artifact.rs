@@ -1,4 +1,6 @@
-use crate::ast::contract::desc::{StructDesc, TraitDesc};
+use crate::ast::contract::desc::{ConstDesc, EnumDesc, StructDesc, TraitDesc};
+use crate::ast::types::AstBaseType;
+use crate::errors::ErrorKind::*;
 use crate::errors::*;
 use crate::AstResult;
 use std::fs;
@@ -33,7 +35,37 @@ where
     pub gen_bridge_trait: Box<dyn Fn(&GenContext<'a, Extra>, &TraitDesc) -> Result<String>>,
     pub gen_wrapper_trait: Box<dyn Fn(&GenContext<'a, Extra>, &TraitDesc) -> Result<String>>,
     pub gen_trait: Box<dyn Fn(&GenContext<'a, Extra>, &TraitDesc) -> Result<String>>,
+    /// Generate the class holding the handle of an object trait implemented by rust.
+    pub gen_object: Box<dyn Fn(&GenContext<'a, Extra>, &TraitDesc) -> Result<String>>,
     pub gen_struct: Box<dyn Fn(&GenContext<'a, Extra>, &StructDesc) -> Result<String>>,
+    pub gen_enum: Box<dyn Fn(&GenContext<'a, Extra>, &EnumDesc) -> Result<String>>,
+    /// Generate the error type for `Result<T, E>`, returns the file name without extension and the content.
+    pub gen_error: Box<dyn Fn(&GenContext<'a, Extra>, &AstBaseType) -> Result<(String, String)>>,
+    /// Generate the value class for tuples with the given number of elements,
+    /// returns the file name without extension and the content. None if tuples are native types.
+    pub gen_tuple: Option<Box<dyn Fn(&GenContext<'a, Extra>, usize) -> Result<(String, String)>>>,
+    /// Generate the class wrapping buffers backed by rust memory,
+    /// returns the file name without extension and the content. None if buffers are native types.
+    pub gen_buffer: Option<Box<dyn Fn(&GenContext<'a, Extra>) -> Result<(String, String)>>>,
+    /// Generate the subscription of the publishers wrapping streams,
+    /// returns the file name without extension and the content. None if streams are native sequences.
+    pub gen_subscription: Option<Box<dyn Fn(&GenContext<'a, Extra>) -> Result<(String, String)>>>,
+    /// Generate the class holding the cancellation tokens passed to rust,
+    /// returns the file name without extension and the content.
+    pub gen_cancellation: Box<dyn Fn(&GenContext<'a, Extra>) -> Result<(String, String)>>,
+    /// Generate the registry of the dispatchers running callback methods,
+    /// returns the file name without extension and the content.
+    pub gen_dispatcher: Box<dyn Fn(&GenContext<'a, Extra>) -> Result<(String, String)>>,
+    /// Generate the error thrown when rust panics,
+    /// returns the file name without extension and the content. None if panics are not reported.
+    pub gen_panic: Option<Box<dyn Fn(&GenContext<'a, Extra>) -> Result<(String, String)>>>,
+    /// Generate the class releasing the handles of objects which are not closed,
+    /// returns the file name without extension and the content. None if they are released deterministically.
+    pub gen_cleaner: Option<Box<dyn Fn(&GenContext<'a, Extra>) -> Result<(String, String)>>>,
+    /// Generate the class holding the constants of all the contract modules,
+    /// returns the file name without extension and the content.
+    pub gen_constants:
+        Box<dyn Fn(&GenContext<'a, Extra>, &[ConstDesc]) -> Result<(String, String)>>,
     pub gen_manager: Box<dyn Fn(&GenContext<'a, Extra>) -> Result<String>>,
 }
 
@@ -76,7 +108,14 @@ where
         for desc in self.ast.traits.iter() {
             let descs = desc.1;
             for each in descs.iter() {
-                if !each.is_callback {
+                if each.is_object {
+                    let str = (*self.strategy.gen_object)(&ctx, each)?;
+                    let file_name = format!("{}.{}", &each.name, &self.file_ext);
+                    let path = self.gen_dir.clone().join(file_name);
+                    fs::write(path, str)?;
+                }
+
+                if each.is_service() {
                     let str = (*self.strategy.gen_bridge_trait)(&ctx, each)?;
                     let file_name = format!("Internal{}.{}", &each.name, &self.file_ext);
                     let path = self.gen_dir.clone().join(file_name);
@@ -105,6 +144,118 @@ where
             }
         }
 
+        // generate all the enums
+        for (_key, enum_descs) in self.ast.enums.iter() {
+            for enum_desc in enum_descs.iter() {
+                let enum_str = (*self.strategy.gen_enum)(&ctx, enum_desc)?;
+                let file_name = format!("{}.{}", &enum_desc.name, &self.file_ext);
+                let path = self.gen_dir.join(file_name);
+                fs::write(path, enum_str)?
+            }
+        }
+
+        // generate all the error types in `Result<T, E>`
+        let mut errors: Vec<AstBaseType> = vec![];
+        for (_key, descs) in self.ast.traits.iter() {
+            for method in descs.iter().flat_map(|desc| desc.methods.iter()) {
+                if let Some(err) = method.return_type.err_type() {
+                    if !errors.contains(&err) {
+                        errors.push(err);
+                    }
+                }
+            }
+        }
+        for err in errors.iter() {
+            let (name, error_str) = (*self.strategy.gen_error)(&ctx, err)?;
+            let file_name = format!("{}.{}", &name, &self.file_ext);
+            let path = self.gen_dir.join(file_name);
+            fs::write(path, error_str)?
+        }
+
+        // generate all the tuple classes, one for each number of elements.
+        if let Some(ref gen_tuple) = self.strategy.gen_tuple {
+            for arity in self.ast.tuple_arities().iter() {
+                let (name, tuple_str) = (*gen_tuple)(&ctx, *arity)?;
+                let file_name = format!("{}.{}", &name, &self.file_ext);
+                let path = self.gen_dir.join(file_name);
+                fs::write(path, tuple_str)?
+            }
+        }
+
+        // generate the buffer class if any method returns a buffer.
+        if let Some(ref gen_buffer) = self.strategy.gen_buffer {
+            if self.ast.has_buffer() {
+                let (name, buffer_str) = (*gen_buffer)(&ctx)?;
+                let file_name = format!("{}.{}", &name, &self.file_ext);
+                let path = self.gen_dir.join(file_name);
+                fs::write(path, buffer_str)?
+            }
+        }
+
+        // generate the subscription class if any method returns a stream.
+        if let Some(ref gen_subscription) = self.strategy.gen_subscription {
+            if self.ast.has_stream() {
+                let (name, subscription_str) = (*gen_subscription)(&ctx)?;
+                let file_name = format!("{}.{}", &name, &self.file_ext);
+                let path = self.gen_dir.join(file_name);
+                fs::write(path, subscription_str)?
+            }
+        }
+
+        // generate the cancellation class if any method takes a cancellation token.
+        if self.ast.has_cancellation() {
+            let (name, cancellation_str) = (*self.strategy.gen_cancellation)(&ctx)?;
+            let file_name = format!("{}.{}", &name, &self.file_ext);
+            let path = self.gen_dir.join(file_name);
+            fs::write(path, cancellation_str)?
+        }
+
+        // generate the dispatcher registry if any callback method is dispatched.
+        if self.ast.has_dispatch() {
+            let (name, dispatcher_str) = (*self.strategy.gen_dispatcher)(&ctx)?;
+            let file_name = format!("{}.{}", &name, &self.file_ext);
+            let path = self.gen_dir.join(file_name);
+            fs::write(path, dispatcher_str)?
+        }
+
+        if let Some(ref gen_panic) = self.strategy.gen_panic {
+            let (name, panic_str) = (*gen_panic)(&ctx)?;
+            let file_name = format!("{}.{}", &name, &self.file_ext);
+            let path = self.gen_dir.join(file_name);
+            fs::write(path, panic_str)?
+        }
+
+        if let Some(ref gen_cleaner) = self.strategy.gen_cleaner {
+            let (name, cleaner_str) = (*gen_cleaner)(&ctx)?;
+            let file_name = format!("{}.{}", &name, &self.file_ext);
+            let path = self.gen_dir.join(file_name);
+            fs::write(path, cleaner_str)?
+        }
+
+        // generate the constants class, their names must be unique among the modules.
+        let mut mod_names = self.ast.consts.keys().collect::<Vec<&String>>();
+        mod_names.sort();
+        let mut consts: Vec<ConstDesc> = vec![];
+        for desc in mod_names
+            .iter()
+            .flat_map(|key| self.ast.consts[*key].iter())
+        {
+            if let Some(found) = consts.iter().find(|each| each.name == desc.name) {
+                return Err(GenerateError(format!(
+                    "Constant {} is declared in both {} and {}, the names of constants must be unique.",
+                    &desc.name, &found.mod_name, &desc.mod_name
+                ))
+                .into());
+            }
+            consts.push(desc.clone());
+        }
+        if !consts.is_empty() {
+            let (name, constants_str) = (*self.strategy.gen_constants)(&ctx, &consts)?;
+            let file_name = format!("{}.{}", &name, &self.file_ext);
+            let path = self.gen_dir.join(file_name);
+            fs::write(path, constants_str)?
+        }
+
         let manager_result = (*self.strategy.gen_manager)(&ctx)?;
         let path = self.gen_dir.join(format!("RustLib.{}", &self.file_ext));
         fs::write(path, manager_result)?;
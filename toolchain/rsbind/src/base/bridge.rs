@@ -1,15 +1,15 @@
-use crate::ast::contract::desc::TraitDesc;
-use crate::ast::imp::desc::ImpDesc;
-use crate::ast::types::AstType;
+use crate::ast::contract::desc::{
+    EnumDesc, MethodDesc, SequenceKind, StructDesc, TraitDesc, VariantDesc,
+};
+use crate::ast::types::{AstBaseType, AstType};
 use crate::base::lang::{
-    ArgumentContext, BridgeContext, CallbackContext, Direction, LangImp, MethodContext, ModContext,
-    ServiceContext, StructContext,
+    ArgumentContext, BridgeContext, CallbackContext, Direction, EnumContext, LangImp,
+    MethodContext, ModContext, ServiceContext, StructContext,
 };
 use crate::errors::*;
 use crate::ErrorKind::GenerateError;
 use crate::{ident, AstResult};
 use proc_macro2::{Ident, TokenStream};
-use std::cmp::Ordering;
 use std::fs::File;
 use std::io::Write;
 use std::marker::PhantomData;
@@ -43,6 +43,7 @@ pub(crate) struct BaseBridgeGen<'a, Lang, Extra> {
     pub bridge_dir: &'a Path,
     pub crate_name: String,
     pub lang_imp: Box<dyn LangImp<Lang, Extra>>,
+    pub executor: Option<String>,
     pub extra: Extra,
     pub generator: FilesGenerator<Lang, Extra>,
 }
@@ -55,6 +56,7 @@ impl<'a, Lang, Extra> BaseBridgeGen<'a, Lang, Extra> {
             extra: &self.extra,
             lang_imp: &self.lang_imp,
             lang_name: self.lang_name.to_owned(),
+            executor: self.executor.clone(),
         };
         let file_vec = self.generator.gen_files(&ctx)?;
         for file_token in file_vec.into_iter() {
@@ -85,10 +87,12 @@ impl<Lang, Extra> FilesGenerator<Lang, Extra> {
     fn gen_files(&self, ctx: &BridgeContext<Lang, Extra>) -> Result<Vec<(String, TokenStream)>> {
         let mut file_vec = vec![];
         let empty_vec = vec![];
+        let empty_enum_vec = vec![];
         let mut bridges: Vec<String> = vec![];
 
         for (mod_name, trait_vec) in &ctx.ast.traits {
             let struct_vec = ctx.ast.structs.get(mod_name).unwrap_or(&empty_vec);
+            let enum_vec = ctx.ast.enums.get(mod_name).unwrap_or(&empty_enum_vec);
 
             // generate bridge files.
             let out_mod_name = format!("{}_{}", &ctx.lang_name, mod_name);
@@ -97,7 +101,7 @@ impl<Lang, Extra> FilesGenerator<Lang, Extra> {
             let ctx = ModContext {
                 traits: trait_vec,
                 structs: struct_vec,
-                imps: &ctx.ast.imps,
+                enums: enum_vec,
                 mod_name: mod_name.clone(),
                 bridge_ctx: ctx,
             };
@@ -118,15 +122,32 @@ impl<Lang, Extra> FilesGenerator<Lang, Extra> {
             #(pub mod #bridge_ident;)*
         };
 
+        let contract_idents = &bridge_ident[..bridge_ident.len() - 1];
         let sdk_tokens = (*self.quote_lib_file)(ctx)?;
         let lib_tokens = quote! {
             #sdk_tokens
             #bridge_mod_tokens
+
+            /// The callbacks implemented by rust and held by native code, for finding leaks.
+            pub fn callback_stats() -> common::CallbackStats {
+                let mut stats = common::CallbackStats::default();
+                #(#contract_idents::callback_stats(&mut stats);)*
+                stats
+            }
         };
         file_vec.push(("lib.rs".to_owned(), lib_tokens));
 
         // generate common.rs
         let tokens = (*self.quote_common_file)(ctx)?;
+        let registry_tokens = quote_callback_registry();
+        let executor_tokens = quote_executor(ctx);
+        let sequence_tokens = quote_sequence_objects(ctx);
+        let tokens = quote! {
+            #tokens
+            #registry_tokens
+            #executor_tokens
+            #sequence_tokens
+        };
         file_vec.push(("common.rs".to_owned(), tokens));
 
         Ok(file_vec)
@@ -158,19 +179,40 @@ impl<Lang, Extra> Default for FilesGenerator<Lang, Extra> {
                                 let rust_arg_str = format!("r_{}", &ctx.arg.name);
                                 let rust_arg_name = ident!(&rust_arg_str);
                                 let arg_name_ident = ident!(&ctx.arg.name);
-                                let convert = ctx
+                                let converter = ctx
                                     .method_ctx
                                     .service_ctx
                                     .mod_ctx
                                     .bridge_ctx
                                     .lang_imp
-                                    .provide_converter(&ctx.arg.ty)
-                                    .transferable_to_rust(
+                                    .provide_converter(&ctx.arg.ty);
+                                let borrowed = if ctx.arg.borrowed {
+                                    converter.transferable_to_rust_borrowed(
                                         quote! {#arg_name_ident},
-                                        Direction::Down,
-                                    );
-                                let result = quote! {
-                                    let #rust_arg_name = #convert;
+                                        &rust_arg_str,
+                                    )
+                                } else {
+                                    None
+                                };
+                                let result = match borrowed {
+                                    Some(borrowed) => borrowed,
+                                    None => {
+                                        let convert = converter.transferable_to_rust(
+                                            quote! {#arg_name_ident},
+                                            Direction::Down,
+                                        );
+                                        if ctx.arg.borrowed {
+                                            // The owned value lives until the end of the method.
+                                            let ty = quote_ty(&ctx.arg.ty);
+                                            quote! {
+                                                let #rust_arg_name: &#ty = &#convert;
+                                            }
+                                        } else {
+                                            quote! {
+                                                let #rust_arg_name = #convert;
+                                            }
+                                        }
+                                    }
                                 };
 
                                 println!(
@@ -183,7 +225,7 @@ impl<Lang, Extra> Default for FilesGenerator<Lang, Extra> {
                             quote_method_imp_call: Box::new(|ctx| {
                                 println!(
                                     "[bridge][{}.{}]  🔆 ️begin quote imp call.",
-                                    &ctx.service_ctx.imp.name, &ctx.method.name
+                                    &ctx.service_ctx.trait_.name, &ctx.method.name
                                 );
 
                                 let ret_name_str = "result";
@@ -202,14 +244,91 @@ impl<Lang, Extra> Default for FilesGenerator<Lang, Extra> {
                                     #(#tmp_arg_names),*
                                 };
 
-                                let imp_ident = ident!(&ctx.service_ctx.imp.name);
-                                let imp_call = quote! {
-                                    let #ret_name_ident = #imp_ident::#imp_fun_name(#rust_args_repeat);
+                                let trait_ = ctx.service_ctx.trait_;
+                                let object_ty = quote_object_ty(ctx.service_ctx);
+                                let imp_call = match ctx.service_ctx.imp {
+                                    // The future of an async method is run by the executor, the completion
+                                    // callback in the last argument receives the result.
+                                    Some(imp) if ctx.method.is_async => {
+                                        let imp_ident = ident!(&imp.name);
+                                        let imp_args = &tmp_arg_names[..tmp_arg_names.len() - 1];
+                                        let completion = ctx
+                                            .service_ctx
+                                            .mod_ctx
+                                            .traits
+                                            .iter()
+                                            .find(|desc| desc.name == ctx.method.completion_name(trait_))
+                                            .ok_or_else(|| {
+                                                GenerateError(format!(
+                                                    "Can't find the completion callback of async method {}.",
+                                                    &ctx.method.name
+                                                ))
+                                            })?;
+                                        let complete = quote_complete_call(completion);
+                                        quote! {
+                                            let completion = r_completion;
+                                            let future = #imp_ident::#imp_fun_name(#(#imp_args),*);
+                                            crate::common::spawn(crate::common::complete_with(future, move |outcome| {
+                                                #complete
+                                            }));
+                                            let #ret_name_ident = ();
+                                        }
+                                    }
+                                    // Constructors of instance services put the implementation behind a lock.
+                                    Some(imp) if trait_.is_constructor(ctx.method) => {
+                                        let imp_ident = ident!(&imp.name);
+                                        quote! {
+                                            let #ret_name_ident = std::sync::RwLock::new(#imp_ident::#imp_fun_name(#rust_args_repeat));
+                                        }
+                                    }
+                                    Some(imp) if !trait_.is_object => {
+                                        let imp_ident = ident!(&imp.name);
+                                        quote! {
+                                            let #ret_name_ident = #imp_ident::#imp_fun_name(#rust_args_repeat);
+                                        }
+                                    }
+                                    // A panic caught in a method poisons the lock, the instance stays
+                                    // usable like it is after the panic in rust.
+                                    Some(_) => {
+                                        let lock = if ctx.method.mut_self {
+                                            quote!(write)
+                                        } else {
+                                            quote!(read)
+                                        };
+                                        quote! {
+                                            let object = unsafe { &*(handle as *const #object_ty) };
+                                            let #ret_name_ident = object.#lock().unwrap_or_else(std::sync::PoisonError::into_inner).#imp_fun_name(#rust_args_repeat);
+                                        }
+                                    }
+                                    // Objects are borrowed from the handle, which is freed by the native side.
+                                    None => {
+                                        quote! {
+                                            let object = unsafe { &*(handle as *const #object_ty) };
+                                            let #ret_name_ident = object.#imp_fun_name(#rust_args_repeat);
+                                        }
+                                    }
+                                };
+
+                                // The iterator or the stream is pulled by the object declared by the bridge.
+                                let imp_call = match (ctx.method.sequence, &ctx.method.return_type)
+                                {
+                                    (Some(kind), AstType::Object(ref origin)) => {
+                                        let object_ident = ident!(&origin.origin);
+                                        let sequence_object = match kind {
+                                            SequenceKind::Iterator => quote!(IteratorObject),
+                                            SequenceKind::Stream => quote!(StreamObject),
+                                        };
+                                        quote! {
+                                            #imp_call
+                                            let #ret_name_ident: Box<dyn #object_ident> = Box::new(crate::common::#sequence_object::new(#ret_name_ident));
+                                        }
+                                    }
+                                    _ => imp_call,
                                 };
 
                                 println!(
                                     "[bridge][{}.{}]  ✅ end quote imp call.",
-                                    ctx.service_ctx.imp.name, &ctx.method.name
+                                    &ctx.service_ctx.trait_.name, &ctx.method.name
                                 );
 
                                 Ok(imp_call)
@@ -235,6 +354,7 @@ impl<Lang, Extra> Default for FilesGenerator<Lang, Extra> {
 
                                 Ok(result)
                             }),
+                            quote_method_body: Box::new(|_ctx, body| Ok(body)),
                         },
                     },
                     callback_generator: CallbackCodeGenerator {
@@ -245,6 +365,10 @@ impl<Lang, Extra> Default for FilesGenerator<Lang, Extra> {
                         phantom_lang: Default::default(),
                         phantom_extra: Default::default(),
                     },
+                    enum_generator: EnumCodeGenerator {
+                        phantom_lang: Default::default(),
+                        phantom_extra: Default::default(),
+                    },
                 },
                 quote_use_part: Box::new(|ctx| {
                     println!("[bridge]  🔆  begin quote use part.");
@@ -258,7 +382,20 @@ impl<Lang, Extra> Default for FilesGenerator<Lang, Extra> {
                             continue;
                         }
 
-                        if let Ok(imp) = find_imp(trait_desc, ctx.imps) {
+                        if trait_desc.is_object && !trait_desc.has_constructor() {
+                            let trait_mod_splits: Vec<Ident> = trait_desc
+                                .mod_path
+                                .split("::")
+                                .map(|str| ident!(str))
+                                .collect();
+                            merge = quote! {
+                                use #crate_ident::#(#trait_mod_splits::)**;
+                                #merge
+                            };
+                            continue;
+                        }
+
+                        if let Some(imp) = ctx.bridge_ctx.ast.find_imp(trait_desc)? {
                             let trait_mod_splits: Vec<Ident> = trait_desc
                                 .mod_path
                                 .split("::")
@@ -334,6 +471,7 @@ pub(crate) struct BridgeCodeGenerator<Lang, Extra> {
     pub(crate) trait_generator: TraitCodeGenerator<Lang, Extra>,
     pub(crate) callback_generator: CallbackCodeGenerator<Lang, Extra>,
     pub(crate) struct_generator: StructCodeGenerator<Lang, Extra>,
+    pub(crate) enum_generator: EnumCodeGenerator<Lang, Extra>,
 }
 
 impl<Lang, Extra> BridgeCodeGenerator<Lang, Extra> {
@@ -345,13 +483,34 @@ impl<Lang, Extra> BridgeCodeGenerator<Lang, Extra> {
                 continue;
             }
 
-            if let Ok(imp) = find_imp(trait_, ctx.imps) {
+            if trait_.is_object && !trait_.has_constructor() {
+                let ctx = ServiceContext {
+                    trait_,
+                    imp: None,
+                    mod_ctx: ctx,
+                };
+                results.push(self.trait_generator.quote_for_one_trait(&ctx));
+                results.push(self.trait_generator.quote_free_object(&ctx));
+                continue;
+            }
+
+            let imp = match ctx.bridge_ctx.ast.find_imp(trait_) {
+                Ok(imp) => imp,
+                Err(err) => {
+                    results.push(Err(err));
+                    continue;
+                }
+            };
+            if let Some(imp) = imp {
                 let ctx = ServiceContext {
                     trait_,
-                    imp,
+                    imp: Some(imp),
                     mod_ctx: ctx,
                 };
                 results.push(self.trait_generator.quote_for_one_trait(&ctx));
+                if trait_.is_object {
+                    results.push(self.trait_generator.quote_free_object(&ctx));
+                }
             }
         }
 
@@ -371,14 +530,58 @@ impl<Lang, Extra> BridgeCodeGenerator<Lang, Extra> {
             .map(|item| ident!(&item.name))
             .collect::<Vec<Ident>>();
 
+        let enum_names = callbacks
+            .iter()
+            .map(|item| item.name.clone())
+            .collect::<Vec<String>>();
+
+        // Callbacks stay in CALLBACK_HASHMAP until the other side frees them,
+        // CALLBACK_REGISTRY records them for `callback_stats`.
         let enum_tokens = quote! {
             enum CallbackEnum {
                 #(#enum_items(Box<dyn #enum_items>)),*
             }
+
+            impl CallbackEnum {
+                fn trait_name(&self) -> &'static str {
+                    match *self {
+                        #(CallbackEnum::#enum_items(_) => #enum_names),*
+                    }
+                }
+            }
+
+            lazy_static! {
+                static ref CALLBACK_HASHMAP: Arc<RwLock<HashMap<i64, CallbackEnum>>> =  Arc::new(RwLock::new(HashMap::new()));
+                static ref CALLBACK_INDEX : Arc<RwLock<i64>> = Arc::new(RwLock::new(0));
+                static ref CALLBACK_REGISTRY: RwLock<crate::common::CallbackRegistry> = RwLock::new(Default::default());
+            }
+
+            fn insert_callback(index: i64, callback: CallbackEnum) {
+                CALLBACK_REGISTRY.write().unwrap().created(index, callback.trait_name());
+                (*CALLBACK_HASHMAP.write().unwrap()).insert(index, callback);
+            }
+
+            fn remove_callback(index: i64) {
+                if (*CALLBACK_HASHMAP.write().unwrap()).remove(&index).is_some() {
+                    CALLBACK_REGISTRY.write().unwrap().freed(index);
+                }
+            }
+
+            pub fn callback_stats(stats: &mut crate::common::CallbackStats) {
+                CALLBACK_REGISTRY.read().unwrap().collect(stats);
+            }
         };
 
         results.push(Ok(enum_tokens));
 
+        for callback in callbacks.iter().filter(|desc| desc.is_completion) {
+            results.push(Ok(quote_completion_trait(callback)));
+        }
+
+        for object in ctx.traits.iter().filter(|desc| desc.sequence.is_some()) {
+            results.push(quote_sequence_trait(object, ctx.traits));
+        }
+
         for callback in callbacks.iter() {
             let ctx = CallbackContext {
                 callback,
@@ -397,6 +600,15 @@ impl<Lang, Extra> BridgeCodeGenerator<Lang, Extra> {
             results.push(tokens);
         }
 
+        for enum_ in ctx.enums.iter() {
+            let ctx = EnumContext {
+                enum_,
+                mod_ctx: ctx,
+            };
+            let tokens = self.enum_generator.quote_for_one_enum(&ctx);
+            results.push(tokens);
+        }
+
         results
     }
 }
@@ -416,7 +628,13 @@ impl<Lang, Extra> TraitCodeGenerator<Lang, Extra> {
         );
         let mut merge: TokenStream = TokenStream::new();
 
-        for method in ctx.trait_.methods.iter() {
+        let methods = ctx
+            .trait_
+            .methods
+            .iter()
+            .map(|method| method.to_bridge(ctx.trait_))
+            .collect::<Vec<MethodDesc>>();
+        for method in methods.iter() {
             println!(
                 "[bridge][{}.{}]  🔆  begin generate bridge method.",
                 &ctx.trait_.name, &method.name
@@ -445,6 +663,10 @@ impl<Lang, Extra> TraitCodeGenerator<Lang, Extra> {
         );
         Ok(merge)
     }
+
+    pub(crate) fn quote_free_object(&self, ctx: &ServiceContext<Lang, Extra>) -> TokenResult {
+        ctx.mod_ctx.bridge_ctx.lang_imp.quote_free_object(ctx)
+    }
 }
 
 ///
@@ -455,6 +677,9 @@ pub(crate) struct TraitMethodGenerator<Lang, Extra> {
     pub(crate) quote_arg_convert: Box<dyn Fn(&ArgumentContext<Lang, Extra>) -> TokenResult>,
     pub(crate) quote_method_imp_call: Box<dyn Fn(&MethodContext<Lang, Extra>) -> TokenResult>,
     pub(crate) quote_method_return_convert: Box<dyn Fn(&MethodContext<Lang, Extra>) -> TokenResult>,
+    /// Wrap the statements of the method, for example to catch the panics of the implementation.
+    pub(crate) quote_method_body:
+        Box<dyn Fn(&MethodContext<Lang, Extra>, TokenStream) -> TokenResult>,
 }
 
 impl<Lang, Extra> TraitMethodGenerator<Lang, Extra> {
@@ -482,12 +707,19 @@ impl<Lang, Extra> TraitMethodGenerator<Lang, Extra> {
 
         let return_handle = (*self.quote_method_return_convert)(ctx)?;
 
-        // combine all the parts
-        let result = quote! {
-            #sig_define {
+        let body = (*self.quote_method_body)(
+            ctx,
+            quote! {
                 #arg_convert
                 #call_imp
                 #return_handle
+            },
+        )?;
+
+        // combine all the parts
+        let result = quote! {
+            #sig_define {
+                #body
             }
         };
 
@@ -532,20 +764,632 @@ impl<Lang, Extra> StructCodeGenerator<Lang, Extra> {
     }
 }
 
-fn find_imp<'a>(trait_: &'a TraitDesc, imps: &'a [ImpDesc]) -> Result<&'a ImpDesc> {
-    let imps = imps
+///
+/// Used for generate code for one enum.
+///
+pub(crate) struct EnumCodeGenerator<Lang, Extra> {
+    phantom_lang: PhantomData<Lang>,
+    phantom_extra: PhantomData<Extra>,
+}
+
+impl<Lang, Extra> EnumCodeGenerator<Lang, Extra> {
+    fn quote_for_one_enum(&self, context: &EnumContext<Lang, Extra>) -> TokenResult {
+        context
+            .mod_ctx
+            .bridge_ctx
+            .lang_imp
+            .quote_for_one_enum(context)
+    }
+}
+
+///
+/// Quote conversions between the enum and its proxy, which holds the discriminant.
+///
+pub(crate) fn quote_enum_proxy_convert(enum_: &EnumDesc) -> TokenStream {
+    let proxy_enum_name = ident!(&format!("Proxy{}", &enum_.name));
+    let origin_enum_name = ident!(&enum_.name);
+    let variant_names = enum_
+        .variants
+        .iter()
+        .map(|variant| ident!(&variant.name))
+        .collect::<Vec<Ident>>();
+    let variant_values = enum_
+        .variants
+        .iter()
+        .map(|variant| variant.value)
+        .collect::<Vec<i32>>();
+    let unknown_msg = format!("Unknown value {{}} for enum {}.", &enum_.name);
+
+    quote! {
+        impl From<#origin_enum_name> for #proxy_enum_name {
+            fn from(origin: #origin_enum_name) -> Self {
+                #proxy_enum_name(origin as i32)
+            }
+        }
+
+        impl From<#proxy_enum_name> for #origin_enum_name {
+            fn from(proxy: #proxy_enum_name) -> Self {
+                match proxy.0 {
+                    #(#variant_values => #origin_enum_name::#variant_names,)*
+                    _ => panic!(#unknown_msg, proxy.0),
+                }
+            }
+        }
+    }
+}
+
+///
+/// The pattern of a variant in the origin enum, binding every field to its name.
+///
+pub(crate) fn quote_variant_pattern(enum_: &EnumDesc, variant: &VariantDesc) -> TokenStream {
+    let names = variant
+        .fields
+        .iter()
+        .map(|field| ident!(&field.name))
+        .collect::<Vec<Ident>>();
+    quote_variant_value(
+        enum_,
+        variant,
+        &names
+            .iter()
+            .map(|name| quote! {#name})
+            .collect::<Vec<TokenStream>>(),
+    )
+}
+
+///
+/// Build a variant of the origin enum with the values of its fields in order.
+///
+pub(crate) fn quote_variant_value(
+    enum_: &EnumDesc,
+    variant: &VariantDesc,
+    values: &[TokenStream],
+) -> TokenStream {
+    let origin_enum_name = ident!(&enum_.name);
+    let variant_name = ident!(&variant.name);
+    let names = variant
+        .fields
+        .iter()
+        .map(|field| ident!(&field.name))
+        .collect::<Vec<Ident>>();
+    if variant.fields.is_empty() {
+        quote! {#origin_enum_name::#variant_name}
+    } else if variant.is_tuple {
+        quote! {#origin_enum_name::#variant_name(#(#values),*)}
+    } else {
+        quote! {#origin_enum_name::#variant_name{#(#names: #values),*}}
+    }
+}
+
+///
+/// The members of the fields in the origin struct, like `name`, or `0` for tuple structs.
+///
+pub(crate) fn quote_struct_members(struct_: &StructDesc) -> Vec<TokenStream> {
+    struct_
+        .fields
+        .iter()
+        .enumerate()
+        .map(|(index, field)| {
+            if struct_.is_tuple {
+                let index = syn::Index::from(index);
+                quote! {#index}
+            } else {
+                let name = ident!(&field.name);
+                quote! {#name}
+            }
+        })
+        .collect()
+}
+
+///
+/// The rust type of a tuple like `(i32, String)`.
+///
+pub(crate) fn quote_tuple_ty(elems: &[AstBaseType]) -> TokenStream {
+    let tys = elems.iter().map(quote_base_ty);
+    quote!((#(#tys,)*))
+}
+
+///
+/// The rust type of a base type, collections inside are quoted recursively.
+///
+///
+/// The owned rust type, like `Vec<Vec<u8>>` or `std::collections::HashMap<String, i32>`.
+///
+pub(crate) fn quote_ty(ty: &AstType) -> TokenStream {
+    match ty {
+        AstType::Void => quote!(()),
+        AstType::Vec(ref base) => {
+            let base_ty = quote_base_ty(base);
+            quote!(Vec<#base_ty>)
+        }
+        AstType::Optional(ref base) => {
+            let base_ty = quote_base_ty(base);
+            quote!(Option<#base_ty>)
+        }
+        AstType::Result(ref ok, ref err) => {
+            let ok_ty = quote_base_ty(ok);
+            let err_ty = quote_base_ty(err);
+            quote!(Result<#ok_ty, #err_ty>)
+        }
+        AstType::Map(ref map, ref key, ref value) => {
+            let map_ident = ident!(map);
+            let key_ty = quote_base_ty(key);
+            let value_ty = quote_base_ty(value);
+            quote!(std::collections::#map_ident<#key_ty, #value_ty>)
+        }
+        AstType::Tuple(ref elems) => quote_tuple_ty(elems),
+        AstType::Callback(ref origin) | AstType::Object(ref origin) => {
+            let callback_ident = ident!(&origin.origin);
+            quote!(Box<dyn #callback_ident>)
+        }
+        AstType::Cancellation(_) => quote!(tokio_util::sync::CancellationToken),
+        _ => {
+            let ident = ident!(&ty.origin());
+            quote!(#ident)
+        }
+    }
+}
+
+pub(crate) fn quote_base_ty(base: &AstBaseType) -> TokenStream {
+    match base {
+        AstBaseType::Vec(ref inner) => {
+            let inner_ty = quote_base_ty(inner);
+            quote!(Vec<#inner_ty>)
+        }
+        AstBaseType::Optional(ref inner) => {
+            let inner_ty = quote_base_ty(inner);
+            quote!(Option<#inner_ty>)
+        }
+        _ => {
+            let ident = ident!(&base.origin());
+            quote!(#ident)
+        }
+    }
+}
+
+///
+/// The registry of callbacks implemented by rust in one module, and the stats merged from all the modules.
+/// Objects, buffers and the callbacks implemented by native code are not in it.
+///
+fn quote_callback_registry() -> TokenStream {
+    quote! {
+        /// The number of the oldest live callbacks reported by `CallbackStats`.
+        pub const OLDEST_CALLBACKS: usize = 10;
+
+        #[derive(Serialize, Debug, Clone, Default)]
+        pub struct CallbackStats {
+            /// Rust callbacks handed to native code since the library is loaded.
+            pub created: u64,
+            /// Rust callbacks freed by native code since the library is loaded.
+            pub freed: u64,
+            /// Live callbacks by the trait names.
+            pub live: std::collections::BTreeMap<String, u64>,
+            /// The oldest live callbacks, the oldest first.
+            pub oldest: Vec<CallbackEntry>,
+        }
+
+        #[derive(Serialize, Debug, Clone)]
+        pub struct CallbackEntry {
+            pub trait_name: String,
+            pub index: i64,
+            pub age_millis: u64,
+        }
+
+        #[derive(Default)]
+        pub struct CallbackRegistry {
+            created: u64,
+            freed: u64,
+            entries: std::collections::HashMap<i64, (&'static str, std::time::Instant)>,
+        }
+
+        impl CallbackRegistry {
+            pub fn created(&mut self, index: i64, trait_name: &'static str) {
+                self.created += 1;
+                self.entries.insert(index, (trait_name, std::time::Instant::now()));
+            }
+
+            pub fn freed(&mut self, index: i64) {
+                self.freed += 1;
+                self.entries.remove(&index);
+            }
+
+            pub fn collect(&self, stats: &mut CallbackStats) {
+                stats.created += self.created;
+                stats.freed += self.freed;
+                for (index, (trait_name, created)) in self.entries.iter() {
+                    *stats.live.entry(trait_name.to_string()).or_insert(0) += 1;
+                    stats.oldest.push(CallbackEntry {
+                        trait_name: trait_name.to_string(),
+                        index: *index,
+                        age_millis: created.elapsed().as_millis() as u64,
+                    });
+                }
+                stats.oldest.sort_by(|a, b| b.age_millis.cmp(&a.age_millis));
+                stats.oldest.truncate(OLDEST_CALLBACKS);
+            }
+        }
+    }
+}
+
+///
+/// Declare the completion callback of an async method, which is not in the contract.
+///
+fn quote_completion_trait(desc: &TraitDesc) -> TokenStream {
+    let trait_ident = ident!(&desc.name);
+    let methods = desc.methods.iter().map(|method| {
+        let method_ident = ident!(&method.name);
+        let arg_idents = method.args.iter().map(|arg| ident!(&arg.name));
+        let arg_tys = method.args.iter().map(|arg| quote_ty(&arg.ty));
+        quote! {
+            fn #method_ident(&self, #(#arg_idents: #arg_tys),*);
+        }
+    });
+    quote! {
+        pub trait #trait_ident: Send + Sync {
+            #(#methods)*
+        }
+    }
+}
+
+///
+/// Declare the object pulling the items of an iterator or a stream, and implement it for
+/// the generic object in common.rs.
+///
+fn quote_sequence_trait(desc: &TraitDesc, traits: &[TraitDesc]) -> TokenResult {
+    let trait_ident = ident!(&desc.name);
+    let item_ty = match desc.methods[0].return_type {
+        AstType::Optional(ref item) => quote_base_ty(item),
+        _ => {
+            return Err(GenerateError(format!(
+                "The items of {} should be returned as Option.",
+                &desc.name
+            ))
+            .into())
+        }
+    };
+
+    match desc.sequence {
+        Some(SequenceKind::Stream) => {
+            let completion_name = desc.methods[0].completion_name(desc);
+            let completion = traits
+                .iter()
+                .find(|each| each.name == completion_name)
+                .ok_or_else(|| {
+                    GenerateError(format!(
+                        "Can't find the completion callback of {}.",
+                        &desc.name
+                    ))
+                })?;
+            let completion_ident = ident!(&completion_name);
+            let complete = quote_complete_call(completion);
+            Ok(quote! {
+                pub trait #trait_ident: Send + Sync {
+                    fn next_item(&self, completion: Box<dyn #completion_ident>);
+                }
+
+                impl #trait_ident for crate::common::StreamObject<#item_ty> {
+                    fn next_item(&self, completion: Box<dyn #completion_ident>) {
+                        crate::common::spawn(crate::common::complete_with(self.pull(), move |outcome| {
+                            #complete
+                        }));
+                    }
+                }
+            })
+        }
+        _ => Ok(quote! {
+            pub trait #trait_ident: Send + Sync {
+                fn next_item(&self) -> Option<#item_ty>;
+            }
+
+            impl #trait_ident for crate::common::IteratorObject<#item_ty> {
+                fn next_item(&self) -> Option<#item_ty> {
+                    self.pull()
+                }
+            }
+        }),
+    }
+}
+
+///
+/// Pass `result` of an async method to the completion callback, `on_error` is only declared for `Result`.
+/// The panic message of the future is passed to `on_panic`.
+///
+fn quote_complete_call(completion: &TraitDesc) -> TokenStream {
+    let has_value = completion.methods[0].args.len() == 1;
+    let has_error = completion
+        .methods
         .iter()
-        .filter(|info| info.contract == trait_.name)
-        .collect::<Vec<&ImpDesc>>();
+        .any(|method| method.name == "on_error");
+    let complete = match (has_value, has_error) {
+        (true, true) => quote! {
+            match result {
+                Ok(value) => completion.on_complete(value),
+                Err(error) => completion.on_error(error),
+            }
+        },
+        (false, true) => quote! {
+            match result {
+                Ok(_) => completion.on_complete(),
+                Err(error) => completion.on_error(error),
+            }
+        },
+        (true, false) => quote! {
+            completion.on_complete(result);
+        },
+        (false, false) => quote! {
+            completion.on_complete();
+        },
+    };
+    quote! {
+        match outcome {
+            Ok(result) => {
+                #complete
+            }
+            Err(message) => completion.on_panic(message),
+        }
+    }
+}
 
-    return match imps.len().cmp(&1) {
-        Ordering::Less => {
-            println!("No impl found for trait {}", trait_.name);
-            Err(GenerateError(format!("No impl found for trait {}", trait_.name)).into())
+///
+/// The executor of async methods, the function configured by `executor` in the host crate,
+/// or a pool of worker threads shared by all the futures.
+///
+fn quote_executor<Lang, Extra>(ctx: &BridgeContext<Lang, Extra>) -> TokenStream {
+    let (spawn, workers) = match ctx.executor {
+        Some(ref executor) => {
+            let crate_ident = ident!(&ctx.crate_name.replace("-", "_"));
+            let path = executor
+                .split("::")
+                .map(|segment| ident!(segment))
+                .collect::<Vec<Ident>>();
+            let spawn = quote! {
+                #crate_ident::#(#path)::*(task);
+            };
+            (spawn, quote!())
         }
-        Ordering::Equal => Ok(imps[0]),
-        Ordering::Greater => {
-            panic!("You have more than one impl for trait {}", trait_.name);
+        None => {
+            let spawn = quote! {
+                schedule(std::sync::Arc::new(Job {
+                    task: std::sync::Mutex::new(Some(task)),
+                }));
+            };
+            (spawn, quote_workers())
         }
     };
+
+    quote! {
+        /// The future of an async method, which passes the result to the completion callback.
+        pub type Task = std::pin::Pin<Box<dyn std::future::Future<Output = ()> + Send + 'static>>;
+
+        /// Run the future of an async method.
+        pub fn spawn(task: Task) {
+            #spawn
+        }
+
+        /// The task calling `complete` with the output of the future, or the message if polling it panics,
+        /// so the completion is always called. The bridge crate may be built with the 2015 edition,
+        /// which has no async blocks.
+        pub fn complete_with<F, C>(future: F, complete: C) -> Task
+        where
+            F: std::future::Future + Send + 'static,
+            C: FnOnce(Result<F::Output, String>) + Send + Unpin + 'static,
+        {
+            Box::pin(Completing {
+                future: Box::pin(future),
+                complete: Some(complete),
+            })
+        }
+
+        struct Completing<F: std::future::Future, C> {
+            future: std::pin::Pin<Box<F>>,
+            complete: Option<C>,
+        }
+
+        impl<F, C> std::future::Future for Completing<F, C>
+        where
+            F: std::future::Future,
+            C: FnOnce(Result<F::Output, String>) + Unpin,
+        {
+            type Output = ();
+
+            fn poll(mut self: std::pin::Pin<&mut Self>, context: &mut std::task::Context) -> std::task::Poll<()> {
+                let future = &mut self.future;
+                let output = match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| future.as_mut().poll(context))) {
+                    Ok(std::task::Poll::Ready(output)) => Ok(output),
+                    Ok(std::task::Poll::Pending) => return std::task::Poll::Pending,
+                    Err(payload) => Err(panic_message(payload)),
+                };
+                if let Some(complete) = self.complete.take() {
+                    complete(output);
+                }
+                std::task::Poll::Ready(())
+            }
+        }
+
+        #workers
+    }
+}
+
+///
+/// The default executor, started by the first future. A future is queued when it is spawned or woken,
+/// and polled by one of the workers, so pending futures don't hold a thread.
+///
+fn quote_workers() -> TokenStream {
+    quote! {
+        /// A spawned future, which is queued again when it is woken.
+        struct Job {
+            task: std::sync::Mutex<Option<Task>>,
+        }
+
+        impl std::task::Wake for Job {
+            fn wake(self: std::sync::Arc<Self>) {
+                schedule(self);
+            }
+        }
+
+        type JobSender = std::sync::mpsc::Sender<std::sync::Arc<Job>>;
+        type JobReceiver = std::sync::Arc<std::sync::Mutex<std::sync::mpsc::Receiver<std::sync::Arc<Job>>>>;
+
+        static JOBS: std::sync::OnceLock<std::sync::Mutex<JobSender>> = std::sync::OnceLock::new();
+
+        /// Queue the job for the workers, one thread for each cpu.
+        fn schedule(job: std::sync::Arc<Job>) {
+            let jobs = JOBS.get_or_init(|| {
+                let (sender, receiver) = std::sync::mpsc::channel();
+                let receiver: JobReceiver = std::sync::Arc::new(std::sync::Mutex::new(receiver));
+                let workers = std::thread::available_parallelism().map(|count| count.get()).unwrap_or(2);
+                for index in 0..workers {
+                    let receiver = receiver.clone();
+                    std::thread::Builder::new()
+                        .name(format!("rsbind-worker-{}", index))
+                        .spawn(move || run_jobs(receiver))
+                        .expect("Can't start the workers of async methods.");
+                }
+                std::sync::Mutex::new(sender)
+            });
+            let _ = jobs.lock().unwrap().send(job);
+        }
+
+        /// Poll the queued jobs, a job woken while it is polled waits for the poll to finish.
+        /// The future is dropped when it is ready, or if it panics.
+        fn run_jobs(receiver: JobReceiver) {
+            loop {
+                let job = match receiver.lock().unwrap().recv() {
+                    Ok(job) => job,
+                    Err(_) => return,
+                };
+                let waker = std::task::Waker::from(job.clone());
+                let mut context = std::task::Context::from_waker(&waker);
+                let mut task = job.task.lock().unwrap();
+                let ready = match *task {
+                    Some(ref mut future) => std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                        future.as_mut().poll(&mut context).is_ready()
+                    }))
+                    .unwrap_or(true),
+                    None => false,
+                };
+                if ready {
+                    *task = None;
+                }
+            }
+        }
+    }
+}
+
+///
+/// The generic objects pulling the items of iterators and streams, one item for each call from
+/// native code. The stream is only polled for the item requested, and both are dropped with the handle.
+///
+fn quote_sequence_objects<Lang, Extra>(ctx: &BridgeContext<Lang, Extra>) -> TokenStream {
+    let stream = if ctx.ast.has_stream() {
+        quote! {
+            pub type BoxStream<T> = std::pin::Pin<Box<dyn futures_core::Stream<Item = T> + Send>>;
+
+            pub struct StreamObject<T> {
+                stream: std::sync::Arc<std::sync::Mutex<BoxStream<T>>>,
+            }
+
+            impl<T> StreamObject<T> {
+                pub fn new<S: Into<BoxStream<T>>>(stream: S) -> Self {
+                    StreamObject {
+                        stream: std::sync::Arc::new(std::sync::Mutex::new(stream.into())),
+                    }
+                }
+
+                /// The future of the next item, `None` at the end of the stream.
+                pub fn pull(&self) -> NextItem<T> {
+                    NextItem {
+                        stream: self.stream.clone(),
+                    }
+                }
+            }
+
+            pub struct NextItem<T> {
+                stream: std::sync::Arc<std::sync::Mutex<BoxStream<T>>>,
+            }
+
+            impl<T> std::future::Future for NextItem<T> {
+                type Output = Option<T>;
+
+                fn poll(self: std::pin::Pin<&mut Self>, context: &mut std::task::Context) -> std::task::Poll<Option<T>> {
+                    let mut stream = self.stream.lock().unwrap();
+                    futures_core::Stream::poll_next(stream.as_mut(), context)
+                }
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    quote! {
+        pub struct IteratorObject<T> {
+            iterator: std::sync::Mutex<Box<dyn Iterator<Item = T> + Send>>,
+        }
+
+        impl<T> IteratorObject<T> {
+            pub fn new(iterator: Box<dyn Iterator<Item = T> + Send>) -> Self {
+                IteratorObject {
+                    iterator: std::sync::Mutex::new(iterator),
+                }
+            }
+
+            /// The next item, `None` at the end of the iterator.
+            pub fn pull(&self) -> Option<T> {
+                self.iterator.lock().unwrap().next()
+            }
+        }
+
+        #stream
+    }
+}
+
+///
+/// Implement a callback for the struct calling native code, the methods inherited from supertraits
+/// are implemented in the impl blocks of the supertraits. `methods` follow the order of `desc.methods`.
+///
+pub(crate) fn quote_callback_impls(
+    desc: &TraitDesc,
+    struct_ident: &Ident,
+    methods: &[TokenStream],
+) -> TokenStream {
+    let mut trait_names = vec![desc.name.as_str()];
+    for method in desc.methods.iter() {
+        if !trait_names.contains(&method.trait_name(desc)) {
+            trait_names.push(method.trait_name(desc));
+        }
+    }
+
+    let impls = trait_names.iter().map(|trait_name| {
+        let trait_ident = ident!(trait_name);
+        let trait_methods = desc
+            .methods
+            .iter()
+            .zip(methods.iter())
+            .filter(|(method, _)| method.trait_name(desc) == *trait_name)
+            .map(|(_, tokens)| tokens);
+        quote! {
+            impl #trait_ident for #struct_ident {
+                #(#trait_methods)*
+            }
+        }
+    });
+    quote! {
+        #(#impls)*
+    }
+}
+
+///
+/// The type behind the handle of an object, instance services hold their implementation behind a lock.
+///
+pub(crate) fn quote_object_ty<Lang, Extra>(ctx: &ServiceContext<Lang, Extra>) -> TokenStream {
+    match ctx.imp {
+        Some(imp) => {
+            let imp_ident = ident!(&imp.name);
+            quote!(std::sync::RwLock<#imp_ident>)
+        }
+        None => {
+            let trait_ident = ident!(&ctx.trait_.name);
+            quote!(Box<dyn #trait_ident>)
+        }
+    }
 }
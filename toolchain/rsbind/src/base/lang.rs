@@ -1,4 +1,4 @@
-use crate::ast::contract::desc::{ArgDesc, MethodDesc, StructDesc, TraitDesc};
+use crate::ast::contract::desc::{ArgDesc, EnumDesc, MethodDesc, StructDesc, TraitDesc};
 use crate::ast::imp::desc::ImpDesc;
 use crate::ast::types::AstType;
 use crate::errors::*;
@@ -33,6 +33,15 @@ pub(crate) trait Convertible<T> {
     fn rust_to_transferable(&self, origin: TokenStream, direction: Direction) -> TokenStream;
     /// Change data from transferable type to rust type.
     fn transferable_to_rust(&self, origin: TokenStream, direction: Direction) -> TokenStream;
+    /// Declare `name` as a borrowed view of the transferable argument like `&str` or `&[u8]`,
+    /// without copying it to an owned rust type. `None` if the owned value is borrowed instead.
+    fn transferable_to_rust_borrowed(
+        &self,
+        _origin: TokenStream,
+        _name: &str,
+    ) -> Option<TokenStream> {
+        None
+    }
     /// Native lang types.
     fn native_type(&self) -> T;
     /// Native transferable type.
@@ -65,7 +74,8 @@ pub(crate) struct MethodContext<'a, Lang, Extra> {
 
 pub(crate) struct ServiceContext<'a, Lang, Extra> {
     pub trait_: &'a TraitDesc,
-    pub imp: &'a ImpDesc,
+    /// The implementation of a service, objects are called on the handle instead.
+    pub imp: Option<&'a ImpDesc>,
     pub mod_ctx: &'a ModContext<'a, Lang, Extra>,
 }
 
@@ -79,10 +89,15 @@ pub(crate) struct StructContext<'a, Lang, Extra> {
     pub mod_ctx: &'a ModContext<'a, Lang, Extra>,
 }
 
+pub(crate) struct EnumContext<'a, Lang, Extra> {
+    pub enum_: &'a EnumDesc,
+    pub mod_ctx: &'a ModContext<'a, Lang, Extra>,
+}
+
 pub(crate) struct ModContext<'a, Lang, Extra> {
     pub traits: &'a Vec<TraitDesc>,
     pub structs: &'a Vec<StructDesc>,
-    pub imps: &'a Vec<ImpDesc>,
+    pub enums: &'a Vec<EnumDesc>,
     pub mod_name: String,
     pub bridge_ctx: &'a BridgeContext<'a, Lang, Extra>,
 }
@@ -93,6 +108,8 @@ pub(crate) struct BridgeContext<'a, Lang, Extra> {
     pub extra: &'a Extra,
     pub lang_imp: &'a Box<dyn LangImp<Lang, Extra>>,
     pub lang_name: String,
+    /// Path of the function running the futures of async methods in the host crate.
+    pub executor: Option<String>,
 }
 
 pub(crate) trait LangImp<Lang, Extra> {
@@ -102,7 +119,10 @@ pub(crate) trait LangImp<Lang, Extra> {
     fn quote_common_part(&self, context: &ModContext<Lang, Extra>) -> Result<TokenStream>;
     fn quote_method_sig(&self, context: &MethodContext<Lang, Extra>) -> Result<TokenStream>;
     fn quote_for_one_struct(&self, context: &StructContext<Lang, Extra>) -> Result<TokenStream>;
+    fn quote_for_one_enum(&self, context: &EnumContext<Lang, Extra>) -> Result<TokenStream>;
     fn quote_for_one_callback(&self, context: &CallbackContext<Lang, Extra>)
         -> Result<TokenStream>;
+    /// Quote the function freeing the handle of an object.
+    fn quote_free_object(&self, context: &ServiceContext<Lang, Extra>) -> Result<TokenStream>;
     fn provide_converter(&self, ty: &AstType) -> Box<dyn Convertible<Lang>>;
 }
@@ -15,6 +15,10 @@ pub(crate) struct Unpack<'a> {
     pub host_crate: &'a str,
     pub buf: &'a [u8],
     pub features: &'a Vec<String>,
+    /// Streams are polled with the `Stream` trait of `futures-core`.
+    pub has_stream: bool,
+    /// Cancellation tokens are the `CancellationToken` of `tokio-util`.
+    pub has_cancellation: bool,
 }
 
 impl<'a> Unpack<'a> {
@@ -49,6 +53,14 @@ impl<'a> Unpack<'a> {
         }
 
         let replaced = replaced.replace(&format!("$({}-features)", MAGIC_NUM), &feature_defs);
+        let mut dependencies = String::from("[dependencies]");
+        if self.has_stream {
+            dependencies.push_str("\nfutures-core = \"0.3\"");
+        }
+        if self.has_cancellation {
+            dependencies.push_str("\ntokio-util = \"0.7\"");
+        }
+        let replaced = replaced.replace("[dependencies]", &dependencies);
         fs::write(manifest_path, replaced)
             .map_err(|e| FileError(format!("write rust project Cargo.toml error {:?}", e)))?;
 
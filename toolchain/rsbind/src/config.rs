@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::fs::File;
 use std::io::Read;
 use std::path::Path;
@@ -23,6 +24,27 @@ pub struct Config {
 pub struct Common {
     pub contract_name: Option<String>,
     pub imp_name: Option<String>,
+    /// Path of the function running the futures of async methods in the host crate,
+    /// like `implementation::runtime::spawn`.
+    pub executor: Option<String>,
+    /// Dispatch policies of the callbacks implemented by native code, keyed by the trait name
+    /// or `Trait.method`. The value is `inline`, or the name of a dispatcher registered by native code.
+    pub dispatch: Option<HashMap<String, String>>,
+}
+
+impl Config {
+    pub fn executor(&self) -> Option<String> {
+        self.common
+            .as_ref()
+            .and_then(|common| common.executor.clone())
+    }
+
+    pub fn dispatch(&self) -> HashMap<String, String> {
+        self.common
+            .as_ref()
+            .and_then(|common| common.dispatch.clone())
+            .unwrap_or_default()
+    }
 }
 
 ///
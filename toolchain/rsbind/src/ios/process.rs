@@ -12,7 +12,7 @@ use crate::base::process::BuildProcess;
 use crate::bridge::prj::Unpack;
 use crate::errors::ErrorKind::*;
 use crate::errors::*;
-use crate::swift::SwiftGen;
+use crate::swift::{SwiftGen, C_HEADER};
 use crate::unzip;
 
 use super::config::Ios;
@@ -27,9 +27,11 @@ pub(crate) struct IosProcess<'a> {
     host_crate_name: &'a str,
     ast_result: &'a AstResult,
     config: Option<Ios>,
+    executor: Option<String>,
 }
 
 impl<'a> IosProcess<'a> {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         origin_prj_path: &'a Path,
         dest_prj_path: &'a Path,
@@ -38,6 +40,7 @@ impl<'a> IosProcess<'a> {
         host_crate_name: &'a str,
         ast_result: &'a AstResult,
         config: Option<Ios>,
+        executor: Option<String>,
     ) -> Self {
         IosProcess {
             origin_prj_path,
@@ -47,6 +50,7 @@ impl<'a> IosProcess<'a> {
             host_crate_name,
             ast_result,
             config,
+            executor,
         }
     }
 }
@@ -61,6 +65,7 @@ impl<'a> IosProcess<'a> {
         let output_file = self.header_path.join("ffi.h").display().to_string();
 
         let config = Config {
+            header: Some(String::from(C_HEADER)),
             namespace: Some(String::from("ffi")),
             language: Language::C,
             ..Default::default()
@@ -101,6 +106,8 @@ impl<'a> BuildProcess for IosProcess<'a> {
             host_crate: self.host_crate_name,
             buf,
             features: &self.config().features(),
+            has_stream: self.ast_result.has_stream(),
+            has_cancellation: self.ast_result.has_cancellation(),
         };
 
         unpack.unpack()?;
@@ -110,6 +117,7 @@ impl<'a> BuildProcess for IosProcess<'a> {
         SwiftGen {
             crate_name: self.host_crate_name.to_string(),
             ast: self.ast_result.clone(),
+            executor: self.executor.clone(),
         }
         .gen_bridge(&bridge_c_src_path)?;
 
@@ -269,6 +277,7 @@ impl<'a> BuildProcess for IosProcess<'a> {
         SwiftGen {
             crate_name: self.host_crate_name.to_string(),
             ast: self.ast_result.clone(),
+            executor: self.executor.clone(),
         }
         .gen_native(&swift_gen_path)?;
 
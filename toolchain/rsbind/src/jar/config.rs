@@ -1,4 +1,6 @@
 const NAMESPACE: &str = "com.afoxer.xxx.ffi";
+const UNSIGNED: &str = "keep";
+const PANIC: &str = "exception";
 const DYLIB_NAME: &str = "ffi";
 
 ///
@@ -15,6 +17,8 @@ pub struct Jar {
     pub features_def: Option<Vec<String>>,
     pub contract_name: Option<String>,
     pub imp_name: Option<String>,
+    pub unsigned: Option<String>,
+    pub panic: Option<String>,
 }
 
 impl Default for Jar {
@@ -28,7 +32,9 @@ impl Default for Jar {
             ext_lib: None,
             features_def: None,
             contract_name: None,
-            imp_name: None
+            imp_name: None,
+            unsigned: Some(UNSIGNED.to_owned()),
+            panic: Some(PANIC.to_owned()),
         }
     }
 }
@@ -41,6 +47,27 @@ impl Jar {
         }
     }
 
+    ///
+    /// How unsigned integers are exported to java, `widen` or `keep`.
+    ///
+    pub fn unsigned(&self) -> String {
+        match self.unsigned {
+            Some(ref unsigned) => unsigned.to_owned(),
+            None => UNSIGNED.to_owned(),
+        }
+    }
+
+    ///
+    /// What the jni functions do when rust panics, throw `RustPanicException` with `exception`
+    /// or abort the process with `abort`.
+    ///
+    pub fn panic(&self) -> String {
+        match self.panic {
+            Some(ref panic) => panic.to_owned(),
+            None => PANIC.to_owned(),
+        }
+    }
+
     pub fn rustc_param(&self) -> String {
         match self.rustc_param {
             Some(ref rustc) => rustc.to_owned(),
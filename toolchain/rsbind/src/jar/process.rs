@@ -24,6 +24,7 @@ pub(crate) struct JarProcess<'a> {
     host_crate_name: &'a str,
     ast_result: &'a AstResult,
     config: Option<Jar>,
+    executor: Option<String>,
 }
 
 impl<'a> JarProcess<'a> {
@@ -34,6 +35,7 @@ impl<'a> JarProcess<'a> {
         host_crate_name: &'a str,
         ast_result: &'a AstResult,
         config: Option<Jar>,
+        executor: Option<String>,
     ) -> Self {
         JarProcess {
             origin_prj_path,
@@ -42,6 +44,7 @@ impl<'a> JarProcess<'a> {
             host_crate_name,
             ast_result,
             config,
+            executor,
         }
     }
 }
@@ -89,6 +92,8 @@ impl<'a> BuildProcess for JarProcess<'a> {
                 host_crate: self.host_crate_name,
                 buf,
                 features: &self.config().features(),
+                has_stream: self.ast_result.has_stream(),
+                has_cancellation: self.ast_result.has_cancellation(),
             };
 
             unpack.unpack()?;
@@ -103,6 +108,9 @@ impl<'a> BuildProcess for JarProcess<'a> {
             namespace: self.config().namespace(),
             so_name: self.config().dylib_name(),
             ext_libs: self.config().ext_libs(),
+            unsigned: self.config().unsigned(),
+            panic: self.config().panic(),
+            executor: self.executor.clone(),
         }
         .gen_bridge(&bridge_c_src_path)?;
 
@@ -233,6 +241,9 @@ impl<'a> BuildProcess for JarProcess<'a> {
             namespace: self.config().namespace(),
             so_name: self.config().dylib_name(),
             ext_libs: self.config().ext_libs(),
+            unsigned: self.config().unsigned(),
+            panic: self.config().panic(),
+            executor: self.executor.clone(),
         }
         .gen_native(&java_gen_path)?;
 
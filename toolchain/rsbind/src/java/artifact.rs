@@ -1,11 +1,23 @@
 use crate::ast::AstResult;
 use crate::base::artifact::{NativeCodeGen, NativeGenStrategy};
 use crate::errors::*;
+use crate::java::buffer::{BufferGen, BUFFER_CLASS};
 use crate::java::callback::{CallbackGen, InnerCallbackGen};
+use crate::java::cancellation::{CancellationGen, CANCELLATION_CLASS};
+use crate::java::cleaner::{CleanerGen, CLEANER_CLASS};
+use crate::java::constants::{ConstantsGen, CONSTANTS_CLASS};
+use crate::java::dispatcher::{DispatcherGen, DISPATCHER_CLASS};
+use crate::java::enum_::EnumGen;
+use crate::java::exception::ExceptionGen;
 use crate::java::interface::InterfaceGen;
 use crate::java::internal::InnerTraitGen;
 use crate::java::manager::ManagerGen;
+use crate::java::object::ObjectGen;
+use crate::java::panic::{PanicGen, PANIC_CLASS};
 use crate::java::struct_::StructGen;
+use crate::java::subscription::{SubscriptionGen, SUBSCRIPTION_CLASS};
+use crate::java::tuple::TupleGen;
+use crate::java::types::{exception_name, tuple_name};
 use crate::java::wrapper::WrapperGen;
 use std::path::PathBuf;
 
@@ -63,6 +75,13 @@ impl<'a> JavaCodeGen<'a> {
                 }
                 .gen()
             }),
+            gen_object: Box::new(|ctx, desc| {
+                ObjectGen {
+                    desc,
+                    pkg: ctx.extra.namespace.clone(),
+                }
+                .gen()
+            }),
             gen_struct: Box::new(|ctx, desc| {
                 StructGen {
                     desc,
@@ -70,6 +89,79 @@ impl<'a> JavaCodeGen<'a> {
                 }
                 .gen()
             }),
+            gen_enum: Box::new(|ctx, desc| {
+                EnumGen {
+                    desc,
+                    pkg: ctx.extra.namespace.clone(),
+                }
+                .gen()
+            }),
+            gen_error: Box::new(|ctx, err| {
+                let content = ExceptionGen {
+                    err,
+                    pkg: ctx.extra.namespace.clone(),
+                }
+                .gen()?;
+                Ok((exception_name(err), content))
+            }),
+            gen_tuple: Some(Box::new(|ctx, arity| {
+                let content = TupleGen {
+                    arity,
+                    pkg: ctx.extra.namespace.clone(),
+                }
+                .gen()?;
+                Ok((tuple_name(arity), content))
+            })),
+            gen_buffer: Some(Box::new(|ctx| {
+                let content = BufferGen {
+                    pkg: ctx.extra.namespace.clone(),
+                }
+                .gen()?;
+                Ok((BUFFER_CLASS.to_owned(), content))
+            })),
+            gen_subscription: Some(Box::new(|ctx| {
+                let content = SubscriptionGen {
+                    pkg: ctx.extra.namespace.clone(),
+                }
+                .gen()?;
+                Ok((SUBSCRIPTION_CLASS.to_owned(), content))
+            })),
+            gen_cancellation: Box::new(|ctx| {
+                let content = CancellationGen {
+                    pkg: ctx.extra.namespace.clone(),
+                }
+                .gen()?;
+                Ok((CANCELLATION_CLASS.to_owned(), content))
+            }),
+            gen_dispatcher: Box::new(|ctx| {
+                let content = DispatcherGen {
+                    pkg: ctx.extra.namespace.clone(),
+                }
+                .gen()?;
+                Ok((DISPATCHER_CLASS.to_owned(), content))
+            }),
+            gen_panic: Some(Box::new(|ctx| {
+                let content = PanicGen {
+                    pkg: ctx.extra.namespace.clone(),
+                }
+                .gen()?;
+                Ok((PANIC_CLASS.to_owned(), content))
+            })),
+            gen_cleaner: Some(Box::new(|ctx| {
+                let content = CleanerGen {
+                    pkg: ctx.extra.namespace.clone(),
+                }
+                .gen()?;
+                Ok((CLEANER_CLASS.to_owned(), content))
+            })),
+            gen_constants: Box::new(|ctx, consts| {
+                let content = ConstantsGen {
+                    pkg: ctx.extra.namespace.clone(),
+                    consts,
+                }
+                .gen()?;
+                Ok((CONSTANTS_CLASS.to_owned(), content))
+            }),
             gen_manager: Box::new(|ctx| {
                 ManagerGen {
                     ast: ctx.ast,
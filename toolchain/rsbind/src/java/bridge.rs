@@ -2,22 +2,34 @@ use heck::ToUpperCamelCase;
 use proc_macro2::{Ident, Literal, TokenStream};
 use rstgen::Java;
 
-use crate::ast::contract::desc::TraitDesc;
+use crate::ast::contract::desc::{EnumDesc, MethodDesc, TraitDesc};
 
 use crate::ast::types::*;
+use crate::base::bridge::{
+    quote_base_ty, quote_callback_impls, quote_enum_proxy_convert, quote_object_ty,
+    quote_struct_members, quote_tuple_ty, quote_variant_pattern, quote_variant_value,
+};
 use crate::base::lang::{
-    BridgeContext, CallbackContext, Convertible, Direction, LangImp, MethodContext, ModContext,
-    StructContext,
+    BridgeContext, CallbackContext, Convertible, Direction, EnumContext, LangImp, MethodContext,
+    ModContext, ServiceContext, StructContext,
 };
 use crate::errors::*;
 use crate::java::converter::JavaConvert;
+use crate::java::panic::PANIC_CLASS;
+use crate::java::ty::proxy::{has_proxy, quote_from_proxy, quote_into_proxy, quote_proxy_ty};
+use crate::java::types::exception_name;
 use crate::java::JavaExtra;
 
 use crate::ident;
 
+///
+/// Wrap the java callback registered with the index, with `rust_defaults` the methods with default bodies
+/// are left to the rust trait instead of calling java, which is how the java default methods run them.
+///
 pub(crate) fn index_to_callback(
     callback_desc: &TraitDesc,
     java_namespace: &str,
+    rust_defaults: bool,
 ) -> Result<TokenStream> {
     let index_struct = quote! {
         #[derive(Serialize, Deserialize)]
@@ -29,8 +41,12 @@ pub(crate) fn index_to_callback(
     let class_name =
         format!("{}.Internal{}", java_namespace, &callback_desc.name).replace('.', "/");
 
-    let mut all_method_tokens = TokenStream::new();
+    let mut all_method_tokens = vec![];
     for method in callback_desc.methods.iter() {
+        if rust_defaults && method.has_default {
+            all_method_tokens.push(TokenStream::new());
+            continue;
+        }
         println!("[bridge] 🔆  begin quote callback method");
         // arguments converting in callback
         let mut args_convert = TokenStream::new();
@@ -55,7 +71,7 @@ pub(crate) fn index_to_callback(
                 #args_convert_each
             };
 
-            let cb_arg_array_each = match cb_arg.ty.clone() {
+            let cb_arg_array_each = match cb_arg.ty.unwrap_newtype() {
                 AstType::Byte(_) => quote! {
                     JValue::Byte(#cb_arg_name),
                 },
@@ -64,11 +80,11 @@ pub(crate) fn index_to_callback(
                     JValue::Short(#cb_arg_name),
                 },
 
-                AstType::Boolean | AstType::Int(_) => quote! {
+                AstType::Boolean | AstType::Int(_) | AstType::Enum(_) => quote! {
                     JValue::Int(#cb_arg_name),
                 },
 
-                AstType::Long(_) => quote! {
+                AstType::Long(_) | AstType::BigInt(_) => quote! {
                     JValue::Long(#cb_arg_name),
                 },
 
@@ -120,13 +136,24 @@ pub(crate) fn index_to_callback(
             .iter()
             .map(|arg| match arg.ty.clone() {
                 AstType::Vec(vec_inner_name) => {
-                    let vec_innder_ident = ident!(&vec_inner_name.origin());
+                    let vec_innder_ident = quote_base_ty(&vec_inner_name);
                     quote!(Vec<#vec_innder_ident>)
                 }
+                AstType::Optional(optional_inner_name) => {
+                    let optional_inner_ident = quote_base_ty(&optional_inner_name);
+                    quote!(Option<#optional_inner_ident>)
+                }
+                AstType::Map(map, key, value) => {
+                    let map_ident = ident!(&map);
+                    let key_ident = ident!(&key.origin());
+                    let value_ident = quote_base_ty(&value);
+                    quote!(std::collections::#map_ident<#key_ident, #value_ident>)
+                }
                 AstType::Callback(ref origin) => {
                     let callback_ident = ident!(&origin.origin);
                     quote!(Box<dyn #callback_ident>)
                 }
+                AstType::Tuple(ref elems) => quote_tuple_ty(elems),
                 _ => {
                     let ident = ident!(&arg.ty.origin());
                     quote!(#ident)
@@ -141,13 +168,24 @@ pub(crate) fn index_to_callback(
         let ret_ty_tokens = match method.return_type {
             AstType::Void => quote!(()),
             AstType::Vec(ref base) => {
-                let origin_ident = ident!(&base.origin());
+                let origin_ident = quote_base_ty(base);
                 quote!(Vec<#origin_ident>)
             }
+            AstType::Optional(ref base) => {
+                let origin_ident = quote_base_ty(base);
+                quote!(Option<#origin_ident>)
+            }
+            AstType::Map(ref map, ref key, ref value) => {
+                let map_ident = ident!(map);
+                let key_ident = ident!(&key.origin());
+                let value_ident = quote_base_ty(value);
+                quote!(std::collections::#map_ident<#key_ident, #value_ident>)
+            }
             AstType::Callback(ref origin) => {
                 let callback_ident = ident!(&origin.origin);
                 quote!(Box<dyn #callback_ident>)
             }
+            AstType::Tuple(ref elems) => quote_tuple_ty(elems),
             _ => {
                 let ident = ident!(&method.return_type.origin());
                 quote!(#ident)
@@ -179,13 +217,12 @@ pub(crate) fn index_to_callback(
         let method_name = ident!(&method.name);
         let java_method_name = format!("r2j{}", &method.name.to_upper_camel_case());
 
-        all_method_tokens = quote! {
-            #all_method_tokens
-
+        all_method_tokens.push(quote! {
             fn #method_name(&self, #(#arg_names: #arg_types),*) -> #ret_ty_tokens {
                 let ptr_jvm = JVM.read().unwrap();
                 let jvm = (*ptr_jvm).as_ref().unwrap();
-                let env = jvm.attach_current_thread_permanently().unwrap();
+                // Rust threads like the workers of async methods don't keep the jvm alive.
+                let env = jvm.attach_current_thread_as_daemon().unwrap();
 
                 #args_convert
 
@@ -201,7 +238,7 @@ pub(crate) fn index_to_callback(
                 #return_convert
                 #return_result_ident
             }
-        };
+        });
 
         println!(
             "[bridge] ✅ end quote callback method => {}.{}",
@@ -209,19 +246,18 @@ pub(crate) fn index_to_callback(
         );
     }
 
-    let callback_ident = ident!(&callback_desc.name);
+    let callback_impls =
+        quote_callback_impls(callback_desc, &ident!("IndexStruct"), &all_method_tokens);
     let result = quote! {
         #index_struct
 
-        impl #callback_ident for IndexStruct {
-            #all_method_tokens
-        }
+        #callback_impls
 
         impl Drop for IndexStruct {
             fn drop(&mut self) {
                 let ptr_jvm = JVM.read().unwrap();
                 let jvm = (*ptr_jvm).as_ref().unwrap();
-                let env = jvm.attach_current_thread_permanently().unwrap();
+                let env = jvm.attach_current_thread_as_daemon().unwrap();
 
                 let _method_result = env.call_static_method(
                     #class_name,
@@ -239,6 +275,353 @@ pub(crate) fn index_to_callback(
     Ok(result)
 }
 
+///
+/// The arguments of a callback method called by java on a callback implemented by rust,
+/// and their conversion to the rust arguments named `r_{arg}`.
+///
+fn quote_j2r_args(method: &MethodDesc) -> (Vec<Ident>, Vec<TokenStream>, TokenStream, Vec<Ident>) {
+    let arg_names = method
+        .args
+        .iter()
+        .map(|arg| ident!(&arg.name))
+        .collect::<Vec<Ident>>();
+
+    let arg_types = method
+        .args
+        .iter()
+        .map(|arg| JavaConvert { ty: arg.ty.clone() }.rust_transferable_type(Direction::Down))
+        .collect::<Vec<TokenStream>>();
+
+    let mut args_convert = TokenStream::new();
+    for arg in method.args.iter() {
+        let rust_arg_str = format!("r_{}", &arg.name);
+        let rust_arg_name = ident!(&rust_arg_str);
+        let arg_name_ident = ident!(&arg.name);
+        let convert = JavaConvert { ty: arg.ty.clone() }
+            .transferable_to_rust(quote! {#arg_name_ident}, Direction::Down);
+        let each_convert = quote! {
+            let #rust_arg_name = #convert;
+        };
+
+        args_convert = quote! {
+            #args_convert
+            #each_convert
+        }
+    }
+
+    let r_arg_names = method
+        .args
+        .iter()
+        .filter(|arg| !matches!(arg.ty, AstType::Void))
+        .map(|arg| ident!(&format!("r_{}", &arg.name)))
+        .collect::<Vec<Ident>>();
+
+    (arg_names, arg_types, args_convert, r_arg_names)
+}
+
+///
+/// Run the body of a jni function with the panics caught, they must not unwind into the jvm.
+///
+pub(crate) fn quote_catch_panic(body: TokenStream) -> TokenStream {
+    quote! {
+        crate::common::catch_panic(&env, || {
+            #body
+        })
+    }
+}
+
+///
+/// The function running the jni functions, a panic is thrown as `RustPanicException` with the message
+/// and the backtrace captured by the panic hook if `RUST_BACKTRACE` enables it, or aborts the process.
+/// The value returned to java after throwing is ignored by the jvm.
+///
+fn quote_catch_panic_fn(context: &BridgeContext<Java<'static>, JavaExtra>) -> TokenStream {
+    if context.extra.abort_on_panic {
+        return quote! {
+            pub fn catch_panic<T, F: FnOnce() -> T>(_env: &JNIEnv, f: F) -> T {
+                match std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)) {
+                    Ok(result) => result,
+                    Err(_) => std::process::abort(),
+                }
+            }
+
+            /// Async methods abort like the jni functions when the future panics.
+            pub fn panic_message(_payload: Box<dyn std::any::Any + Send>) -> String {
+                std::process::abort()
+            }
+        };
+    }
+
+    let panic_class = format!("{}.{}", &context.extra.namespace, PANIC_CLASS).replace('.', "/");
+    quote! {
+        thread_local! {
+            static PANIC_BACKTRACE: std::cell::RefCell<Option<String>> = std::cell::RefCell::new(None);
+        }
+
+        static PANIC_HOOK: std::sync::Once = std::sync::Once::new();
+
+        pub fn catch_panic<T, F: FnOnce() -> T>(env: &JNIEnv, f: F) -> T {
+            PANIC_HOOK.call_once(|| {
+                let default_hook = std::panic::take_hook();
+                std::panic::set_hook(Box::new(move |info| {
+                    let backtrace = std::backtrace::Backtrace::capture();
+                    if backtrace.status() == std::backtrace::BacktraceStatus::Captured {
+                        PANIC_BACKTRACE.with(|cell| *cell.borrow_mut() = Some(backtrace.to_string()));
+                    }
+                    default_hook(info);
+                }));
+            });
+            PANIC_BACKTRACE.with(|cell| cell.borrow_mut().take());
+
+            match std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)) {
+                Ok(result) => result,
+                Err(payload) => {
+                    let backtrace = PANIC_BACKTRACE.with(|cell| cell.borrow_mut().take());
+                    let message = panic_message(payload);
+
+                    // The panic replaces the java exception it may be caused by.
+                    if env.exception_check().unwrap_or(false) {
+                        let _ = env.exception_clear();
+                    }
+                    let j_message = env.new_string(&message).map(JObject::from);
+                    let j_backtrace = match backtrace {
+                        Some(backtrace) => env.new_string(backtrace).map(JObject::from),
+                        None => Ok(JObject::null()),
+                    };
+                    let exception = match (j_message, j_backtrace) {
+                        (Ok(j_message), Ok(j_backtrace)) => env.new_object(
+                            #panic_class,
+                            "(Ljava/lang/String;Ljava/lang/String;)V",
+                            &[JValue::Object(j_message), JValue::Object(j_backtrace)],
+                        ),
+                        (Err(err), _) | (_, Err(err)) => Err(err),
+                    };
+                    match exception {
+                        Ok(exception) => {
+                            let _ = env.throw(JThrowable::from(exception));
+                        }
+                        Err(_) => {
+                            let _ = env.throw_new(#panic_class, message);
+                        }
+                    }
+                    unsafe { std::mem::zeroed() }
+                }
+            }
+        }
+
+        /// The message of a caught panic, which completes async methods with `RustPanicException`.
+        /// The backtrace captured by the hook on the thread polling the future is dropped.
+        pub fn panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+            PANIC_BACKTRACE.with(|cell| cell.borrow_mut().take());
+            if let Some(message) = payload.downcast_ref::<&str>() {
+                message.to_string()
+            } else if let Some(message) = payload.downcast_ref::<String>() {
+                message.clone()
+            } else {
+                "rust panicked".to_owned()
+            }
+        }
+    }
+}
+
+/// Err is thrown as the generated exception, and Ok is converted as a normal return.
+pub(crate) fn quote_result_return_convert(
+    ctx: &MethodContext<Java<'static>, JavaExtra>,
+) -> Result<TokenStream> {
+    let ok_ty = ctx.method.return_type.ok_type();
+    let err_ty = match ctx.method.return_type.err_type() {
+        Some(err) => err,
+        None => {
+            return Err(ErrorKind::GenerateError("not a result return type.".to_string()).into())
+        }
+    };
+
+    let exception_class = format!(
+        "{}.{}",
+        &ctx.service_ctx.mod_ctx.bridge_ctx.extra.namespace,
+        exception_name(&err_ty)
+    )
+    .replace('.', "/");
+
+    let err_msg = match err_ty {
+        AstBaseType::Struct(ref origin) | AstBaseType::Enum(ref origin) => {
+            let proxy_struct = ident!(&format!("Proxy{}", &origin.origin));
+            quote! {serde_json::to_string(&#proxy_struct::from(err)).unwrap()}
+        }
+        _ => quote! {err},
+    };
+
+    let (ok_pattern, ok_convert, err_return) = if ok_ty == AstType::Void {
+        (quote! {_}, quote! {()}, quote! {()})
+    } else {
+        (
+            quote! {result},
+            JavaConvert { ty: ok_ty }.rust_to_transferable(quote! {result}, Direction::Down),
+            quote! {unsafe { std::mem::zeroed() }},
+        )
+    };
+
+    Ok(quote! {
+        match result {
+            Ok(#ok_pattern) => #ok_convert,
+            Err(err) => {
+                let _ = env.throw_new(#exception_class, #err_msg);
+                #err_return
+            }
+        }
+    })
+}
+
+///
+/// The base type of a field which may hold structs, enums or unsigned integers, maps are handled
+/// by their keys and values.
+///
+fn proxy_field_base(ty: &AstType) -> Option<AstBaseType> {
+    match ty.clone() {
+        AstType::Byte(origin) => Some(AstBaseType::Byte(origin)),
+        AstType::Short(origin) => Some(AstBaseType::Short(origin)),
+        AstType::Int(origin) => Some(AstBaseType::Int(origin)),
+        AstType::Long(origin) => Some(AstBaseType::Long(origin)),
+        AstType::Struct(origin) => Some(AstBaseType::Struct(origin)),
+        AstType::Enum(origin) => Some(AstBaseType::Enum(origin)),
+        AstType::Vec(base) => Some(AstBaseType::Vec(Box::new(base))),
+        AstType::Optional(base) => Some(AstBaseType::Optional(Box::new(base))),
+        _ => None,
+    }
+}
+
+fn quote_proxy_field_ty(ty: &AstType) -> TokenStream {
+    match (ty, proxy_field_base(ty)) {
+        (_, Some(ref base)) => quote_proxy_ty(base),
+        (AstType::Newtype(_, ref inner), _) => quote_proxy_ty(inner),
+        (AstType::Map(ref map, ref key, ref value), _) => {
+            let map_ident = ident!(map);
+            let key_ty = quote_proxy_ty(key);
+            let value_ty = quote_proxy_ty(value);
+            quote!(std::collections::#map_ident<#key_ty, #value_ty>)
+        }
+        _ => {
+            let ident = ident!(&ty.origin());
+            quote!(#ident)
+        }
+    }
+}
+
+///
+/// Structs and enums in fields are converted to their proxies recursively.
+///
+fn quote_field_into_proxy(value: TokenStream, ty: &AstType) -> TokenStream {
+    match (ty, proxy_field_base(ty)) {
+        (_, Some(ref base)) => quote_into_proxy(value, base),
+        (AstType::Newtype(_, ref inner), _) => quote_into_proxy(quote! {#value.0}, inner),
+        (AstType::Map(_, ref key, ref base), _) if has_proxy(key) || has_proxy(base) => {
+            let key_each = quote_into_proxy(quote! {key}, key);
+            let each = quote_into_proxy(quote! {each}, base);
+            quote! {#value.into_iter().map(|(key, each)| (#key_each, #each)).collect()}
+        }
+        _ => value,
+    }
+}
+
+fn quote_field_from_proxy(value: TokenStream, ty: &AstType) -> TokenStream {
+    match (ty, proxy_field_base(ty)) {
+        (_, Some(ref base)) => quote_from_proxy(value, base),
+        (AstType::Newtype(ref origin, ref inner), _) => {
+            let newtype = ident!(&origin.origin);
+            let value = quote_from_proxy(value, inner);
+            quote! {#newtype(#value)}
+        }
+        (AstType::Map(_, ref key, ref base), _) if has_proxy(key) || has_proxy(base) => {
+            let key_each = quote_from_proxy(quote! {key}, key);
+            let each = quote_from_proxy(quote! {each}, base);
+            quote! {#value.into_iter().map(|(key, each)| (#key_each, #each)).collect()}
+        }
+        _ => value,
+    }
+}
+
+///
+/// Enums with data are serialized with an internal `tag` field holding the variant name,
+/// tuple fields are named by their index like `_0`.
+///
+fn quote_data_enum_proxy(enum_: &EnumDesc) -> TokenStream {
+    let proxy_enum_name = ident!(&format!("Proxy{}", &enum_.name));
+    let origin_enum_name = ident!(&enum_.name);
+
+    let mut proxy_variants = vec![];
+    let mut origin_to_proxy = vec![];
+    let mut proxy_to_origin = vec![];
+    for variant in enum_.variants.iter() {
+        let variant_name = ident!(&variant.name);
+        let names = variant
+            .fields
+            .iter()
+            .map(|field| ident!(&field.name))
+            .collect::<Vec<Ident>>();
+        let tys = variant
+            .fields
+            .iter()
+            .map(|field| quote_proxy_field_ty(&field.ty))
+            .collect::<Vec<TokenStream>>();
+        let to_proxy_converts = variant
+            .fields
+            .iter()
+            .map(|field| {
+                let name = ident!(&field.name);
+                quote_field_into_proxy(quote! {#name}, &field.ty)
+            })
+            .collect::<Vec<TokenStream>>();
+        let to_origin_converts = variant
+            .fields
+            .iter()
+            .map(|field| {
+                let name = ident!(&field.name);
+                quote_field_from_proxy(quote! {#name}, &field.ty)
+            })
+            .collect::<Vec<TokenStream>>();
+
+        let origin_pattern = quote_variant_pattern(enum_, variant);
+        let origin_value = quote_variant_value(enum_, variant, &to_origin_converts);
+        if variant.fields.is_empty() {
+            proxy_variants.push(quote! {#variant_name});
+            origin_to_proxy.push(quote! {#origin_pattern => #proxy_enum_name::#variant_name});
+            proxy_to_origin.push(quote! {#proxy_enum_name::#variant_name => #origin_value});
+        } else {
+            proxy_variants.push(quote! {#variant_name { #(#names: #tys),* }});
+            origin_to_proxy.push(quote! {
+                #origin_pattern => #proxy_enum_name::#variant_name { #(#names: #to_proxy_converts),* }
+            });
+            proxy_to_origin.push(quote! {
+                #proxy_enum_name::#variant_name { #(#names),* } => #origin_value
+            });
+        }
+    }
+
+    quote! {
+        #[derive(Serialize, Deserialize)]
+        #[serde(tag = "tag")]
+        pub enum #proxy_enum_name {
+            #(#proxy_variants),*
+        }
+
+        impl From<#origin_enum_name> for #proxy_enum_name {
+            fn from(origin: #origin_enum_name) -> Self {
+                match origin {
+                    #(#origin_to_proxy),*
+                }
+            }
+        }
+
+        impl From<#proxy_enum_name> for #origin_enum_name {
+            fn from(proxy: #proxy_enum_name) -> Self {
+                match proxy {
+                    #(#proxy_to_origin),*
+                }
+            }
+        }
+    }
+}
+
 pub struct JavaImp {}
 
 impl LangImp<Java<'static>, JavaExtra> for JavaImp {
@@ -258,9 +641,25 @@ impl LangImp<Java<'static>, JavaExtra> for JavaImp {
             .collect::<Vec<Ident>>();
 
         let host_crate_underscore = ident!(&context.crate_name.replace("-", "_"));
+        let stream_crate = if context.ast.has_stream() {
+            quote!(
+                extern crate futures_core;
+            )
+        } else {
+            quote!()
+        };
+        let cancellation_crate = if context.ast.has_cancellation() {
+            quote!(
+                extern crate tokio_util;
+            )
+        } else {
+            quote!()
+        };
         Ok(quote! {
             #![allow(warnings)]
             extern crate #host_crate_underscore;
+            #stream_crate
+            #cancellation_crate
             extern crate jni;
             #[macro_use]
             extern crate serde_derive;
@@ -281,8 +680,11 @@ impl LangImp<Java<'static>, JavaExtra> for JavaImp {
             #[no_mangle]
             #[allow(non_snake_case)]
             pub extern "C" fn JNI_OnLoad(jvm: JavaVM, _reserved: *mut c_void) -> jint {
-                set_java_vm(jvm);
-                JNI_VERSION_1_6
+                // There is no java caller to throw to, a panic fails loading the library.
+                match std::panic::catch_unwind(std::panic::AssertUnwindSafe(move || set_java_vm(jvm))) {
+                    Ok(_) => JNI_VERSION_1_6,
+                    Err(_) => jni::sys::JNI_ERR,
+                }
             }
 
             pub fn set_java_vm(jvm: JavaVM) {
@@ -293,9 +695,111 @@ impl LangImp<Java<'static>, JavaExtra> for JavaImp {
 
     fn quote_common_file(
         &self,
-        _context: &BridgeContext<Java<'static>, JavaExtra>,
+        context: &BridgeContext<Java<'static>, JavaExtra>,
     ) -> Result<TokenStream> {
-        Ok(quote! {})
+        let namespace = context.extra.namespace.replace('.', "_");
+        let stats_fn = ident!(&format!("Java_{}_RustLib_nativeCallbackStats", &namespace));
+        let stats_convert = JavaConvert {
+            ty: AstType::String,
+        }
+        .rust_to_transferable(quote! {stats}, Direction::Down);
+        let catch_panic_tokens = quote_catch_panic_fn(context);
+        let stats_tokens = quote! {
+            use jni::JNIEnv;
+            use jni::objects::{JClass, JObject, JThrowable, JValue};
+            use jni::sys::{jobject, jstring};
+
+            #[no_mangle]
+            #[allow(non_snake_case)]
+            pub extern "C" fn #stats_fn(env: JNIEnv, _class: JClass) -> jstring {
+                catch_panic(&env, || {
+                    let stats = serde_json::to_string(&crate::callback_stats()).unwrap();
+                    #stats_convert
+                })
+            }
+
+            #catch_panic_tokens
+        };
+
+        // Tokens are owned by `CancellationToken`, rust receives the clones.
+        let stats_tokens = if context.ast.has_cancellation() {
+            let class_name = format!("Java_{}_CancellationToken", &namespace);
+            let new_fn = ident!(&format!("{}_nativeNew", &class_name));
+            let cancel_fn = ident!(&format!("{}_nativeCancel", &class_name));
+            let cancelled_fn = ident!(&format!("{}_nativeIsCancelled", &class_name));
+            let release_fn = ident!(&format!("{}_nativeRelease", &class_name));
+            quote! {
+                #stats_tokens
+
+                #[no_mangle]
+                #[allow(non_snake_case)]
+                pub extern "C" fn #new_fn(env: JNIEnv, _class: JClass) -> i64 {
+                    catch_panic(&env, || {
+                        Box::into_raw(Box::new(tokio_util::sync::CancellationToken::new())) as i64
+                    })
+                }
+
+                #[no_mangle]
+                #[allow(non_snake_case)]
+                pub extern "C" fn #cancel_fn(env: JNIEnv, _class: JClass, handle: i64) {
+                    catch_panic(&env, || {
+                        unsafe { (*(handle as *const tokio_util::sync::CancellationToken)).cancel() };
+                    })
+                }
+
+                #[no_mangle]
+                #[allow(non_snake_case)]
+                pub extern "C" fn #cancelled_fn(env: JNIEnv, _class: JClass, handle: i64) -> jni::sys::jboolean {
+                    catch_panic(&env, || {
+                        let token = unsafe { &*(handle as *const tokio_util::sync::CancellationToken) };
+                        token.is_cancelled() as jni::sys::jboolean
+                    })
+                }
+
+                #[no_mangle]
+                #[allow(non_snake_case)]
+                pub extern "C" fn #release_fn(env: JNIEnv, _class: JClass, handle: i64) {
+                    catch_panic(&env, || {
+                        unsafe { drop(Box::from_raw(handle as *mut tokio_util::sync::CancellationToken)) };
+                    })
+                }
+            }
+        } else {
+            stats_tokens
+        };
+
+        if !context.ast.has_buffer() {
+            return Ok(stats_tokens);
+        }
+
+        // The buffer is exposed as a read-only direct `ByteBuffer` until `RustBuffer` is closed.
+        let class_name = format!("Java_{}_RustBuffer", &namespace);
+        let buffer_fn = ident!(&format!("{}_nativeBuffer", &class_name));
+        let release_fn = ident!(&format!("{}_nativeRelease", &class_name));
+        Ok(quote! {
+            #stats_tokens
+
+            #[no_mangle]
+            #[allow(non_snake_case)]
+            pub extern "C" fn #buffer_fn(env: JNIEnv, _class: JClass, handle: i64) -> jobject {
+                catch_panic(&env, || {
+                    let buffer = unsafe { &*(handle as *const Box<dyn AsRef<[u8]> + Send>) };
+                    let slice = AsRef::<[u8]>::as_ref(&**buffer);
+                    let slice = unsafe { std::slice::from_raw_parts_mut(slice.as_ptr() as *mut u8, slice.len()) };
+                    env.new_direct_byte_buffer(slice)
+                        .expect("Couldn't new direct byte buffer!")
+                        .into_inner()
+                })
+            }
+
+            #[no_mangle]
+            #[allow(non_snake_case)]
+            pub extern "C" fn #release_fn(env: JNIEnv, _class: JClass, handle: i64) {
+                catch_panic(&env, || {
+                    unsafe { drop(Box::from_raw(handle as *mut Box<dyn AsRef<[u8]> + Send>)) };
+                })
+            }
+        })
     }
 
     fn quote_use_part(
@@ -324,10 +828,15 @@ impl LangImp<Java<'static>, JavaExtra> for JavaImp {
             .traits
             .iter()
             .map(|desc| {
+                let class_name = if desc.is_object {
+                    desc.name.clone()
+                } else {
+                    format!("Internal{}", &desc.name)
+                };
                 format!(
-                    "{}.Internal{}",
+                    "{}.{}",
                     context.bridge_ctx.extra.namespace.to_owned(),
-                    &desc.name
+                    &class_name
                 )
                 .replace('.', "/")
             })
@@ -336,8 +845,6 @@ impl LangImp<Java<'static>, JavaExtra> for JavaImp {
         Ok(quote! {
             lazy_static! {
                 static ref JVM : Arc<RwLock<Option<JavaVM>>> = Arc::new(RwLock::new(None));
-                static ref CALLBACK_HASHMAP: Arc<RwLock<HashMap<i64, CallbackEnum>>> =  Arc::new(RwLock::new(HashMap::new()));
-                static ref CALLBACK_INDEX : Arc<RwLock<i64>> = Arc::new(RwLock::new(0));
             }
 
             pub fn set_global_vm(jvm: JavaVM) {
@@ -363,34 +870,46 @@ impl LangImp<Java<'static>, JavaExtra> for JavaImp {
             .namespace
             .replace('.', "_");
 
+        let trait_ = context.service_ctx.trait_;
+        let class_name = if trait_.is_object {
+            trait_.name.clone()
+        } else {
+            format!("Internal{}", &trait_.name)
+        };
         let method_name = format!(
-            "Java_{}_Internal{}_native{}",
+            "Java_{}_{}_native{}",
             &namespace,
-            context.service_ctx.trait_.name.clone(),
+            &class_name,
             &context.method.name.to_upper_camel_case().replace('_', "_1")
         );
         let method_name_ident = ident!(&method_name);
-        let arg_names = context
+        let mut arg_names = context
             .method
             .args
             .iter()
             .map(|arg| ident!(&arg.name))
             .collect::<Vec<Ident>>();
 
-        let arg_types = context
+        let mut arg_types = context
             .method
             .args
             .iter()
             .map(|arg| JavaConvert { ty: arg.ty.clone() }.rust_transferable_type(Direction::Down))
             .collect::<Vec<TokenStream>>();
 
+        // Methods of objects are called on the handle, constructors create it.
+        if trait_.is_object && !context.method.is_static {
+            arg_names.insert(0, ident!("handle"));
+            arg_types.insert(0, quote! {i64});
+        }
+
         let ret_ty_tokens = JavaConvert {
             ty: context.method.return_type.clone(),
         }
         .rust_transferable_type(Direction::Up);
 
         let method_sig = if arg_names.is_empty() {
-            match context.method.return_type {
+            match context.method.return_type.ok_type() {
                 AstType::Void => quote! {
                     #[no_mangle]
                     #[allow(non_snake_case)]
@@ -403,7 +922,7 @@ impl LangImp<Java<'static>, JavaExtra> for JavaImp {
                 },
             }
         } else {
-            match context.method.return_type {
+            match context.method.return_type.ok_type() {
                 AstType::Void => quote! {
                     #[no_mangle]
                     #[allow(non_snake_case)]
@@ -436,14 +955,30 @@ impl LangImp<Java<'static>, JavaExtra> for JavaImp {
             .iter()
             .map(|field| ident!(&field.name))
             .collect::<Vec<Ident>>();
-        let arg_names = names.clone();
-        let origin_arg_names = names.clone();
+        let members = quote_struct_members(context.struct_);
         let tys = context
             .struct_
             .fields
             .iter()
-            .map(|field| ident!(&field.ty.origin()))
-            .collect::<Vec<Ident>>();
+            .map(|field| quote_proxy_field_ty(&field.ty))
+            .collect::<Vec<TokenStream>>();
+        let to_proxy_converts = context
+            .struct_
+            .fields
+            .iter()
+            .zip(members.iter())
+            .map(|(field, member)| quote_field_into_proxy(quote! {origin.#member}, &field.ty))
+            .collect::<Vec<TokenStream>>();
+        let to_origin_converts = context
+            .struct_
+            .fields
+            .iter()
+            .map(|field| {
+                let name = ident!(&field.name);
+                quote_field_from_proxy(quote! {proxy.#name}, &field.ty)
+            })
+            .collect::<Vec<TokenStream>>();
+
         Ok(quote! {
             #[derive(Serialize, Deserialize)]
             pub struct #struct_name {
@@ -452,18 +987,36 @@ impl LangImp<Java<'static>, JavaExtra> for JavaImp {
 
             impl From<#origin_struct_name> for #struct_name {
                 fn from(origin: #origin_struct_name) -> Self {
-                    #struct_name{#(#origin_arg_names: origin.#arg_names),*}
+                    #struct_name{#(#names: #to_proxy_converts),*}
                 }
             }
 
             impl From<#struct_name> for #origin_struct_name {
-                fn from(origin: #struct_name) -> Self {
-                    #origin_struct_name{#(#origin_arg_names: origin.#arg_names),*}
+                fn from(proxy: #struct_name) -> Self {
+                    #origin_struct_name{#(#members: #to_origin_converts),*}
                 }
             }
         })
     }
 
+    fn quote_for_one_enum(
+        &self,
+        context: &EnumContext<Java<'static>, JavaExtra>,
+    ) -> Result<TokenStream> {
+        let proxy_enum_name = ident!(&format!("Proxy{}", &context.enum_.name));
+        if !context.enum_.is_c_like() {
+            return Ok(quote_data_enum_proxy(context.enum_));
+        }
+
+        let convert = quote_enum_proxy_convert(context.enum_);
+        Ok(quote! {
+            #[derive(Serialize, Deserialize)]
+            pub struct #proxy_enum_name(pub i32);
+
+            #convert
+        })
+    }
+
     fn quote_for_one_callback(
         &self,
         context: &CallbackContext<Java<'static>, JavaExtra>,
@@ -483,49 +1036,13 @@ impl LangImp<Java<'static>, JavaExtra> for JavaImp {
             );
             let origin_method_name = ident!(&method.name);
             let method_name_ident = ident!(&method_name);
-            let arg_names = method
-                .args
-                .iter()
-                .map(|arg| ident!(&arg.name))
-                .collect::<Vec<Ident>>();
-
-            let arg_types = method
-                .args
-                .iter()
-                .map(|arg| {
-                    JavaConvert { ty: arg.ty.clone() }.rust_transferable_type(Direction::Down)
-                })
-                .collect::<Vec<TokenStream>>();
+            let (arg_names, arg_types, args_convert, r_arg_names) = quote_j2r_args(method);
 
             let ret_ty_tokens = JavaConvert {
                 ty: method.return_type.clone(),
             }
             .rust_transferable_type(Direction::Up);
 
-            let mut args_convert = TokenStream::new();
-            for arg in method.args.iter() {
-                let rust_arg_str = format!("r_{}", &arg.name);
-                let rust_arg_name = ident!(&rust_arg_str);
-                let arg_name_ident = ident!(&arg.name);
-                let convert = JavaConvert { ty: arg.ty.clone() }
-                    .transferable_to_rust(quote! {#arg_name_ident}, Direction::Down);
-                let each_convert = quote! {
-                    let #rust_arg_name = #convert;
-                };
-
-                args_convert = quote! {
-                    #args_convert
-                    #each_convert
-                }
-            }
-
-            let r_arg_names = &method
-                .args
-                .iter()
-                .filter(|arg| !matches!(arg.ty, AstType::Void))
-                .map(|arg| ident!(&format!("r_{}", &arg.name)))
-                .collect::<Vec<Ident>>();
-
             let mut return_convert = if let AstType::Void = method.return_type.clone() {
                 quote! {}
             } else {
@@ -544,39 +1061,41 @@ impl LangImp<Java<'static>, JavaExtra> for JavaImp {
                     #[no_mangle]
                     #[allow(non_snake_case)]
                     pub extern "C" fn #method_name_ident(env: JNIEnv, class: JClass, index: i64, #(#arg_names: #arg_types),*) -> #ret_ty_tokens {
-                        #args_convert
-                        let mut result: Option<Box<dyn #return_callback_ident >> = None;
-                        let final_result = {
-                            let callback_hashmap = &*CALLBACK_HASHMAP.read().unwrap();
-                            let ret_callback = callback_hashmap.get(&index);
-                            match ret_callback {
-                                Some(ret_callback) => {
-                                    if let CallbackEnum::#callback_ident(ret_callback) = ret_callback {
-                                        result = Some(ret_callback.#origin_method_name(#(#r_arg_names),*));
-                                        {
-                                            let mut global_index = CALLBACK_INDEX.write().unwrap();
-                                            let mut index = *global_index;
-                                            if index == i64::MAX {
-                                                *global_index = 0;
-                                                index = 0;
-                                            } else {
-                                                *global_index = index + 1;
-                                                index = index + 1;
+                        crate::common::catch_panic(&env, || {
+                            #args_convert
+                            let mut result: Option<Box<dyn #return_callback_ident >> = None;
+                            let final_result = {
+                                let callback_hashmap = &*CALLBACK_HASHMAP.read().unwrap();
+                                let ret_callback = callback_hashmap.get(&index);
+                                match ret_callback {
+                                    Some(ret_callback) => {
+                                        if let CallbackEnum::#callback_ident(ret_callback) = ret_callback {
+                                            result = Some(ret_callback.#origin_method_name(#(#r_arg_names),*));
+                                            {
+                                                let mut global_index = CALLBACK_INDEX.write().unwrap();
+                                                let mut index = *global_index;
+                                                if index == i64::MAX {
+                                                    *global_index = 0;
+                                                    index = 0;
+                                                } else {
+                                                    *global_index = index + 1;
+                                                    index = index + 1;
+                                                }
+                                                index
                                             }
-                                            index
+                                        } else {
+                                            panic!("Callback doesn't match for index: {}", index);
                                         }
-                                    } else {
-                                        panic!("Callback doesn't match for index: {}", index);
+                                    }
+                                    None => {
+                                        panic!("No callback found for index: {}", index);
                                     }
                                 }
-                                None => {
-                                    panic!("No callback found for index: {}", index);
-                                }
-                            }
-                        };
+                            };
 
-                        (*CALLBACK_HASHMAP.write().unwrap()).insert(final_result, CallbackEnum::#return_callback_ident(result.unwrap()));
-                        final_result
+                            insert_callback(final_result, CallbackEnum::#return_callback_ident(result.unwrap()));
+                            final_result
+                        })
                     }
 
                 }
@@ -587,22 +1106,24 @@ impl LangImp<Java<'static>, JavaExtra> for JavaImp {
                     #[no_mangle]
                     #[allow(non_snake_case)]
                     pub extern "C" fn #method_name_ident(env: JNIEnv, class: JClass, index: i64, #(#arg_names: #arg_types),*) -> #ret_ty_tokens {
-                        #args_convert
-                        let callback_hashmap = &*CALLBACK_HASHMAP.read().unwrap();
-                        let ret_callback = callback_hashmap.get(&index);
-                        match ret_callback {
-                            Some(ret_callback) => {
-                                if let CallbackEnum::#callback_ident(ret_callback) = ret_callback {
-                                    let mut result = ret_callback.#origin_method_name(#(#r_arg_names),*);
-                                    #return_convert
-                                } else {
-                                    panic!("Callback doesn't match for index: {}", index);
+                        crate::common::catch_panic(&env, || {
+                            #args_convert
+                            let callback_hashmap = &*CALLBACK_HASHMAP.read().unwrap();
+                            let ret_callback = callback_hashmap.get(&index);
+                            match ret_callback {
+                                Some(ret_callback) => {
+                                    if let CallbackEnum::#callback_ident(ret_callback) = ret_callback {
+                                        let mut result = ret_callback.#origin_method_name(#(#r_arg_names),*);
+                                        #return_convert
+                                    } else {
+                                        panic!("Callback doesn't match for index: {}", index);
+                                    }
+                                }
+                                None => {
+                                    panic!("No callback found for index: {}", index);
                                 }
                             }
-                            None => {
-                                panic!("No callback found for index: {}", index);
-                            }
-                        }
+                        })
                     }
                 }
             }
@@ -620,7 +1141,7 @@ impl LangImp<Java<'static>, JavaExtra> for JavaImp {
             #[no_mangle]
             #[allow(non_snake_case)]
             pub extern "C" fn #free_method_name(env: JNIEnv, class: JClass, index: i64) {
-                (*CALLBACK_HASHMAP.write().unwrap()).remove(&index);
+                crate::common::catch_panic(&env, || remove_callback(index))
             }
         };
 
@@ -628,13 +1149,66 @@ impl LangImp<Java<'static>, JavaExtra> for JavaImp {
 
         let callback_ident = ident!(&callback.name);
         let index_to_cb_fn_body =
-            index_to_callback(callback, &context.mod_ctx.bridge_ctx.extra.namespace)?;
+            index_to_callback(callback, &context.mod_ctx.bridge_ctx.extra.namespace, false)?;
         let index_to_cb_fn = quote! {
             fn #index_to_cb_fn_name(index: i64) -> Box<dyn #callback_ident> {
                 #index_to_cb_fn_body
             }
         };
 
+        // The default methods of the java interface run the rust default implementations.
+        let default_methods = callback
+            .methods
+            .iter()
+            .filter(|method| method.has_default && method.supertrait.is_none())
+            .collect::<Vec<&MethodDesc>>();
+        if !default_methods.is_empty() {
+            let index_to_default_cb_fn_name =
+                ident!(&format!("index_to_default_callback_{}", &callback.name));
+            let index_to_default_cb_fn_body =
+                index_to_callback(callback, &context.mod_ctx.bridge_ctx.extra.namespace, true)?;
+            body = quote! {
+                #body
+
+                fn #index_to_default_cb_fn_name(index: i64) -> Box<dyn #callback_ident> {
+                    #index_to_default_cb_fn_body
+                }
+            };
+            for method in default_methods {
+                let method_name = ident!(&format!(
+                    "Java_{}_Internal{}_j2rDefault{}",
+                    &namespace,
+                    &callback.name,
+                    &method.name.to_upper_camel_case()
+                ));
+                let origin_method_name = ident!(&method.name);
+                let (arg_names, arg_types, args_convert, r_arg_names) = quote_j2r_args(method);
+                let ret_ty_tokens = JavaConvert {
+                    ty: method.return_type.clone(),
+                }
+                .rust_transferable_type(Direction::Up);
+                let return_convert = JavaConvert {
+                    ty: method.return_type.clone(),
+                }
+                .rust_to_transferable(quote! {result}, Direction::Down);
+
+                body = quote! {
+                    #body
+
+                    #[no_mangle]
+                    #[allow(non_snake_case)]
+                    pub extern "C" fn #method_name(env: JNIEnv, class: JClass, index: i64, #(#arg_names: #arg_types),*) -> #ret_ty_tokens {
+                        crate::common::catch_panic(&env, || {
+                            #args_convert
+                            let callback = #index_to_default_cb_fn_name(index);
+                            let mut result = callback.#origin_method_name(#(#r_arg_names),*);
+                            #return_convert
+                        })
+                    }
+                };
+            }
+        }
+
         let cb_to_index_fn_name = ident!(&format!("callback_to_index_{}", &callback.name));
         let cb_to_index_fn = quote! {
             fn #cb_to_index_fn_name(callback: Box<dyn #callback_ident>) -> i64 {
@@ -650,7 +1224,7 @@ impl LangImp<Java<'static>, JavaExtra> for JavaImp {
                     }
                     index
                 };
-                (*CALLBACK_HASHMAP.write().unwrap()).insert(callback_index, CallbackEnum::#callback_ident(callback));
+                insert_callback(callback_index, CallbackEnum::#callback_ident(callback));
 
                 callback_index
             }
@@ -664,6 +1238,26 @@ impl LangImp<Java<'static>, JavaExtra> for JavaImp {
         })
     }
 
+    fn quote_free_object(
+        &self,
+        context: &ServiceContext<Java<'static>, JavaExtra>,
+    ) -> Result<TokenStream> {
+        let object_ty = quote_object_ty(context);
+        let free_method_name = ident!(&format!(
+            "Java_{}_{}_nativeFreeObject",
+            context.mod_ctx.bridge_ctx.extra.namespace.replace('.', "_"),
+            &context.trait_.name
+        ));
+
+        Ok(quote! {
+            #[no_mangle]
+            #[allow(non_snake_case)]
+            pub extern "C" fn #free_method_name(env: JNIEnv, class: JClass, handle: i64) {
+                crate::common::catch_panic(&env, || unsafe { drop(Box::from_raw(handle as *mut #object_ty)) });
+            }
+        })
+    }
+
     fn provide_converter(&self, ty: &AstType) -> Box<dyn Convertible<Java<'static>>> {
         Box::new(JavaConvert { ty: ty.clone() })
     }
@@ -0,0 +1,95 @@
+use rstgen::java::{Argument, Class, Constructor, Field, Method, Modifier};
+use rstgen::{java, IntoTokens, Java, Tokens};
+
+use crate::errors::*;
+use crate::java::cleaner::{cleanable_field, register_statement, release_class};
+use crate::java::types::to_java_file;
+
+pub(crate) const BUFFER_CLASS: &str = "RustBuffer";
+
+///
+/// Generate the class wrapping `bytes::Bytes` returned from rust.
+/// The direct `ByteBuffer` is backed by rust memory, which is released by `close()` or by `RustCleaner`.
+///
+pub(crate) struct BufferGen {
+    pub pkg: String,
+}
+
+impl BufferGen {
+    pub(crate) fn gen(&self) -> Result<String> {
+        let byte_buffer = java::imported("java.nio", "ByteBuffer");
+
+        let mut class = Class::new(BUFFER_CLASS);
+        class.modifiers = vec![Modifier::Public, Modifier::Final];
+        class
+            .implements
+            .push(java::imported("java.lang", "AutoCloseable"));
+
+        let mut handle = Field::new(java::LONG, "handle");
+        handle.modifiers = vec![Modifier::Private];
+        class.fields.push(handle);
+
+        let mut buffer = Field::new(byte_buffer.clone(), "buffer");
+        buffer.modifiers = vec![Modifier::Private, Modifier::Final];
+        class.fields.push(buffer);
+        class.fields.push(cleanable_field());
+
+        let mut constructor = Constructor::new();
+        constructor.modifiers = vec![];
+        let mut argument = Argument::new(java::LONG, "handle");
+        argument.modifiers = vec![];
+        constructor.arguments.push(argument);
+        push!(constructor.body, "this.handle = handle;");
+        push!(
+            constructor.body,
+            "this.buffer = nativeBuffer(handle).asReadOnlyBuffer();"
+        );
+        constructor
+            .body
+            .push(register_statement(BUFFER_CLASS, "handle"));
+        class.constructors.push(constructor);
+
+        let mut method = Method::new("buffer");
+        method.modifiers = vec![Modifier::Public];
+        method.returns = byte_buffer.clone();
+        push!(method.body, "return buffer;");
+        class.methods.push(method);
+
+        let mut method = Method::new("nativeBuffer");
+        method.modifiers = vec![Modifier::Private, Modifier::Static, Modifier::Native];
+        method.returns = byte_buffer;
+        let mut argument = Argument::new(java::LONG, "handle");
+        argument.modifiers = vec![];
+        method.arguments.push(argument);
+        class.methods.push(method);
+
+        let mut method = Method::new("nativeRelease");
+        method.modifiers = vec![Modifier::Private, Modifier::Static, Modifier::Native];
+        method.returns = java::VOID;
+        let mut argument = Argument::new(java::LONG, "handle");
+        argument.modifiers = vec![];
+        method.arguments.push(argument);
+        class.methods.push(method);
+
+        // rstgen has no synchronized modifier, so `close()` is written by hand.
+        let mut body: Tokens<Java> = Tokens::new();
+        body.push("public synchronized void close() {");
+        body.nested({
+            let mut close_body: Tokens<Java> = Tokens::new();
+            close_body.push("if (handle != 0) {");
+            close_body.nested({
+                let mut release_body: Tokens<Java> = Tokens::new();
+                release_body.push("handle = 0;");
+                release_body.push("cleanable.clean();");
+                release_body
+            });
+            close_body.push("}");
+            close_body
+        });
+        body.push("}");
+        body.push(release_class("nativeRelease").into_tokens());
+        class.body = body;
+
+        to_java_file(self.pkg.as_ref(), class.into_tokens())
+    }
+}
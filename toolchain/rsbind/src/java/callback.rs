@@ -6,7 +6,12 @@ use crate::ast::contract::desc::{MethodDesc, TraitDesc};
 use crate::ast::types::AstType;
 use crate::base::lang::{Convertible, Direction};
 use crate::errors::*;
+use crate::java::cleaner::{
+    cleanable_field, guard_comment, guard_fields, guard_methods, guarded_body, register_statement,
+    release_class,
+};
 use crate::java::converter::JavaConvert;
+use crate::java::dispatcher::DISPATCHER_CLASS;
 use crate::java::types::{to_java_file, JavaType};
 
 pub(crate) struct CallbackGen<'a> {
@@ -19,8 +24,17 @@ impl<'a> CallbackGen<'a> {
         let mut interface = Interface::new(self.desc.name.clone());
         interface.modifiers.push(Modifier::Public);
         interface.extends = toks!(java::imported("java.io", "Serializable"));
+        for supertrait in self.desc.supertraits.iter() {
+            interface.extends.append(java::local(supertrait.clone()));
+        }
 
-        for method in self.desc.methods.iter() {
+        // Inherited methods are declared by the interfaces of the supertraits.
+        for method in self
+            .desc
+            .methods
+            .iter()
+            .filter(|method| method.supertrait.is_none())
+        {
             let mut m = Method::new(method.name.to_lower_camel_case());
             m.modifiers = vec![];
             m.returns = Java::from(JavaType::new(method.return_type.clone()));
@@ -31,6 +45,26 @@ impl<'a> CallbackGen<'a> {
 
                 m.arguments.push(argument);
             }
+
+            // Default methods call the default implementation of the rust trait.
+            if method.has_default {
+                m.modifiers = vec![Modifier::Default];
+                let args = method
+                    .args
+                    .iter()
+                    .map(|arg| format!(", {}", &arg.name))
+                    .collect::<String>();
+                let call = format!(
+                    "Internal{}.default{}(this{});",
+                    &self.desc.name,
+                    method.name.to_upper_camel_case(),
+                    args
+                );
+                match method.return_type {
+                    AstType::Void => push!(m.body, call),
+                    _ => push!(m.body, "return ", call),
+                }
+            }
             interface.methods.push(m);
         }
 
@@ -88,9 +122,11 @@ impl<'a> InnerCallbackGen<'a> {
         self.fill_free_freecb_method(&mut inner_class)?;
 
         for cb_method in self.desc.methods.iter() {
-            let m = self.fill_j2r_cb_method_sig(cb_method, self.desc)?;
+            let native_name = format!("j2r{}", &cb_method.name.to_upper_camel_case());
+            let m = self.fill_j2r_cb_method_sig(cb_method, native_name)?;
             inner_class.methods.push(m);
         }
+        self.fill_default_methods(&mut inner_class)?;
         let mut free_cb_method = java::Method::new("j2rFreeCallback");
         free_cb_method.modifiers = vec![
             java::Modifier::Native,
@@ -102,14 +138,20 @@ impl<'a> InnerCallbackGen<'a> {
             .push(Argument::new(java::LONG, "index"));
         inner_class.methods.push(free_cb_method);
 
+        // Callbacks implemented by rust are released by `close()` or by `RustCleaner`.
         let class_name = format!("J2R{}Wrapper", &self.desc.name);
         let mut class = java::Class::new(class_name);
         class.modifiers = vec![java::Modifier::Static];
         class.implements.push(java::local(self.desc.name.clone()));
+        class
+            .implements
+            .push(java::imported("java.lang", "AutoCloseable"));
 
         let mut filed = java::Field::new(java::LONG, "index");
         filed.modifiers = vec![Modifier::Private];
         class.fields.push(filed);
+        class.fields.push(cleanable_field());
+        class.fields.extend(guard_fields());
 
         let mut constructor = java::Constructor::new();
         constructor.modifiers = vec![Modifier::Public];
@@ -117,10 +159,9 @@ impl<'a> InnerCallbackGen<'a> {
             .arguments
             .push(Argument::new(java::LONG, "index"));
         push!(constructor.body, "this.index = index;");
-        // push!(constructor.body,        //     "cleaner.register(this, () -> ",
-        //     inner_class_name.clone(),
-        //     ".j2rFreeCallback(index));"
-        // ));
+        constructor
+            .body
+            .push(register_statement(&self.desc.name, "index"));
         class.constructors.push(constructor);
 
         for method in self.desc.methods.iter() {
@@ -144,7 +185,8 @@ impl<'a> InnerCallbackGen<'a> {
 
             let mut method_body = Tokens::new();
             self.fill_j2r_cb_arg_convert(&mut method_body, method)?;
-            self.fill_j2r_cb_invoke(&mut method_body, method, self.desc)?;
+            let native_name = format!("j2r{}", &method.name.to_upper_camel_case());
+            self.fill_j2r_cb_invoke(&mut method_body, method, &native_name)?;
 
             match method.return_type.clone() {
                 AstType::Void => {}
@@ -153,29 +195,73 @@ impl<'a> InnerCallbackGen<'a> {
                         ty: method.return_type.clone(),
                     }
                     .transferable_to_native("ret".to_string(), Direction::Down);
-                    push_f!(method_body, "return {};", convert);
+                    push!(method_body, "return ", convert, ";");
                 }
             }
 
-            m.body = method_body;
+            m.body = guarded_body("index", method_body);
             class.methods.push(m);
         }
 
-        let mut finalize_method = Method::new("finalize");
-        finalize_method.modifiers = vec![Modifier::Protected];
-        finalize_method.annotation(toks!("@Override"));
-        let _ = finalize_method.throws.insert(toks!("Throwable"));
-        push!(finalize_method.body, "super.finalize();");
-        finalize_method
+        class.body.push(guard_methods(&self.desc.name, "index"));
+        class
             .body
-            .push(toks!(inner_class_name, ".j2rFreeCallback(index);"));
-        class.methods.push(finalize_method);
+            .push(release_class("j2rFreeCallback").into_tokens());
 
-        inner_class.body.push(class.into_tokens());
+        let mut wrapper: Tokens<Java> = Tokens::new();
+        wrapper.push(guard_comment(&self.desc.name));
+        wrapper.push(class.into_tokens());
+        inner_class.body.push(wrapper);
 
         to_java_file(self.pkg.as_ref(), inner_class.into_tokens())
     }
 
+    ///
+    /// The default methods of the interface call the default implementation of the rust trait,
+    /// with the callback registered like an argument, so rust can call its other methods.
+    ///
+    fn fill_default_methods(&self, class: &mut Class<'a>) -> Result<()> {
+        for method in self
+            .desc
+            .methods
+            .iter()
+            .filter(|method| method.has_default && method.supertrait.is_none())
+        {
+            let name = method.name.to_upper_camel_case();
+            let native_name = format!("j2rDefault{}", &name);
+            class
+                .methods
+                .push(self.fill_j2r_cb_method_sig(method, native_name.clone())?);
+
+            let mut m = Method::new(format!("default{}", &name));
+            m.modifiers = vec![Modifier::Static];
+            m.returns = Java::from(JavaType::new(method.return_type.clone()));
+            let mut argument = Argument::new(java::local(self.desc.name.clone()), "callback");
+            argument.modifiers = vec![];
+            m.arguments.push(argument);
+            for arg in method.args.iter() {
+                let mut argument =
+                    Argument::new(Java::from(JavaType::new(arg.ty.clone())), arg.name.clone());
+                argument.modifiers = vec![];
+                m.arguments.push(argument);
+            }
+
+            push!(m.body, "long index = pushGlobalCallback(callback);");
+            self.fill_j2r_cb_arg_convert(&mut m.body, method)?;
+            self.fill_j2r_cb_invoke(&mut m.body, method, &native_name)?;
+            if method.return_type != AstType::Void {
+                let convert = JavaConvert {
+                    ty: method.return_type.clone(),
+                }
+                .transferable_to_native("ret".to_string(), Direction::Down);
+                push!(m.body, "return ", convert, ";");
+            }
+            class.methods.push(m);
+        }
+
+        Ok(())
+    }
+
     fn fill_global_fields(&self, class: &mut Class<'a>) -> Result<()> {
         let mut index_field = Field::new(
             java::imported("java.util.concurrent.atomic", "AtomicLong"),
@@ -192,10 +278,6 @@ impl<'a> InnerCallbackGen<'a> {
         callbacks_field.modifiers = vec![Modifier::Private, Modifier::Static];
         class.fields.push(callbacks_field);
 
-        // let mut cleaner_field = Field::new(java::imported("java.lang.ref", "Cleaner"), "cleaner");
-        // cleaner_field.modifiers = vec![Modifier::Static, Modifier::Private];
-        // cleaner_field.initializer(toks!("Cleaner.create();"));
-        // class.fields.push(cleaner_field);
         Ok(())
     }
 
@@ -248,12 +330,19 @@ impl<'a> InnerCallbackGen<'a> {
             let java = Java::from(JavaType::new(arg.ty.clone()));
             let convert = JavaConvert { ty: arg.ty.clone() }
                 .transferable_to_native(arg.name.clone(), Direction::Up);
-            push_f!(
+            // Dispatched methods capture the converted arguments in the task.
+            let modifier = if cb_method.dispatch.is_some() {
+                "final "
+            } else {
+                ""
+            };
+            push!(
                 cb_body,
-                "{} j_{} = {};",
-                java.into_tokens(),
-                arg.name,
+                modifier,
+                java,
+                format!(" j_{} = ", arg.name),
                 convert,
+                ";"
             );
         }
 
@@ -276,13 +365,41 @@ impl<'a> InnerCallbackGen<'a> {
             }
         }
 
+        let modifier = if cb_method.dispatch.is_some() {
+            "final "
+        } else {
+            ""
+        };
         push_f!(
             cb_body,
-            "{} callback = ({}) globalCallbacks.get(index);",
+            "{}{} callback = ({}) globalCallbacks.get(index);",
+            modifier,
             callback.name,
             callback.name,
         );
         match cb_method.return_type.clone() {
+            // Only methods without return value are dispatched, rust doesn't wait for them.
+            AstType::Void if cb_method.dispatch.is_some() => {
+                push_f!(
+                    cb_body,
+                    "{}.execute(\"{}\", new Runnable() {{",
+                    DISPATCHER_CLASS,
+                    cb_method.dispatch.clone().unwrap_or_default()
+                );
+                cb_body.nested({
+                    let mut run: Tokens<Java> = Tokens::new();
+                    push!(run, "public void run() {");
+                    nested_f!(
+                        run,
+                        "callback.{}({});",
+                        cb_method.name.to_lower_camel_case(),
+                        arg_calls,
+                    );
+                    push!(run, "}");
+                    run
+                });
+                push!(cb_body, "});");
+            }
             AstType::Void => {
                 push_f!(
                     cb_body,
@@ -293,12 +410,14 @@ impl<'a> InnerCallbackGen<'a> {
             }
             _ => {
                 let java = JavaType::new(cb_method.return_type.clone());
-                push_f!(
+                push!(
                     cb_body,
-                    "{} result = callback.{}({});",
-                    Java::from(java).into_tokens(),
-                    cb_method.name.to_lower_camel_case(),
-                    arg_calls,
+                    Java::from(java),
+                    format!(
+                        " result = callback.{}({});",
+                        cb_method.name.to_lower_camel_case(),
+                        arg_calls
+                    )
                 );
             }
         }
@@ -319,7 +438,7 @@ impl<'a> InnerCallbackGen<'a> {
             ty: cb_method.return_type.clone(),
         }
         .native_to_transferable("result".to_string(), Direction::Up);
-        push_f!(cb_body, "return {};", convert);
+        push!(cb_body, "return ", convert, ";");
 
         Ok(())
     }
@@ -327,10 +446,9 @@ impl<'a> InnerCallbackGen<'a> {
     fn fill_j2r_cb_method_sig(
         &self,
         cb_method: &'a MethodDesc,
-        _callback: &'a TraitDesc,
+        native_name: String,
     ) -> Result<Method<'a>> {
-        let method_name = format!("j2r{}", &cb_method.name.to_upper_camel_case());
-        let mut m = java::Method::new(method_name);
+        let mut m = java::Method::new(native_name);
         m.modifiers = vec![Modifier::Private, Modifier::Static, Modifier::Native];
 
         if cb_method.return_type != AstType::Void {
@@ -364,13 +482,7 @@ impl<'a> InnerCallbackGen<'a> {
             let converted = format!("r_{}", &arg.name);
             let convert = JavaConvert { ty: arg.ty.clone() }
                 .native_to_transferable(arg.name.clone(), Direction::Down);
-            push_f!(
-                cb_body,
-                "{} {} = {};",
-                java.into_tokens(),
-                converted,
-                convert
-            );
+            push!(cb_body, java, format!(" {} = ", converted), convert, ";");
         }
 
         Ok(())
@@ -380,7 +492,7 @@ impl<'a> InnerCallbackGen<'a> {
         &self,
         cb_body: &mut Tokens<Java>,
         cb_method: &MethodDesc,
-        _callback: &TraitDesc,
+        native_name: &str,
     ) -> Result<()> {
         let mut arg_calls = "index".to_string();
         for arg in cb_method.args.iter() {
@@ -389,20 +501,15 @@ impl<'a> InnerCallbackGen<'a> {
 
         match cb_method.return_type.clone() {
             AstType::Void => {
-                push_f!(
-                    cb_body,
-                    "j2r{}({});",
-                    cb_method.name.to_upper_camel_case(),
-                    arg_calls
-                );
+                push_f!(cb_body, "{}({});", native_name, arg_calls);
             }
             _ => {
                 let java = JavaType::new(cb_method.return_type.clone()).to_transfer();
                 push_f!(
                     cb_body,
-                    "{} ret = j2r{}({});",
+                    "{} ret = {}({});",
                     java.into_tokens(),
-                    cb_method.name.to_upper_camel_case(),
+                    native_name,
                     arg_calls
                 );
             }
@@ -0,0 +1,123 @@
+use rstgen::java::{Class, Constructor, Field, Method, Modifier};
+use rstgen::{java, IntoTokens, Java, Tokens};
+
+use crate::errors::*;
+use crate::java::cleaner::{cleanable_field, register_statement, release_class};
+use crate::java::types::to_java_file;
+
+pub(crate) const CANCELLATION_CLASS: &str = "CancellationToken";
+
+///
+/// Generate the class holding a `CancellationToken` created by rust, which is passed to contract
+/// methods and cancelled by java code. The token is released by `close()` or by `RustCleaner`,
+/// the clones passed to rust still share the cancellation, but can't be cancelled by the closed token.
+///
+pub(crate) struct CancellationGen {
+    pub pkg: String,
+}
+
+impl CancellationGen {
+    pub(crate) fn gen(&self) -> Result<String> {
+        let mut class = Class::new(CANCELLATION_CLASS);
+        class.modifiers = vec![Modifier::Public, Modifier::Final];
+        class
+            .implements
+            .push(java::imported("java.lang", "AutoCloseable"));
+
+        let mut handle = Field::new(java::LONG, "handle");
+        handle.modifiers = vec![Modifier::Private];
+        class.fields.push(handle);
+        // Whether the token was cancelled when it was closed.
+        let mut cancelled = Field::new(java::BOOLEAN, "cancelled");
+        cancelled.modifiers = vec![Modifier::Private];
+        class.fields.push(cancelled);
+        class.fields.push(cleanable_field());
+
+        let mut constructor = Constructor::new();
+        constructor.modifiers = vec![Modifier::Public];
+        push!(constructor.body, "this.handle = nativeNew();");
+        constructor
+            .body
+            .push(register_statement(CANCELLATION_CLASS, "handle"));
+        class.constructors.push(constructor);
+
+        // The handle passed to rust, which clones the token.
+        let mut method = Method::new("handle");
+        method.modifiers = vec![];
+        method.returns = java::LONG;
+        push!(method.body, "if (handle == 0) {");
+        nested!(
+            method.body,
+            "throw new IllegalStateException(\"",
+            CANCELLATION_CLASS,
+            " is closed.\");"
+        );
+        push!(method.body, "}");
+        push!(method.body, "return handle;");
+        class.methods.push(method);
+
+        let mut method = Method::new("nativeNew");
+        method.modifiers = vec![Modifier::Private, Modifier::Static, Modifier::Native];
+        method.returns = java::LONG;
+        class.methods.push(method);
+
+        for (name, returns) in [
+            ("nativeCancel", java::VOID),
+            ("nativeIsCancelled", java::BOOLEAN),
+            ("nativeRelease", java::VOID),
+        ] {
+            let mut method = Method::new(name);
+            method.modifiers = vec![Modifier::Private, Modifier::Static, Modifier::Native];
+            method.returns = returns;
+            let mut argument = java::Argument::new(java::LONG, "handle");
+            argument.modifiers = vec![];
+            method.arguments.push(argument);
+            class.methods.push(method);
+        }
+
+        // rstgen has no synchronized modifier, so `cancel()`, `isCancelled()` and `close()` are
+        // written by hand. Like closing twice, cancelling a closed token does nothing.
+        let mut methods: Vec<Tokens<Java>> = vec![];
+        let mut cancel = Tokens::new();
+        push!(cancel, "public synchronized void cancel() {");
+        cancel.nested({
+            let mut body = Tokens::new();
+            push!(body, "if (handle != 0) {");
+            nested!(body, "nativeCancel(handle);");
+            push!(body, "}");
+            body
+        });
+        push!(cancel, "}");
+        methods.push(cancel);
+
+        let mut is_cancelled = Tokens::new();
+        push!(is_cancelled, "public synchronized boolean isCancelled() {");
+        nested!(
+            is_cancelled,
+            "return handle == 0 ? cancelled : nativeIsCancelled(handle);"
+        );
+        push!(is_cancelled, "}");
+        methods.push(is_cancelled);
+
+        let mut close = Tokens::new();
+        push!(close, "public synchronized void close() {");
+        close.nested({
+            let mut body = Tokens::new();
+            push!(body, "if (handle != 0) {");
+            nested!(body, "cancelled = nativeIsCancelled(handle);");
+            nested!(body, "handle = 0;");
+            nested!(body, "cleanable.clean();");
+            push!(body, "}");
+            body
+        });
+        push!(close, "}");
+        methods.push(close);
+
+        let mut body: Tokens<Java> = Tokens::new();
+        body.push(methods.into_tokens().join_line_spacing());
+        body.push(release_class("nativeRelease").into_tokens());
+        class.body = body;
+
+        to_java_file(self.pkg.as_ref(), class.into_tokens())
+    }
+}
@@ -0,0 +1,438 @@
+use rstgen::java::{Argument, Class, Constructor, Field, Method, Modifier};
+use rstgen::{java, IntoTokens, Java, Tokens};
+
+use crate::errors::*;
+use crate::java::types::to_java_file;
+
+pub(crate) const CLEANER_CLASS: &str = "RustCleaner";
+
+///
+/// Generate the class releasing the handles owned by java objects when they are not closed.
+/// `java.lang.ref.Cleaner` is used when the runtime has it, it is looked up by reflection because
+/// android before API 33 doesn't, which falls back to a daemon thread polling phantom references.
+///
+pub(crate) struct CleanerGen {
+    pub pkg: String,
+}
+
+impl CleanerGen {
+    pub(crate) fn gen(&self) -> Result<String> {
+        let object = java::imported("java.lang", "Object");
+        let runnable = java::imported("java.lang", "Runnable");
+        let queue_ty =
+            java::imported("java.lang.ref", "ReferenceQueue").with_arguments(vec![object.clone()]);
+        let set_ty = java::imported("java.util", "Set")
+            .with_arguments(vec![java::local("PhantomCleanable")]);
+
+        let mut class = Class::new(CLEANER_CLASS);
+        class.modifiers = vec![Modifier::Public, Modifier::Final];
+
+        let atomic = java::imported("java.util.concurrent.atomic", "AtomicBoolean");
+        let mut debug = Field::new(atomic.clone(), "debug");
+        debug.modifiers = vec![Modifier::Private, Modifier::Static, Modifier::Final];
+        debug.initializer(toks!("new ", atomic, "(false)"));
+        class.fields.push(debug);
+
+        let mut cleaner = Field::new(object.clone(), "jdkCleaner");
+        cleaner.modifiers = vec![Modifier::Private, Modifier::Static];
+        class.fields.push(cleaner);
+
+        let mut register = Field::new(java::imported("java.lang.reflect", "Method"), "jdkRegister");
+        register.modifiers = vec![Modifier::Private, Modifier::Static];
+        class.fields.push(register);
+
+        let mut queue = Field::new(queue_ty.clone(), "queue");
+        queue.modifiers = vec![Modifier::Private, Modifier::Static, Modifier::Final];
+        queue.initializer(toks!("new ", queue_ty, "()"));
+        class.fields.push(queue);
+
+        // Phantom references are only enqueued while they are reachable.
+        let mut references = Field::new(set_ty, "references");
+        references.modifiers = vec![Modifier::Private, Modifier::Static, Modifier::Final];
+        references.initializer(toks!(
+            java::imported("java.util", "Collections"),
+            ".synchronizedSet(new ",
+            java::imported("java.util", "HashSet"),
+            "<PhantomCleanable>())"
+        ));
+        class.fields.push(references);
+
+        let mut constructor = Constructor::new();
+        constructor.modifiers = vec![Modifier::Private];
+        class.constructors.push(constructor);
+
+        let mut method = Method::new("setDebug");
+        method.modifiers = vec![Modifier::Public, Modifier::Static];
+        method.returns = java::VOID;
+        let mut argument = Argument::new(java::BOOLEAN, "enabled");
+        argument.modifiers = vec![];
+        method.arguments.push(argument);
+        push!(method.body, "debug.set(enabled);");
+        class.methods.push(method);
+
+        let mut method = Method::new("register");
+        method.modifiers = vec![Modifier::Static];
+        method.returns = java::local("Cleanable");
+        for (ty, name) in [
+            (object, "owner"),
+            (java::imported("java.lang", "String"), "name"),
+            (java::LONG, "handle"),
+            (runnable.clone(), "release"),
+        ] {
+            let mut argument = Argument::new(ty, name);
+            argument.modifiers = vec![];
+            method.arguments.push(argument);
+        }
+        push!(
+            method.body,
+            "Cleanable cleanable = new Cleanable(name, handle, release);"
+        );
+        push!(method.body, "if (jdkCleaner == null) {");
+        nested!(
+            method.body,
+            "references.add(new PhantomCleanable(owner, cleanable));"
+        );
+        nested!(method.body, "return cleanable;");
+        push!(method.body, "}");
+        push!(method.body, "try {");
+        nested!(
+            method.body,
+            "jdkRegister.invoke(jdkCleaner, owner, cleanable);"
+        );
+        push!(method.body, "} catch (Exception e) {");
+        nested!(method.body, "throw new IllegalStateException(e);");
+        push!(method.body, "}");
+        push!(method.body, "return cleanable;");
+        class.methods.push(method);
+
+        let mut method = Method::new("startDaemon");
+        method.modifiers = vec![Modifier::Private, Modifier::Static];
+        method.returns = java::VOID;
+        push!(method.body, "Thread daemon = new Thread(new Runnable() {");
+        nested!(method.body, "public void run() {");
+        method.body.nested({
+            let mut body: Tokens<Java> = Tokens::new();
+            nested!(body, "drain();");
+            body.push("}");
+            body
+        });
+        push!(method.body, "}, \"", CLEANER_CLASS, "\");");
+        push!(method.body, "daemon.setDaemon(true);");
+        push!(method.body, "daemon.start();");
+        class.methods.push(method);
+
+        let mut method = Method::new("drain");
+        method.modifiers = vec![Modifier::Private, Modifier::Static];
+        method.returns = java::VOID;
+        push!(method.body, "while (true) {");
+        method.body.nested({
+            let mut body: Tokens<Java> = Tokens::new();
+            body.push("try {");
+            body.nested({
+                let mut poll: Tokens<Java> = Tokens::new();
+                poll.push("PhantomCleanable reference = (PhantomCleanable) queue.remove();");
+                poll.push("references.remove(reference);");
+                poll.push("reference.cleanable.run();");
+                poll
+            });
+            body.push("} catch (InterruptedException e) {");
+            nested!(
+                body,
+                "// Keep polling, the thread lives as long as the process."
+            );
+            body.push("}");
+            body
+        });
+        push!(method.body, "}");
+        class.methods.push(method);
+
+        let mut body: Tokens<Java> = Tokens::new();
+        body.push("static {");
+        body.nested({
+            let mut init: Tokens<Java> = Tokens::new();
+            init.push("try {");
+            init.nested({
+                let mut lookup: Tokens<Java> = Tokens::new();
+                lookup.push("Class<?> type = Class.forName(\"java.lang.ref.Cleaner\");");
+                lookup.push(
+                    "jdkRegister = type.getMethod(\"register\", Object.class, Runnable.class);",
+                );
+                lookup.push("jdkCleaner = type.getMethod(\"create\").invoke(null);");
+                lookup
+            });
+            init.push("} catch (Exception e) {");
+            nested!(init, "jdkCleaner = null;");
+            nested!(init, "startDaemon();");
+            init.push("}");
+            init
+        });
+        body.push("}");
+        body.push(cleanable_class(runnable).into_tokens());
+        body.push(phantom_class().into_tokens());
+        class.body = body;
+
+        to_java_file(self.pkg.as_ref(), class.into_tokens())
+    }
+}
+
+///
+/// The action registered for one owner, the handle is released at most once,
+/// by `clean()` when the owner is closed or by `run()` when it is collected.
+///
+fn cleanable_class<'el>(runnable: Java<'el>) -> Class<'el> {
+    let mut class = Class::new("Cleanable");
+    class.modifiers = vec![Modifier::Static, Modifier::Final];
+    class.implements.push(runnable.clone());
+
+    for (ty, name) in [
+        (java::imported("java.lang", "String"), "name"),
+        (java::LONG, "handle"),
+        (runnable, "release"),
+    ] {
+        let mut field = Field::new(ty, name);
+        field.modifiers = vec![Modifier::Private, Modifier::Final];
+        class.fields.push(field);
+    }
+    let atomic = java::imported("java.util.concurrent.atomic", "AtomicBoolean");
+    let mut released = Field::new(atomic.clone(), "released");
+    released.modifiers = vec![Modifier::Private, Modifier::Final];
+    released.initializer(toks!("new ", atomic, "(false)"));
+    class.fields.push(released);
+
+    let mut constructor = Constructor::new();
+    constructor.modifiers = vec![];
+    for (ty, name) in [
+        (java::imported("java.lang", "String"), "name"),
+        (java::LONG, "handle"),
+        (java::imported("java.lang", "Runnable"), "release"),
+    ] {
+        let mut argument = Argument::new(ty, name);
+        argument.modifiers = vec![];
+        constructor.arguments.push(argument);
+        push!(constructor.body, "this.", name, " = ", name, ";");
+    }
+    class.constructors.push(constructor);
+
+    let mut method = Method::new("clean");
+    method.modifiers = vec![];
+    method.returns = java::VOID;
+    push!(method.body, "if (released.compareAndSet(false, true)) {");
+    nested!(method.body, "release.run();");
+    push!(method.body, "}");
+    class.methods.push(method);
+
+    let mut method = Method::new("run");
+    method.modifiers = vec![Modifier::Public];
+    method.returns = java::VOID;
+    push!(method.body, "if (released.compareAndSet(false, true)) {");
+    method.body.nested({
+        let mut body: Tokens<Java> = Tokens::new();
+        body.push("if (debug.get()) {");
+        nested!(
+            body,
+            "System.err.println(\"",
+            CLEANER_CLASS,
+            ": \" + name + \" with handle \" + handle + \" is not closed, released by the GC.\");"
+        );
+        body.push("}");
+        body.push("release.run();");
+        body
+    });
+    push!(method.body, "}");
+    class.methods.push(method);
+
+    class
+}
+
+fn phantom_class<'el>() -> Class<'el> {
+    let object = java::imported("java.lang", "Object");
+    let mut class = Class::new("PhantomCleanable");
+    class.modifiers = vec![Modifier::Private, Modifier::Static, Modifier::Final];
+    class.extends = Some(
+        java::imported("java.lang.ref", "PhantomReference").with_arguments(vec![object.clone()]),
+    );
+
+    let mut field = Field::new(java::local("Cleanable"), "cleanable");
+    field.modifiers = vec![Modifier::Private, Modifier::Final];
+    class.fields.push(field);
+
+    let mut constructor = Constructor::new();
+    constructor.modifiers = vec![];
+    let mut argument = Argument::new(object, "owner");
+    argument.modifiers = vec![];
+    constructor.arguments.push(argument);
+    let mut argument = Argument::new(java::local("Cleanable"), "cleanable");
+    argument.modifiers = vec![];
+    constructor.arguments.push(argument);
+    push!(constructor.body, "super(owner, queue);");
+    push!(constructor.body, "this.cleanable = cleanable;");
+    class.constructors.push(constructor);
+
+    class
+}
+
+///
+/// The static class releasing a handle for `RustCleaner`, it must not reference the owner,
+/// or the owner would never be collected.
+///
+pub(crate) fn release_class<'el>(native_release: &str) -> Class<'el> {
+    let mut class = Class::new("Release");
+    class.modifiers = vec![Modifier::Private, Modifier::Static, Modifier::Final];
+    class
+        .implements
+        .push(java::imported("java.lang", "Runnable"));
+
+    let mut field = Field::new(java::LONG, "handle");
+    field.modifiers = vec![Modifier::Private, Modifier::Final];
+    class.fields.push(field);
+
+    let mut constructor = Constructor::new();
+    constructor.modifiers = vec![];
+    let mut argument = Argument::new(java::LONG, "handle");
+    argument.modifiers = vec![];
+    constructor.arguments.push(argument);
+    push!(constructor.body, "this.handle = handle;");
+    class.constructors.push(constructor);
+
+    let mut method = Method::new("run");
+    method.modifiers = vec![Modifier::Public];
+    method.returns = java::VOID;
+    push!(method.body, native_release.to_owned(), "(handle);");
+    class.methods.push(method);
+
+    class
+}
+
+///
+/// Register the owner being constructed with `RustCleaner`, and store the cleanable for `close()`.
+///
+pub(crate) fn register_statement<'el>(name: &str, handle: &str) -> Tokens<'el, Java<'el>> {
+    toks!(
+        "this.cleanable = ",
+        CLEANER_CLASS,
+        ".register(this, \"",
+        name.to_owned(),
+        "\", ",
+        handle.to_owned(),
+        ", new Release(",
+        handle.to_owned(),
+        "));"
+    )
+}
+
+///
+/// The field holding the cleanable registered by `register_statement`.
+///
+pub(crate) fn cleanable_field<'el>() -> Field<'el> {
+    let mut field = Field::new(
+        java::local(format!("{}.Cleanable", CLEANER_CLASS)),
+        "cleanable",
+    );
+    field.modifiers = vec![Modifier::Private];
+    field
+}
+
+///
+/// The fields counting the native calls in flight, `close()` defers the release until the last one returns.
+///
+pub(crate) fn guard_fields<'el>() -> Vec<Field<'el>> {
+    let mut calls = Field::new(java::INTEGER, "calls");
+    calls.modifiers = vec![Modifier::Private];
+    let mut closing = Field::new(java::BOOLEAN, "closing");
+    closing.modifiers = vec![Modifier::Private];
+    vec![calls, closing]
+}
+
+///
+/// `acquire()`, `release()` and `close()` of an owner guarded by `guard_fields`,
+/// the handle is only read and released while holding the monitor of the owner.
+///
+pub(crate) fn guard_methods<'el>(name: &str, handle: &str) -> Tokens<'el, Java<'el>> {
+    let release = |body: &mut Tokens<'el, Java<'el>>| {
+        push!(body, "if (calls == 0 && ", handle.to_owned(), " != 0) {");
+        nested!(body, handle.to_owned(), " = 0;");
+        nested!(body, "cleanable.clean();");
+        push!(body, "}");
+    };
+
+    // rstgen has no synchronized modifier, so the methods are written by hand.
+    let mut methods: Vec<Tokens<Java>> = vec![];
+    let mut acquire = Tokens::new();
+    push!(acquire, "private synchronized long acquire() {");
+    acquire.nested({
+        let mut body = Tokens::new();
+        push!(body, "if (closing) {");
+        nested!(
+            body,
+            "throw new IllegalStateException(\"",
+            name.to_owned(),
+            " is closed.\");"
+        );
+        push!(body, "}");
+        push!(body, "calls++;");
+        push!(body, "return ", handle.to_owned(), ";");
+        body
+    });
+    push!(acquire, "}");
+    methods.push(acquire);
+
+    let mut release_method = Tokens::new();
+    push!(release_method, "private synchronized void release() {");
+    release_method.nested({
+        let mut body = Tokens::new();
+        push!(body, "calls--;");
+        push!(body, "if (closing) {");
+        body.nested({
+            let mut free = Tokens::new();
+            release(&mut free);
+            free
+        });
+        push!(body, "}");
+        body
+    });
+    push!(release_method, "}");
+    methods.push(release_method);
+
+    let mut close = Tokens::new();
+    push!(close, "public synchronized void close() {");
+    close.nested({
+        let mut body = Tokens::new();
+        push!(body, "closing = true;");
+        release(&mut body);
+        body
+    });
+    push!(close, "}");
+    methods.push(close);
+
+    methods.into_tokens().join_line_spacing()
+}
+
+///
+/// Run the statements of a method between `acquire()` and `release()`, with the handle in a local.
+///
+pub(crate) fn guarded_body<'el>(
+    handle: &str,
+    body: Tokens<'el, Java<'el>>,
+) -> Tokens<'el, Java<'el>> {
+    let mut guarded = Tokens::new();
+    push!(guarded, "long ", handle.to_owned(), " = acquire();");
+    push!(guarded, "try {");
+    guarded.nested(body);
+    push!(guarded, "} finally {");
+    nested!(guarded, "release();");
+    push!(guarded, "}");
+    guarded
+}
+
+///
+/// The javadoc of an owner guarded by `guard_methods`.
+///
+pub(crate) fn guard_comment<'el>(name: &str) -> Tokens<'el, Java<'el>> {
+    java::BlockComment(vec![
+        format!("{} may be closed from any thread.", name).into(),
+        "A {@code close()} racing with native calls releases the native object after they return,"
+            .into(),
+        "the calls after {@code close()} throw {@code IllegalStateException}.".into(),
+    ])
+    .into_tokens()
+}
@@ -0,0 +1,90 @@
+use rstgen::java::{Class, Constructor, Field, Modifier};
+use rstgen::{java, IntoTokens, Java, Tokens};
+
+use crate::ast::contract::desc::{ConstDesc, ConstValue};
+use crate::ast::types::AstType;
+use crate::errors::*;
+use crate::java::types::{to_java_file, JavaType};
+
+pub(crate) const CONSTANTS_CLASS: &str = "RustConstants";
+
+///
+/// Generate the class holding the `pub const` values of the contracts as `public static final` fields.
+///
+pub(crate) struct ConstantsGen<'a> {
+    pub pkg: String,
+    pub consts: &'a [ConstDesc],
+}
+
+impl<'a> ConstantsGen<'a> {
+    pub(crate) fn gen(&self) -> Result<String> {
+        let mut class = Class::new(CONSTANTS_CLASS);
+        class.modifiers = vec![Modifier::Public, Modifier::Final];
+
+        for desc in self.consts.iter() {
+            let ty = Java::from(JavaType::new(desc.ty.clone()));
+            let mut field = Field::new(ty, desc.name.clone());
+            field.modifiers = vec![Modifier::Public, Modifier::Static, Modifier::Final];
+            field.initializer(quote_value(desc));
+            class.fields.push(field);
+        }
+
+        let mut constructor = Constructor::new();
+        constructor.modifiers = vec![Modifier::Private];
+        class.constructors.push(constructor);
+
+        to_java_file(self.pkg.as_ref(), class.into_tokens())
+    }
+}
+
+///
+/// The java literal of the value. Unsigned integers which are kept as the signed types of the
+/// same size are wrapped, like `Integer.toUnsignedLong` expects.
+///
+fn quote_value(desc: &ConstDesc) -> Tokens<'static, Java<'static>> {
+    match (&desc.ty, &desc.value) {
+        (AstType::Byte(_), ConstValue::Int(value)) => toks!((*value as i8).to_string()),
+        (AstType::Short(_), ConstValue::Int(value)) => toks!((*value as i16).to_string()),
+        (AstType::Int(_), ConstValue::Int(value)) => toks!((*value as i32).to_string()),
+        (AstType::Long(_), ConstValue::Int(value)) => toks!(format!("{}L", *value as i64)),
+        (AstType::BigInt(_), ConstValue::Int(value)) => toks!(
+            "new ",
+            java::imported("java.math", "BigInteger"),
+            format!("(\"{}\")", value)
+        ),
+        (AstType::Float(_), ConstValue::Float(value)) => toks!(format!("{:?}f", *value as f32)),
+        (_, ConstValue::Float(value)) => toks!(format!("{:?}", value)),
+        (_, ConstValue::Bool(value)) => toks!(value.to_string()),
+        (_, ConstValue::String(value)) => toks!(format!("\"{}\"", escape(value))),
+        (_, ConstValue::Int(value)) => toks!(value.to_string()),
+    }
+}
+
+///
+/// Escape the string for a java literal. Control and non-ASCII characters are escaped,
+/// so the generated sources don't depend on the encoding of the compiler.
+///
+fn escape(value: &str) -> String {
+    let mut escaped = String::new();
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            // Unicode escapes are translated before lexing, so line breaks must be octal.
+            c if (c as u32) < 0x20 || c == '\u{7f}' => {
+                escaped.push_str(&format!("\\{:03o}", c as u32))
+            }
+            c if c.is_ascii() => escaped.push(c),
+            c => {
+                let mut units = [0; 2];
+                for unit in c.encode_utf16(&mut units).iter() {
+                    escaped.push_str(&format!("\\u{:04x}", unit));
+                }
+            }
+        }
+    }
+    escaped
+}
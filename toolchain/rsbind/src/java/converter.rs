@@ -4,11 +4,20 @@ use rstgen::{Java, Tokens};
 use crate::ast::types::{AstBaseType, AstType};
 use crate::base::lang::{Convertible, Direction};
 use crate::java::ty::basic::{Basic, Bool};
+use crate::java::ty::buffer::Buffer;
 use crate::java::ty::callback::Callback;
+use crate::java::ty::cancellation::Cancellation;
+use crate::java::ty::enum_::Enum;
+use crate::java::ty::map::Map;
+use crate::java::ty::newtype::Newtype;
+use crate::java::ty::object::Object;
+use crate::java::ty::optional::Optional;
 use crate::java::ty::str::Str;
 use crate::java::ty::struct_::Struct;
+use crate::java::ty::tuple::Tuple;
 use crate::java::ty::vec_byte::VecByte;
 use crate::java::ty::vec_default::VecDefault;
+use crate::java::ty::vec_nested::VecNested;
 use crate::java::ty::vec_struct::VecStruct;
 use crate::java::ty::void::Void;
 
@@ -22,10 +31,19 @@ pub(crate) enum ConvertEnum {
     Bool(Bool),
     Str(Str),
     Struct(Struct),
+    Enum(Enum),
     VecByte(VecByte),
     VecDefault(VecDefault),
+    VecNested(VecNested),
     VecStruct(VecStruct),
+    Optional(Optional),
+    Map(Map),
+    Tuple(Tuple),
+    Newtype(Newtype),
     Callback(Callback),
+    Buffer(Buffer),
+    Cancellation(Cancellation),
+    Object(Object),
 }
 
 impl<'a> ConvertEnum {
@@ -39,10 +57,19 @@ impl<'a> ConvertEnum {
             ConvertEnum::Bool(c) => f(c),
             ConvertEnum::Str(c) => f(c),
             ConvertEnum::Struct(c) => f(c),
+            ConvertEnum::Enum(c) => f(c),
             ConvertEnum::VecByte(c) => f(c),
             ConvertEnum::VecDefault(c) => f(c),
+            ConvertEnum::VecNested(c) => f(c),
             ConvertEnum::VecStruct(c) => f(c),
+            ConvertEnum::Optional(c) => f(c),
+            ConvertEnum::Map(c) => f(c),
+            ConvertEnum::Tuple(c) => f(c),
+            ConvertEnum::Newtype(c) => f(c),
             ConvertEnum::Callback(c) => f(c),
+            ConvertEnum::Buffer(c) => f(c),
+            ConvertEnum::Cancellation(c) => f(c),
+            ConvertEnum::Object(c) => f(c),
         }
     }
 }
@@ -56,16 +83,30 @@ impl<'a> JavaConvert {
             | AstType::Short(_)
             | AstType::Long(_)
             | AstType::Float(_)
-            | AstType::Double(_) => ConvertEnum::Basic(Basic { ty: ty.clone() }),
+            | AstType::Double(_)
+            | AstType::BigInt(_) => ConvertEnum::Basic(Basic { ty: ty.clone() }),
             AstType::Boolean => ConvertEnum::Bool(Bool {}),
             AstType::String => ConvertEnum::Str(Str {}),
             AstType::Vec(AstBaseType::Byte(_)) => ConvertEnum::VecByte(VecByte { ty: ty.clone() }),
-            AstType::Vec(AstBaseType::Struct(_)) => {
+            AstType::Vec(AstBaseType::Struct(_)) | AstType::Vec(AstBaseType::Enum(_)) => {
                 ConvertEnum::VecStruct(VecStruct { ty: ty.clone() })
             }
+            AstType::Vec(AstBaseType::Vec(_)) | AstType::Vec(AstBaseType::Optional(_)) => {
+                ConvertEnum::VecNested(VecNested { ty: ty.clone() })
+            }
             AstType::Vec(_) => ConvertEnum::VecDefault(VecDefault { ty: ty.clone() }),
+            AstType::Optional(_) => ConvertEnum::Optional(Optional { ty: ty.clone() }),
+            AstType::Map(_, _, _) => ConvertEnum::Map(Map { ty: ty.clone() }),
+            // Errors are thrown by the bridge, so only the value on success is converted.
+            AstType::Result(_, _) => self.get_convert(&ty.ok_type()),
             AstType::Callback(_) => ConvertEnum::Callback(Callback { ty: ty.clone() }),
             AstType::Struct(_) => ConvertEnum::Struct(Struct { ty: ty.clone() }),
+            AstType::Enum(_) => ConvertEnum::Enum(Enum { ty: ty.clone() }),
+            AstType::Tuple(_) => ConvertEnum::Tuple(Tuple { ty: ty.clone() }),
+            AstType::Newtype(_, _) => ConvertEnum::Newtype(Newtype { ty: ty.clone() }),
+            AstType::Buffer(_) => ConvertEnum::Buffer(Buffer {}),
+            AstType::Cancellation(_) => ConvertEnum::Cancellation(Cancellation {}),
+            AstType::Object(_) => ConvertEnum::Object(Object { ty: ty.clone() }),
         }
     }
 
@@ -102,6 +143,14 @@ impl<'a> Convertible<Java<'a>> for JavaConvert {
         self.handle(|c| c.transferable_to_rust(origin.clone(), direction.clone()))
     }
 
+    fn transferable_to_rust_borrowed(
+        &self,
+        origin: TokenStream,
+        name: &str,
+    ) -> Option<TokenStream> {
+        self.handle(|c| c.transferable_to_rust_borrowed(origin.clone(), name))
+    }
+
     fn native_type(&self) -> Java<'a> {
         self.handle(|c| c.native_type())
     }
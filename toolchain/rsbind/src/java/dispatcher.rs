@@ -0,0 +1,79 @@
+use rstgen::java::{Argument, Class, Constructor, Field, Method, Modifier};
+use rstgen::{java, IntoTokens};
+
+use crate::errors::*;
+use crate::java::types::to_java_file;
+
+pub(crate) const DISPATCHER_CLASS: &str = "RustDispatcher";
+
+///
+/// Generate the registry of the executors running the callback methods with a `dispatch` policy.
+/// Methods whose dispatcher is not registered run inline on the rust thread.
+///
+pub(crate) struct DispatcherGen {
+    pub pkg: String,
+}
+
+impl DispatcherGen {
+    pub(crate) fn gen(&self) -> Result<String> {
+        let executor = java::imported("java.util.concurrent", "Executor");
+        let mut class = Class::new(DISPATCHER_CLASS);
+        class.modifiers = vec![Modifier::Public, Modifier::Final];
+
+        let executors_ty = java::imported("java.util.concurrent", "ConcurrentHashMap")
+            .with_arguments(vec![
+                java::imported("java.lang", "String"),
+                executor.clone(),
+            ]);
+        let mut field = Field::new(executors_ty, "executors");
+        field.initializer("new ConcurrentHashMap<>()");
+        field.modifiers = vec![Modifier::Private, Modifier::Static, Modifier::Final];
+        class.fields.push(field);
+
+        let mut constructor = Constructor::new();
+        constructor.modifiers = vec![Modifier::Private];
+        class.constructors.push(constructor);
+
+        let mut method = Method::new("register");
+        method.modifiers = vec![Modifier::Public, Modifier::Static];
+        method.returns = java::VOID;
+        method
+            .arguments
+            .push(Argument::new(java::imported("java.lang", "String"), "name"));
+        method
+            .arguments
+            .push(Argument::new(executor.clone(), "executor"));
+        push!(method.body, "executors.put(name, executor);");
+        class.methods.push(method);
+
+        let mut method = Method::new("unregister");
+        method.modifiers = vec![Modifier::Public, Modifier::Static];
+        method.returns = java::VOID;
+        method
+            .arguments
+            .push(Argument::new(java::imported("java.lang", "String"), "name"));
+        push!(method.body, "executors.remove(name);");
+        class.methods.push(method);
+
+        // Called by the callbacks with the converted arguments captured by the task.
+        let mut method = Method::new("execute");
+        method.modifiers = vec![Modifier::Static];
+        method.returns = java::VOID;
+        method
+            .arguments
+            .push(Argument::new(java::imported("java.lang", "String"), "name"));
+        method.arguments.push(Argument::new(
+            java::imported("java.lang", "Runnable"),
+            "task",
+        ));
+        push!(method.body, executor, " executor = executors.get(name);");
+        push!(method.body, "if (executor == null) {");
+        nested!(method.body, "task.run();");
+        push!(method.body, "} else {");
+        nested!(method.body, "executor.execute(task);");
+        push!(method.body, "}");
+        class.methods.push(method);
+
+        to_java_file(self.pkg.as_ref(), class.into_tokens())
+    }
+}
@@ -0,0 +1,265 @@
+use heck::ToShoutySnakeCase;
+use rstgen::java::{Argument, Class, Constructor, Enum, Field, Method, Modifier};
+use rstgen::{java, IntoTokens, Java, Tokens};
+
+use crate::ast::contract::desc::EnumDesc;
+use crate::errors::*;
+use crate::java::types::{to_java_file, JavaType};
+
+///
+/// Enums carry their discriminant in `value`, and json uses the discriminant too.
+/// Enums with data are generated as an abstract class which can only be extended by
+/// the classes of its variants, and json carries the variant name in `tag`.
+///
+pub(crate) struct EnumGen<'a> {
+    pub desc: &'a EnumDesc,
+    pub pkg: String,
+}
+
+impl<'a> EnumGen<'a> {
+    pub(crate) fn gen(&self) -> Result<String> {
+        if !self.desc.is_c_like() {
+            return self.gen_data_enum();
+        }
+
+        let name = self.desc.name.clone();
+        let mut enum_ = Enum::new(name.clone());
+        let json_adapter = java::imported("com.google.gson.annotations", "JsonAdapter");
+        enum_.annotation(toks!(
+            "@",
+            json_adapter,
+            "(",
+            name.clone(),
+            ".Adapter.class)"
+        ));
+
+        for variant in self.desc.variants.iter() {
+            enum_.variants.append(toks_f!(
+                "{}({})",
+                variant.name.to_shouty_snake_case(),
+                variant.value
+            ));
+        }
+
+        let mut field = Field::new(java::INTEGER, "value");
+        field.modifiers = vec![Modifier::Public, Modifier::Final];
+        enum_.fields.push(field);
+
+        let mut constructor = Constructor::new();
+        constructor.modifiers = vec![];
+        constructor
+            .arguments
+            .push(Argument::new(java::INTEGER, "value"));
+        push!(constructor.body, "this.value = value;");
+        enum_.constructors.push(constructor);
+
+        enum_.methods.push(self.create_from_value_fn());
+        enum_.body.push(self.create_adapter());
+
+        to_java_file(self.pkg.as_ref(), enum_.into_tokens())
+    }
+
+    fn create_from_value_fn(&self) -> Method<'static> {
+        let name = self.desc.name.clone();
+        let mut method = Method::new("fromValue");
+        method.modifiers = vec![Modifier::Public, Modifier::Static];
+        method.returns = java::local(name.clone());
+        method.arguments.push(Argument::new(java::INTEGER, "value"));
+        push_f!(method.body, "for ({} each : values()) {{", name);
+        nested!(method.body, |t| {
+            push!(t, "if (each.value == value) {");
+            nested!(t, "return each;");
+            push!(t, "}");
+        });
+        push!(method.body, "}");
+        push_f!(
+            method.body,
+            "throw new IllegalArgumentException(\"Unknown value \" + value + \" for enum {}.\");",
+            name
+        );
+        method
+    }
+
+    fn create_adapter(&self) -> Tokens<'static, Java<'static>> {
+        let name = self.desc.name.clone();
+        let io_exception = java::imported("java.io", "IOException");
+
+        let mut adapter = Class::new("Adapter");
+        adapter.modifiers = vec![Modifier::Static];
+        adapter.extends = Some(
+            java::imported("com.google.gson", "TypeAdapter")
+                .with_arguments(vec![java::local(name.clone())]),
+        );
+
+        let mut write = Method::new("write");
+        write.annotation(toks!("@Override"));
+        write.arguments.push(Argument::new(
+            java::imported("com.google.gson.stream", "JsonWriter"),
+            "out",
+        ));
+        write
+            .arguments
+            .push(Argument::new(java::local(name.clone()), "value"));
+        write.throws = Some(io_exception.clone().into_tokens());
+        push!(write.body, "out.value(value.value);");
+        adapter.methods.push(write);
+
+        let mut read = Method::new("read");
+        read.annotation(toks!("@Override"));
+        read.returns = java::local(name.clone());
+        read.arguments.push(Argument::new(
+            java::imported("com.google.gson.stream", "JsonReader"),
+            "in",
+        ));
+        read.throws = Some(io_exception.into_tokens());
+        push_f!(read.body, "return {}.fromValue(in.nextInt());", name);
+        adapter.methods.push(read);
+
+        adapter.into_tokens()
+    }
+
+    fn gen_data_enum(&self) -> Result<String> {
+        let name = self.desc.name.clone();
+        let mut class = Class::new(name.clone());
+        class.modifiers = vec![Modifier::Public, Modifier::Abstract];
+        class
+            .implements
+            .push(java::imported("java.io", "Serializable"));
+        let json_adapter = java::imported("com.google.gson.annotations", "JsonAdapter");
+        class.annotation(toks!(
+            "@",
+            json_adapter,
+            "(",
+            name.clone(),
+            ".Adapter.class)"
+        ));
+
+        // Only the variants can extend the enum.
+        let mut constructor = Constructor::new();
+        constructor.modifiers = vec![Modifier::Private];
+        class.constructors.push(constructor);
+
+        for variant in self.desc.variants.iter() {
+            let mut variant_class = Class::new(variant.name.clone());
+            variant_class.modifiers = vec![Modifier::Public, Modifier::Static, Modifier::Final];
+            variant_class.extends = Some(java::local(name.clone()));
+
+            if !variant.fields.is_empty() {
+                let mut constructor = Constructor::new();
+                for field in variant.fields.iter() {
+                    let field_ty = Java::from(JavaType::new(field.ty.clone()));
+                    let mut java_field = Field::new(field_ty.clone(), field.name.clone());
+                    java_field.modifiers = vec![Modifier::Public];
+                    variant_class.fields.push(java_field);
+
+                    constructor
+                        .arguments
+                        .push(Argument::new(field_ty, field.name.clone()));
+                    push_f!(constructor.body, "this.{} = {};", &field.name, &field.name);
+                }
+                variant_class.constructors.push(constructor);
+            }
+
+            class.body.push(variant_class.into_tokens());
+        }
+        class.body.push(self.create_data_adapter());
+
+        to_java_file(self.pkg.as_ref(), class.into_tokens())
+    }
+
+    fn create_data_adapter(&self) -> Tokens<'static, Java<'static>> {
+        let name = self.desc.name.clone();
+        let json_element = java::imported("com.google.gson", "JsonElement");
+
+        let mut adapter = Class::new("Adapter");
+        adapter.modifiers = vec![Modifier::Static];
+        adapter.implements.push(
+            java::imported("com.google.gson", "JsonSerializer")
+                .with_arguments(vec![java::local(name.clone())]),
+        );
+        adapter.implements.push(
+            java::imported("com.google.gson", "JsonDeserializer")
+                .with_arguments(vec![java::local(name.clone())]),
+        );
+
+        let mut serialize = Method::new("serialize");
+        serialize.annotation(toks!("@Override"));
+        serialize.returns = json_element.clone();
+        serialize
+            .arguments
+            .push(Argument::new(java::local(name.clone()), "src"));
+        serialize.arguments.push(Argument::new(
+            java::imported("java.lang.reflect", "Type"),
+            "typeOfSrc",
+        ));
+        serialize.arguments.push(Argument::new(
+            java::imported("com.google.gson", "JsonSerializationContext"),
+            "context",
+        ));
+        push!(
+            serialize.body,
+            java::imported("com.google.gson", "JsonObject"),
+            " json = context.serialize(src, src.getClass()).getAsJsonObject();"
+        );
+        for (index, variant) in self.desc.variants.iter().enumerate() {
+            let condition = if index == 0 { "if" } else { "} else if" };
+            push_f!(
+                serialize.body,
+                "{} (src instanceof {}) {{",
+                condition,
+                &variant.name
+            );
+            nested_f!(
+                serialize.body,
+                "json.addProperty(\"tag\", \"{}\");",
+                &variant.name
+            );
+        }
+        push!(serialize.body, "}");
+        push!(serialize.body, "return json;");
+        adapter.methods.push(serialize);
+
+        let json_parse_exception = java::imported("com.google.gson", "JsonParseException");
+        let mut deserialize = Method::new("deserialize");
+        deserialize.annotation(toks!("@Override"));
+        deserialize.returns = java::local(name.clone());
+        deserialize
+            .arguments
+            .push(Argument::new(json_element, "json"));
+        deserialize.arguments.push(Argument::new(
+            java::imported("java.lang.reflect", "Type"),
+            "typeOfT",
+        ));
+        deserialize.arguments.push(Argument::new(
+            java::imported("com.google.gson", "JsonDeserializationContext"),
+            "context",
+        ));
+        deserialize.throws = Some(json_parse_exception.clone().into_tokens());
+        push!(
+            deserialize.body,
+            "String tag = json.getAsJsonObject().get(\"tag\").getAsString();"
+        );
+        push!(deserialize.body, "switch (tag) {");
+        nested!(deserialize.body, |t| {
+            for variant in self.desc.variants.iter() {
+                push_f!(t, "case \"{}\":", &variant.name);
+                nested_f!(
+                    t,
+                    "return context.deserialize(json, {}.class);",
+                    &variant.name
+                );
+            }
+            push!(t, "default:");
+            nested!(
+                t,
+                "throw new ",
+                json_parse_exception,
+                format!("(\"Unknown tag \" + tag + \" for enum {}.\");", &name)
+            );
+        });
+        push!(deserialize.body, "}");
+        adapter.methods.push(deserialize);
+
+        adapter.into_tokens()
+    }
+}
@@ -0,0 +1,72 @@
+use rstgen::java::{Argument, Class, Constructor, Field, Modifier};
+use rstgen::{java, IntoTokens, Java};
+
+use crate::ast::types::{AstBaseType, AstType};
+use crate::errors::*;
+use crate::java::types::{exception_name, to_java_file, JavaType};
+
+///
+/// Generate the exception class thrown for the error of `Result<T, E>`.
+/// The bridge throws it with the error as message, json for structs.
+///
+pub(crate) struct ExceptionGen<'a> {
+    pub err: &'a AstBaseType,
+    pub pkg: String,
+}
+
+impl<'a> ExceptionGen<'a> {
+    pub(crate) fn gen(&self) -> Result<String> {
+        let mut class = Class::new(exception_name(self.err));
+        class.modifiers = vec![Modifier::Public];
+        class.extends = Some(java::imported("java.lang", "Exception"));
+
+        let err_ty = Java::from(JavaType::new(AstType::from(self.err.clone())));
+        let mut field = Field::new(err_ty.clone(), "error");
+        field.modifiers = vec![Modifier::Public, Modifier::Final];
+        class.fields.push(field);
+
+        let mut constructor = Constructor::new();
+        constructor.modifiers = vec![Modifier::Public];
+        let mut argument = Argument::new(java::imported("java.lang", "String"), "message");
+        argument.modifiers = vec![];
+        constructor.arguments.push(argument);
+        push!(constructor.body, "super(message);");
+        match self.err {
+            AstBaseType::String => {
+                push!(constructor.body, "this.error = message;");
+            }
+            _ => {
+                let json_cls = java::imported("com.google.gson", "Gson");
+                push!(
+                    constructor.body,
+                    "this.error = new ",
+                    json_cls,
+                    "().fromJson(message, ",
+                    self.err.origin(),
+                    ".class);"
+                );
+            }
+        }
+        class.constructors.push(constructor);
+
+        // Errors of async methods are passed to the completion callback as values.
+        if *self.err != AstBaseType::String {
+            let mut constructor = Constructor::new();
+            constructor.modifiers = vec![Modifier::Public];
+            let mut argument = Argument::new(err_ty, "error");
+            argument.modifiers = vec![];
+            constructor.arguments.push(argument);
+            let json_cls = java::imported("com.google.gson", "Gson");
+            push!(
+                constructor.body,
+                "super(new ",
+                json_cls,
+                "().toJson(error));"
+            );
+            push!(constructor.body, "this.error = error;");
+            class.constructors.push(constructor);
+        }
+
+        to_java_file(self.pkg.as_ref(), class.into_tokens())
+    }
+}
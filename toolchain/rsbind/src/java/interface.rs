@@ -17,9 +17,16 @@ impl<'a> InterfaceGen<'a> {
     pub(crate) fn gen(&self) -> Result<String> {
         let mut interface = Interface::new(self.desc.name.clone());
         interface.modifiers = vec![Modifier::Public];
+        for supertrait in self.desc.supertraits.iter() {
+            interface.extends.append(java::local(supertrait.clone()));
+        }
 
+        // Inherited methods are declared by the interfaces of the supertraits.
         let methods = self.desc.methods.clone();
-        for method in methods.into_iter() {
+        for method in methods
+            .into_iter()
+            .filter(|method| method.supertrait.is_none())
+        {
             let outer_method = self.fill_method_sig(&method)?;
             interface.methods.push(outer_method)
         }
@@ -31,7 +38,15 @@ impl<'a> InterfaceGen<'a> {
         let mut m = java::Method::new(method.name.to_lower_camel_case());
         m.modifiers = vec![];
         let return_ty = JavaType::new(method.return_type.clone());
-        m.returns = Java::from(return_ty);
+        if method.is_async {
+            // Errors of async methods complete the future exceptionally.
+            m.returns = return_ty.to_future();
+        } else {
+            m.throws = return_ty
+                .to_exception()
+                .map(|exception| exception.into_tokens());
+            m.returns = Java::from(return_ty);
+        }
 
         for arg in method.args.clone().into_iter() {
             // Add arguments
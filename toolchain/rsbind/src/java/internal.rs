@@ -29,7 +29,14 @@ impl<'a> InnerTraitGen<'a> {
             .implements
             .push(java::imported("java.io", "Serializable"));
 
-        let methods = self.desc.methods.clone();
+        // Async methods pass the completion callback to rust instead of returning the result.
+        let bridge_methods = self
+            .desc
+            .methods
+            .iter()
+            .map(|method| method.to_bridge(self.desc))
+            .collect::<Vec<MethodDesc>>();
+        let methods = bridge_methods.clone();
         for method in methods.into_iter() {
             let mut inner_method = self.fill_method_sig(&method)?;
             let mut inner_method_body: Tokens<Java> = Tokens::new();
@@ -41,7 +48,7 @@ impl<'a> InnerTraitGen<'a> {
         }
 
         let mut sel_callbacks = vec![];
-        let methods = self.desc.methods.clone();
+        let methods = bridge_methods.clone();
         for method in methods.into_iter() {
             for arg in method.args.clone().into_iter() {
                 // Select the callbacks in arguments
@@ -59,14 +66,22 @@ impl<'a> InnerTraitGen<'a> {
             }
         }
 
-        self.build_native_methods(self.desc.methods.clone(), &mut inner_class);
+        self.build_native_methods(bridge_methods, &mut inner_class);
         to_java_file(self.pkg.as_ref(), inner_class.into_tokens())
     }
 
-    fn fill_method_sig(&self, method: &MethodDesc) -> Result<Method> {
+    pub(crate) fn fill_method_sig(&self, method: &MethodDesc) -> Result<Method> {
         let mut m = java::Method::new(method.name.to_lower_camel_case());
-        m.modifiers = vec![Modifier::Static];
+        // Methods of objects are called on the instance holding the handle.
+        m.modifiers = if self.desc.is_object {
+            vec![Modifier::Public]
+        } else {
+            vec![Modifier::Static]
+        };
         let return_ty = JavaType::new(method.return_type.clone());
+        m.throws = return_ty
+            .to_exception()
+            .map(|exception| exception.into_tokens());
         m.returns = Java::from(return_ty);
 
         for arg in method.args.clone().into_iter() {
@@ -84,7 +99,11 @@ impl<'a> InnerTraitGen<'a> {
         Ok(m)
     }
 
-    fn fill_arg_convert(&self, method_body: &mut Tokens<Java>, method: &MethodDesc) -> Result<()> {
+    pub(crate) fn fill_arg_convert(
+        &self,
+        method_body: &mut Tokens<Java>,
+        method: &MethodDesc,
+    ) -> Result<()> {
         for arg in method.args.iter() {
             if let AstType::Void = arg.ty.clone() {
                 continue;
@@ -94,19 +113,19 @@ impl<'a> InnerTraitGen<'a> {
             let converted = format!("r_{}", &arg.name);
             let convert = JavaConvert { ty: arg.ty.clone() }
                 .native_to_transferable(arg.name.clone(), Direction::Down);
-            push_f!(
+            push!(
                 method_body,
-                "{} {} = {};",
-                java.into_tokens(),
-                converted,
-                convert
+                java,
+                format!(" {} = ", converted),
+                convert,
+                ";"
             );
         }
 
         Ok(())
     }
 
-    fn fill_call_native_method(
+    pub(crate) fn fill_call_native_method(
         &self,
         method_body: &mut Tokens<Java>,
         method: &MethodDesc,
@@ -114,7 +133,7 @@ impl<'a> InnerTraitGen<'a> {
         let return_ty = JavaType::new(method.return_type.clone());
 
         let return_java_ty = return_ty.to_transfer();
-        match return_ty.ast_type.clone() {
+        match return_ty.ast_type.ok_type() {
             AstType::Void => {
                 push!(
                     method_body,
@@ -134,24 +153,25 @@ impl<'a> InnerTraitGen<'a> {
             }
         }
 
-        for (index, item) in method.args.clone().into_iter().enumerate() {
-            let converted = format!("r_{}", &item.name);
-            if index == method.args.len() - 1 {
-                method_body.append(toks!(converted));
-            } else {
-                method_body.append(toks!(converted, ", "));
-            }
+        let mut converted = method
+            .args
+            .iter()
+            .map(|item| format!("r_{}", &item.name))
+            .collect::<Vec<String>>();
+        if self.desc.is_object && !method.is_static {
+            converted.insert(0, "handle".to_string());
         }
+        method_body.append(toks!(converted.join(", ")));
         method_body.append(toks!(");"));
         Ok(())
     }
 
-    fn fill_return_ty_convert(
+    pub(crate) fn fill_return_ty_convert(
         &self,
         method_body: &mut Tokens<Java>,
         method: &MethodDesc,
     ) -> Result<()> {
-        if let AstType::Void = method.return_type.clone() {
+        if let AstType::Void = method.return_type.ok_type() {
             return Ok(());
         }
 
@@ -166,20 +186,27 @@ impl<'a> InnerTraitGen<'a> {
     ///
     /// build native methods for accessing .so
     ///
-    fn build_native_methods(&self, methods: Vec<MethodDesc>, class: &mut Class) {
+    pub(crate) fn build_native_methods(&self, methods: Vec<MethodDesc>, class: &mut Class) {
         for method in methods.iter() {
             let method_name = format!("native{}", method.name.to_upper_camel_case());
             let mut m = java::Method::new(method_name);
             m.modifiers = vec![Modifier::Private, Modifier::Static, Modifier::Native];
 
-            match method.return_type.clone() {
+            let java = JavaType::new(method.return_type.clone());
+            m.throws = java.to_exception().map(|exception| exception.into_tokens());
+            match method.return_type.ok_type() {
                 AstType::Void => (),
                 _ => {
-                    let java = JavaType::new(method.return_type.clone());
                     m.returns = java.to_transfer();
                 }
             }
 
+            if self.desc.is_object && !method.is_static {
+                let mut argument = Argument::new(java::LONG, "handle");
+                argument.modifiers = vec![];
+                m.arguments.push(argument);
+            }
+
             let args = method.args.clone();
             for arg in args.iter() {
                 match arg.ty.clone() {
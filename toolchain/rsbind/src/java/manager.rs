@@ -19,7 +19,7 @@ impl<'a> ManagerGen<'a> {
         for desc in self.ast.traits.iter() {
             let descs = desc.1;
             for each in descs.iter() {
-                if !each.is_callback {
+                if each.is_service() {
                     let method_name = format!("new{}", &each.name);
                     let mut method = java::Method::new(method_name.clone());
                     method.modifiers = vec![Modifier::Public, Modifier::Static];
@@ -32,6 +32,19 @@ impl<'a> ManagerGen<'a> {
             }
         }
 
+        // Json of the callbacks implemented by rust and alive in native code, for finding leaks.
+        let string = java::imported("java.lang", "String");
+        let mut method = java::Method::new("callbackStats");
+        method.modifiers = vec![Modifier::Public, Modifier::Static];
+        method.returns = string.clone();
+        push!(method.body, "return nativeCallbackStats();");
+        class.methods.push(method);
+
+        let mut method = java::Method::new("nativeCallbackStats");
+        method.modifiers = vec![Modifier::Private, Modifier::Static, Modifier::Native];
+        method.returns = string;
+        class.methods.push(method);
+
         self.fill_global_block(&mut class)?;
 
         to_java_file(self.pkg.as_ref(), class.into_tokens())
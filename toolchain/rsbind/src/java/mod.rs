@@ -1,19 +1,32 @@
 use crate::base::bridge::{BaseBridgeGen, FilesGenerator};
 use crate::base::lang::{LangGen, LangImp, ModContext};
+use crate::errors::ErrorKind::*;
 use crate::errors::*;
 use crate::java::artifact::JavaCodeGen;
 use crate::AstResult;
-use bridge::JavaImp;
+use bridge::{quote_catch_panic, quote_result_return_convert, JavaImp};
+use rstgen::Java;
 use std::path::Path;
 mod artifact;
 mod bridge;
+mod buffer;
 mod callback;
+mod cancellation;
+mod cleaner;
+mod constants;
 mod converter;
+mod dispatcher;
+mod enum_;
+mod exception;
 mod interface;
 mod internal;
 mod manager;
+mod object;
+mod panic;
 mod struct_;
-mod ty;
+mod subscription;
+mod tuple;
+pub(crate) mod ty;
 mod types;
 mod wrapper;
 
@@ -23,24 +36,92 @@ pub(crate) struct JavaGen {
     pub(crate) namespace: String,
     pub(crate) so_name: String,
     pub(crate) ext_libs: String,
+    pub(crate) unsigned: String,
+    pub(crate) panic: String,
+    pub(crate) executor: Option<String>,
 }
 
 pub(crate) struct JavaExtra {
     pub(crate) namespace: String,
+    pub(crate) abort_on_panic: bool,
+}
+
+impl JavaGen {
+    ///
+    /// Java has no unsigned integers, they are kept as the signed types of the same size by default,
+    /// read with `Integer.toUnsignedLong` and `Long.toUnsignedString`, and widened with `unsigned = "widen"`.
+    ///
+    fn ast(&self) -> Result<AstResult> {
+        match self.unsigned.as_str() {
+            "widen" => Ok(self.ast.widen_unsigned()),
+            "keep" => Ok(self.ast.clone()),
+            unsigned => Err(GenerateError(format!(
+                "Unknown unsigned strategy {}, it should be widen or keep.",
+                unsigned
+            ))
+            .into()),
+        }
+    }
+
+    ///
+    /// Panics must not unwind into the jvm, they are thrown as `RustPanicException` by default,
+    /// or abort the process.
+    ///
+    fn abort_on_panic(&self) -> Result<bool> {
+        match self.panic.as_str() {
+            "exception" => Ok(false),
+            "abort" => Ok(true),
+            panic => Err(GenerateError(format!(
+                "Unknown panic strategy {}, it should be exception or abort.",
+                panic
+            ))
+            .into()),
+        }
+    }
 }
 
 impl LangGen for JavaGen {
     fn gen_bridge(&self, path: &Path) -> Result<()> {
+        let mut generator = FilesGenerator::<Java<'static>, JavaExtra>::default();
+        let old_return_convert = generator
+            .bridge_file_generator
+            .bridge_code_generator
+            .trait_generator
+            .trait_method_generator
+            .quote_method_return_convert;
+
+        // Result returns throw a java exception for the error.
+        generator
+            .bridge_file_generator
+            .bridge_code_generator
+            .trait_generator
+            .trait_method_generator
+            .quote_method_return_convert = Box::new(move |ctx| {
+            if ctx.method.return_type.err_type().is_some() {
+                return quote_result_return_convert(ctx);
+            }
+            (*old_return_convert)(ctx)
+        });
+
+        generator
+            .bridge_file_generator
+            .bridge_code_generator
+            .trait_generator
+            .trait_method_generator
+            .quote_method_body = Box::new(|_ctx, body| Ok(quote_catch_panic(body)));
+
         BaseBridgeGen {
             lang_name: "java".to_string(),
-            ast: &self.ast,
+            ast: &self.ast()?,
             bridge_dir: path,
             crate_name: self.crate_name.clone(),
             lang_imp: Box::new(JavaImp {}),
+            executor: self.executor.clone(),
             extra: JavaExtra {
                 namespace: self.namespace.clone(),
+                abort_on_panic: self.abort_on_panic()?,
             },
-            generator: FilesGenerator::default(),
+            generator,
         }
         .gen()
     }
@@ -48,7 +129,7 @@ impl LangGen for JavaGen {
     fn gen_native(&self, path: &Path) -> Result<()> {
         JavaCodeGen {
             java_gen_dir: &path.to_path_buf(),
-            ast: &self.ast,
+            ast: &self.ast()?.without_newtypes(),
             namespace: self.namespace.clone(),
             so_name: self.so_name.clone(),
             ext_libs: self.ext_libs.clone(),
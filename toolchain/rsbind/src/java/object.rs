@@ -0,0 +1,272 @@
+use rstgen::java::{Argument, Class, Constructor, Field, Method, Modifier};
+use rstgen::{java, IntoTokens, Java, Tokens};
+
+use crate::ast::contract::desc::{MethodDesc, SequenceKind, TraitDesc};
+use crate::ast::types::AstType;
+use crate::errors::*;
+use crate::java::cleaner::{
+    cleanable_field, guard_comment, guard_fields, guard_methods, guarded_body, register_statement,
+    release_class,
+};
+use crate::java::internal::InnerTraitGen;
+use crate::java::panic::PANIC_CLASS;
+use crate::java::subscription::SUBSCRIPTION_CLASS;
+use crate::java::types::{to_java_file, JavaType};
+
+///
+/// Generate the class for an object trait implemented by rust.
+/// The class holds the handle of the boxed trait object, which is freed by `close()` or by `RustCleaner`.
+/// Instance services are created by public constructors instead of being returned from rust.
+/// Objects pulling the items of an iterator or a stream are also a `java.util.Iterator` or a `Flow.Publisher`.
+///
+pub(crate) struct ObjectGen<'a> {
+    pub desc: &'a TraitDesc,
+    pub pkg: String,
+}
+
+impl<'a> ObjectGen<'a> {
+    pub(crate) fn gen(&self) -> Result<String> {
+        let mut class = Class::new(self.desc.name.clone());
+        class.modifiers = vec![Modifier::Public, Modifier::Final];
+        class
+            .implements
+            .push(java::imported("java.lang", "AutoCloseable"));
+
+        let mut handle = Field::new(java::LONG, "handle");
+        handle.modifiers = vec![Modifier::Private];
+        class.fields.push(handle);
+        class.fields.push(cleanable_field());
+        class.fields.extend(guard_fields());
+
+        let inner = InnerTraitGen {
+            desc: self.desc,
+            pkg: self.pkg.clone(),
+            callbacks: vec![],
+        };
+
+        if !self.desc.has_constructor() {
+            let mut constructor = Constructor::new();
+            constructor.modifiers = vec![];
+            let mut argument = Argument::new(java::LONG, "handle");
+            argument.modifiers = vec![];
+            constructor.arguments.push(argument);
+            push!(constructor.body, "this.handle = handle;");
+            constructor
+                .body
+                .push(register_statement(&self.desc.name, "handle"));
+            class.constructors.push(constructor);
+        }
+
+        // Async methods pass the completion callback to rust instead of returning the result.
+        let bridge_methods = self
+            .desc
+            .methods
+            .iter()
+            .map(|method| method.to_bridge(self.desc))
+            .collect::<Vec<MethodDesc>>();
+        for method in bridge_methods.iter() {
+            if self.desc.is_constructor(method) {
+                let sig = inner.fill_method_sig(method)?;
+                let mut constructor = Constructor::new();
+                constructor.modifiers = vec![Modifier::Public];
+                constructor.arguments = sig.arguments;
+                constructor.throws = sig.throws;
+                inner.fill_arg_convert(&mut constructor.body, method)?;
+                inner.fill_call_native_method(&mut constructor.body, method)?;
+                push!(constructor.body, "this.handle = ret;");
+                constructor
+                    .body
+                    .push(register_statement(&self.desc.name, "ret"));
+                class.constructors.push(constructor);
+                continue;
+            }
+
+            let mut m = inner.fill_method_sig(method)?;
+            let mut body: Tokens<Java> = Tokens::new();
+            inner.fill_arg_convert(&mut body, method)?;
+            inner.fill_call_native_method(&mut body, method)?;
+            inner.fill_return_ty_convert(&mut body, method)?;
+            m.body = guarded_body("handle", body);
+            class.methods.push(m);
+        }
+
+        let sequence_body = match (self.desc.sequence, self.desc.sequence_item()) {
+            (Some(SequenceKind::Iterator), Some(item)) => {
+                Some(self.fill_iterator(&mut class, item))
+            }
+            (Some(SequenceKind::Stream), Some(item)) => Some(self.fill_publisher(&mut class, item)),
+            _ => None,
+        };
+
+        inner.build_native_methods(bridge_methods, &mut class);
+
+        let mut method = Method::new("nativeFreeObject");
+        method.modifiers = vec![Modifier::Private, Modifier::Static, Modifier::Native];
+        method.returns = java::VOID;
+        let mut argument = Argument::new(java::LONG, "handle");
+        argument.modifiers = vec![];
+        method.arguments.push(argument);
+        class.methods.push(method);
+
+        let mut body: Tokens<Java> = Tokens::new();
+        body.push(guard_methods(&self.desc.name, "handle"));
+        body.push(release_class("nativeFreeObject").into_tokens());
+        if let Some(sequence_body) = sequence_body {
+            body.push(sequence_body);
+        }
+        class.body = body;
+
+        let mut file: Tokens<Java> = Tokens::new();
+        file.push(guard_comment(&self.desc.name));
+        file.push(class.into_tokens());
+        to_java_file(self.pkg.as_ref(), file)
+    }
+
+    ///
+    /// Iterators fetch the next item ahead for `hasNext()`, and are closed when the items run out.
+    /// rstgen has no synchronized modifier, so `isClosed()` is written by hand.
+    ///
+    fn fill_iterator(&self, class: &mut Class, item: AstType) -> Tokens<'static, Java<'static>> {
+        let item = JavaType::new(item).to_boxed();
+        class
+            .implements
+            .push(java::imported("java.util", "Iterator").with_arguments(vec![item.clone()]));
+
+        let mut field = Field::new(item.clone(), "nextValue");
+        field.modifiers = vec![Modifier::Private];
+        class.fields.push(field);
+        let mut field = Field::new(java::BOOLEAN, "fetched");
+        field.modifiers = vec![Modifier::Private];
+        class.fields.push(field);
+
+        let mut method = Method::new("hasNext");
+        method.modifiers = vec![Modifier::Public];
+        method.returns = java::BOOLEAN;
+        push!(method.body, "if (!fetched) {");
+        method.body.nested({
+            let mut body: Tokens<Java> = Tokens::new();
+            push!(body, "nextValue = isClosed() ? null : nextItem();");
+            push!(body, "fetched = true;");
+            push!(body, "if (nextValue == null) {");
+            nested!(body, "close();");
+            push!(body, "}");
+            body
+        });
+        push!(method.body, "}");
+        push!(method.body, "return nextValue != null;");
+        class.methods.push(method);
+
+        let mut method = Method::new("next");
+        method.modifiers = vec![Modifier::Public];
+        method.returns = item.clone();
+        push!(method.body, "if (!hasNext()) {");
+        nested!(
+            method.body,
+            "throw new ",
+            java::imported("java.util", "NoSuchElementException"),
+            "();"
+        );
+        push!(method.body, "}");
+        push!(method.body, item, " value = nextValue;");
+        push!(method.body, "nextValue = null;");
+        push!(method.body, "fetched = false;");
+        push!(method.body, "return value;");
+        class.methods.push(method);
+
+        let mut method = Method::new("remove");
+        method.modifiers = vec![Modifier::Public];
+        method.returns = java::VOID;
+        push!(
+            method.body,
+            "throw new UnsupportedOperationException(\"",
+            self.desc.name.clone(),
+            " is read only.\");"
+        );
+        class.methods.push(method);
+
+        let mut is_closed: Tokens<Java> = Tokens::new();
+        push!(is_closed, "private synchronized boolean isClosed() {");
+        nested!(is_closed, "return closing;");
+        push!(is_closed, "}");
+        is_closed
+    }
+
+    ///
+    /// Publishers pull the items for one subscriber, which is told the error if the stream was closed.
+    /// rstgen has no wildcard types, so `subscribe()` is written by hand.
+    ///
+    fn fill_publisher(&self, class: &mut Class, item: AstType) -> Tokens<'static, Java<'static>> {
+        let item = JavaType::new(item).to_boxed();
+        let subscription = toks!(SUBSCRIPTION_CLASS, "<", item.clone(), ">");
+        let completion = self
+            .desc
+            .methods
+            .first()
+            .map(|method| method.completion_name(self.desc))
+            .unwrap_or_default();
+        class.implements.push(
+            java::imported("java.util.concurrent.Flow", "Publisher")
+                .with_arguments(vec![item.clone()]),
+        );
+
+        let mut body: Tokens<Java> = Tokens::new();
+        push!(
+            body,
+            "public void subscribe(final ",
+            java::imported("java.util.concurrent.Flow", "Subscriber"),
+            "<? super ",
+            item.clone(),
+            "> subscriber) {"
+        );
+        body.nested({
+            let mut subscribe: Tokens<Java> = Tokens::new();
+            push!(
+                subscribe,
+                "final ",
+                self.desc.name.clone(),
+                " stream = this;"
+            );
+            push!(
+                subscribe,
+                "subscriber.onSubscribe(new ",
+                subscription.clone(),
+                "(subscriber) {"
+            );
+            subscribe.nested({
+                let mut methods: Tokens<Java> = Tokens::new();
+                push!(methods, "protected void pull() {");
+                methods.nested({
+                    let mut pull: Tokens<Java> = Tokens::new();
+                    push!(pull, "final ", subscription, " subscription = this;");
+                    push!(pull, "stream.nextItem(new ", completion, "() {");
+                    pull.nested({
+                        let mut complete: Tokens<Java> = Tokens::new();
+                        push!(complete, "public void onComplete(", item, " value) {");
+                        nested!(complete, "subscription.onItem(value);");
+                        push!(complete, "}");
+                        push!(complete, "public void onPanic(String message) {");
+                        nested!(
+                            complete,
+                            "subscription.fail(new ",
+                            PANIC_CLASS,
+                            "(message, null));"
+                        );
+                        push!(complete, "}");
+                        complete
+                    });
+                    push!(pull, "});");
+                    pull
+                });
+                push!(methods, "}");
+                push!(methods, "protected void release() {");
+                nested!(methods, "stream.close();");
+                push!(methods, "}");
+                methods
+            });
+            push!(subscribe, "});");
+            subscribe
+        });
+        push!(body, "}");
+        body
+    }
+}
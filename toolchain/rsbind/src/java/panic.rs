@@ -0,0 +1,41 @@
+use rstgen::java::{Argument, Class, Constructor, Field, Modifier};
+use rstgen::{java, IntoTokens};
+
+use crate::errors::*;
+use crate::java::types::to_java_file;
+
+pub(crate) const PANIC_CLASS: &str = "RustPanicException";
+
+///
+/// Generate the unchecked exception thrown by the jni functions when rust panics,
+/// with the panic message and the backtrace if `RUST_BACKTRACE` enabled capturing it.
+///
+pub(crate) struct PanicGen {
+    pub pkg: String,
+}
+
+impl PanicGen {
+    pub(crate) fn gen(&self) -> Result<String> {
+        let mut class = Class::new(PANIC_CLASS);
+        class.modifiers = vec![Modifier::Public];
+        class.extends = Some(java::imported("java.lang", "RuntimeException"));
+
+        let mut field = Field::new(java::imported("java.lang", "String"), "backtrace");
+        field.modifiers = vec![Modifier::Public, Modifier::Final];
+        class.fields.push(field);
+
+        let mut constructor = Constructor::new();
+        constructor.modifiers = vec![Modifier::Public];
+        let mut argument = Argument::new(java::imported("java.lang", "String"), "message");
+        argument.modifiers = vec![];
+        constructor.arguments.push(argument);
+        let mut argument = Argument::new(java::imported("java.lang", "String"), "backtrace");
+        argument.modifiers = vec![];
+        constructor.arguments.push(argument);
+        push!(constructor.body, "super(message);");
+        push!(constructor.body, "this.backtrace = backtrace;");
+        class.constructors.push(constructor);
+
+        to_java_file(self.pkg.as_ref(), class.into_tokens())
+    }
+}
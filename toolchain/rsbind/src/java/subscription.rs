@@ -0,0 +1,177 @@
+use rstgen::java::{Argument, Class, Constructor, Field, Method, Modifier};
+use rstgen::{java, IntoTokens, Java, Tokens};
+
+use crate::errors::*;
+use crate::java::types::to_java_file;
+
+pub(crate) const SUBSCRIPTION_CLASS: &str = "RustSubscription";
+
+///
+/// Generate the subscription of the publishers wrapping rust streams.
+/// Items are pulled from rust one at a time while the subscriber has demand, and cancelling
+/// or reaching the end of the stream closes the object holding it.
+///
+pub(crate) struct SubscriptionGen {
+    pub pkg: String,
+}
+
+impl SubscriptionGen {
+    pub(crate) fn gen(&self) -> Result<String> {
+        let item = java::local("T");
+        let subscriber = java::imported("java.util.concurrent.Flow", "Subscriber")
+            .with_arguments(vec![java::local("? super T")]);
+
+        let mut class = Class::new(SUBSCRIPTION_CLASS);
+        class.modifiers = vec![Modifier::Public, Modifier::Abstract];
+        class.parameters.append("T");
+        class
+            .implements
+            .push(java::imported("java.util.concurrent.Flow", "Subscription"));
+
+        let mut field = Field::new(subscriber.clone(), "subscriber");
+        field.modifiers = vec![Modifier::Private, Modifier::Final];
+        class.fields.push(field);
+        for name in ["pulling", "done"] {
+            let mut field = Field::new(java::BOOLEAN, name);
+            field.modifiers = vec![Modifier::Private];
+            class.fields.push(field);
+        }
+        let mut field = Field::new(java::LONG, "demand");
+        field.modifiers = vec![Modifier::Private];
+        class.fields.push(field);
+
+        let mut constructor = Constructor::new();
+        constructor.modifiers = vec![Modifier::Protected];
+        let mut argument = Argument::new(subscriber, "subscriber");
+        argument.modifiers = vec![];
+        constructor.arguments.push(argument);
+        push!(constructor.body, "this.subscriber = subscriber;");
+        class.constructors.push(constructor);
+
+        // Implemented by the publisher, `pull()` passes the next item or null to `onItem`.
+        for name in ["pull", "release"] {
+            let mut method = Method::new(name);
+            method.modifiers = vec![Modifier::Protected, Modifier::Abstract];
+            method.returns = java::VOID;
+            class.methods.push(method);
+        }
+
+        let mut method = Method::new("request");
+        method.modifiers = vec![Modifier::Public];
+        method.returns = java::VOID;
+        let mut argument = Argument::new(java::LONG, "n");
+        argument.modifiers = vec![];
+        method.arguments.push(argument);
+        push!(method.body, "if (n <= 0) {");
+        nested!(
+            method.body,
+            "fail(new IllegalArgumentException(\"The demand must be positive.\"));"
+        );
+        nested!(method.body, "return;");
+        push!(method.body, "}");
+        push!(method.body, "synchronized (this) {");
+        method.body.nested({
+            let mut body: Tokens<Java> = Tokens::new();
+            push!(
+                body,
+                "demand = demand + n < 0 ? Long.MAX_VALUE : demand + n;"
+            );
+            push!(body, "if (done || pulling) {");
+            nested!(body, "return;");
+            push!(body, "}");
+            push!(body, "pulling = true;");
+            body
+        });
+        push!(method.body, "}");
+        push!(method.body, "pullNext();");
+        class.methods.push(method);
+
+        let mut method = Method::new("cancel");
+        method.modifiers = vec![Modifier::Public];
+        method.returns = java::VOID;
+        push!(method.body, "if (finish()) {");
+        nested!(method.body, "release();");
+        push!(method.body, "}");
+        class.methods.push(method);
+
+        let mut method = Method::new("onItem");
+        method.modifiers = vec![Modifier::Protected];
+        method.returns = java::VOID;
+        let mut argument = Argument::new(item, "item");
+        argument.modifiers = vec![];
+        method.arguments.push(argument);
+        push!(method.body, "if (item == null) {");
+        method.body.nested({
+            let mut body: Tokens<Java> = Tokens::new();
+            push!(body, "if (finish()) {");
+            nested!(body, "release();");
+            nested!(body, "subscriber.onComplete();");
+            push!(body, "}");
+            push!(body, "return;");
+            body
+        });
+        push!(method.body, "}");
+        push!(method.body, "synchronized (this) {");
+        method.body.nested({
+            let mut body: Tokens<Java> = Tokens::new();
+            push!(body, "if (done) {");
+            nested!(body, "return;");
+            push!(body, "}");
+            body
+        });
+        push!(method.body, "}");
+        push!(method.body, "subscriber.onNext(item);");
+        push!(method.body, "synchronized (this) {");
+        method.body.nested({
+            let mut body: Tokens<Java> = Tokens::new();
+            push!(body, "demand--;");
+            push!(body, "pulling = !done && demand > 0;");
+            push!(body, "if (!pulling) {");
+            nested!(body, "return;");
+            push!(body, "}");
+            body
+        });
+        push!(method.body, "}");
+        push!(method.body, "pullNext();");
+        class.methods.push(method);
+
+        let mut method = Method::new("pullNext");
+        method.modifiers = vec![Modifier::Private];
+        method.returns = java::VOID;
+        push!(method.body, "try {");
+        nested!(method.body, "pull();");
+        push!(method.body, "} catch (RuntimeException e) {");
+        nested!(method.body, "fail(e);");
+        push!(method.body, "}");
+        class.methods.push(method);
+
+        let mut method = Method::new("fail");
+        method.modifiers = vec![Modifier::Protected];
+        method.returns = java::VOID;
+        let mut argument = Argument::new(java::imported("java.lang", "Throwable"), "error");
+        argument.modifiers = vec![];
+        method.arguments.push(argument);
+        push!(method.body, "if (finish()) {");
+        nested!(method.body, "release();");
+        nested!(method.body, "subscriber.onError(error);");
+        push!(method.body, "}");
+        class.methods.push(method);
+
+        // rstgen has no synchronized modifier, so `finish()` is written by hand.
+        let mut body: Tokens<Java> = Tokens::new();
+        body.push("private synchronized boolean finish() {");
+        body.nested({
+            let mut finish_body: Tokens<Java> = Tokens::new();
+            finish_body.push("if (done) {");
+            finish_body.nested("return false;");
+            finish_body.push("}");
+            finish_body.push("done = true;");
+            finish_body.push("return true;");
+            finish_body
+        });
+        body.push("}");
+        class.body = body;
+
+        to_java_file(self.pkg.as_ref(), class.into_tokens())
+    }
+}
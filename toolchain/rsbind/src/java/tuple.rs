@@ -0,0 +1,44 @@
+use rstgen::java::{Argument, Class, Constructor, Field, Modifier};
+use rstgen::{java, IntoTokens};
+
+use crate::errors::*;
+use crate::java::types::{to_java_file, tuple_name};
+
+///
+/// Generate the value class for tuples like `(i32, String)`, which becomes `Tuple2<Integer, String>`.
+/// The elements are named by their index like `_0`, the same as in json.
+///
+pub(crate) struct TupleGen {
+    pub arity: usize,
+    pub pkg: String,
+}
+
+impl TupleGen {
+    pub(crate) fn gen(&self) -> Result<String> {
+        let mut class = Class::new(tuple_name(self.arity));
+        class.modifiers = vec![Modifier::Public, Modifier::Final];
+        class
+            .implements
+            .push(java::imported("java.io", "Serializable"));
+
+        let mut constructor = Constructor::new();
+        constructor.modifiers = vec![Modifier::Public];
+        for index in 0..self.arity {
+            let param = java::local(format!("T{}", index));
+            let name = format!("_{}", index);
+            class.parameters.append(param.clone());
+
+            let mut field = Field::new(param.clone(), name.clone());
+            field.modifiers = vec![Modifier::Public, Modifier::Final];
+            class.fields.push(field);
+
+            let mut argument = Argument::new(param, name.clone());
+            argument.modifiers = vec![];
+            constructor.arguments.push(argument);
+            push!(constructor.body, "this.", name.clone(), " = ", name, ";");
+        }
+        class.constructors.push(constructor);
+
+        to_java_file(self.pkg.as_ref(), class.into_tokens())
+    }
+}
@@ -83,7 +83,11 @@ impl<'a> Convertible<Java<'a>> for Basic {
         origin: String,
         _direction: Direction,
     ) -> Tokens<'static, Java<'a>> {
-        toks_f!("{}", origin)
+        match self.ty {
+            // Only the low 64 bits are kept, they are the bits of the u64.
+            AstType::BigInt(_) => toks_f!("{}.longValue()", origin),
+            _ => toks_f!("{}", origin),
+        }
     }
 
     fn transferable_to_native(
@@ -91,7 +95,16 @@ impl<'a> Convertible<Java<'a>> for Basic {
         origin: String,
         _direction: Direction,
     ) -> Tokens<'static, Java<'a>> {
-        toks_f!("{}", origin)
+        match self.ty {
+            AstType::BigInt(_) => toks!(
+                "new ",
+                self.native_type(),
+                "(Long.toUnsignedString(",
+                origin,
+                "))"
+            ),
+            _ => toks_f!("{}", origin),
+        }
     }
 
     fn rust_to_transferable(&self, origin: TokenStream, _direction: Direction) -> TokenStream {
@@ -109,7 +122,8 @@ impl<'a> Convertible<Java<'a>> for Basic {
                 | AstType::Short(ref base)
                 | AstType::Long(ref base)
                 | AstType::Float(ref base)
-                | AstType::Double(ref base) => {
+                | AstType::Double(ref base)
+                | AstType::BigInt(ref base) => {
                     let origin_ident = ident!(base);
                     quote! {
                         #origin as #origin_ident
@@ -124,7 +138,7 @@ impl<'a> Convertible<Java<'a>> for Basic {
                     AstType::Byte(_) => quote! {Byte},
                     AstType::Int(_) => quote! {Int},
                     AstType::Short(_) => quote! {Short},
-                    AstType::Long(_) => quote! {Long},
+                    AstType::Long(_) | AstType::BigInt(_) => quote! {Long},
                     AstType::Float(_) => quote! {Float},
                     AstType::Double(_) => quote! {Double},
                     _ => quote! {},
@@ -135,7 +149,8 @@ impl<'a> Convertible<Java<'a>> for Basic {
                     | AstType::Short(ref base)
                     | AstType::Long(ref base)
                     | AstType::Float(ref base)
-                    | AstType::Double(ref base) => {
+                    | AstType::Double(ref base)
+                    | AstType::BigInt(ref base) => {
                         let base_ident = ident!(base);
                         quote! {#base_ident}
                     }
@@ -159,6 +174,7 @@ impl<'a> Convertible<Java<'a>> for Basic {
             AstType::Long(_) => java::LONG,
             AstType::Float(_) => java::FLOAT,
             AstType::Double(_) => java::DOUBLE,
+            AstType::BigInt(_) => java::imported("java.math", "BigInteger"),
             _ => java::local(""),
         }
     }
@@ -168,7 +184,7 @@ impl<'a> Convertible<Java<'a>> for Basic {
             AstType::Byte(_) => java::BYTE,
             AstType::Int(_) => java::INTEGER,
             AstType::Short(_) => java::SHORT,
-            AstType::Long(_) => java::LONG,
+            AstType::Long(_) | AstType::BigInt(_) => java::LONG,
             AstType::Float(_) => java::FLOAT,
             AstType::Double(_) => java::DOUBLE,
             _ => java::VOID,
@@ -180,7 +196,7 @@ impl<'a> Convertible<Java<'a>> for Basic {
             AstType::Byte(_) => quote!(i8),
             AstType::Short(_) => quote!(i16),
             AstType::Int(_) => quote!(i32),
-            AstType::Long(_) => quote!(i64),
+            AstType::Long(_) | AstType::BigInt(_) => quote!(i64),
             AstType::Float(_) => quote!(f32),
             AstType::Double(_) => quote!(f64),
             _ => quote! {},
@@ -205,7 +221,7 @@ pub(crate) fn basic_ty_to_tokens(ast_type: AstType) -> TokenStream {
         AstType::Byte(_) => quote!(i8),
         AstType::Short(_) => quote!(i16),
         AstType::Int(_) => quote!(i32),
-        AstType::Long(_) => quote!(i64),
+        AstType::Long(_) | AstType::BigInt(_) => quote!(i64),
         AstType::Float(_) => quote!(f32),
         AstType::Double(_) => quote!(f64),
         AstType::Boolean => quote!(u8),
@@ -0,0 +1,67 @@
+use proc_macro2::TokenStream;
+use rstgen::{java, Java, Tokens};
+
+use crate::base::lang::{Convertible, Direction};
+use crate::java::buffer::BUFFER_CLASS;
+
+///
+/// `bytes::Bytes` is boxed without copying and transferred as the handle,
+/// which is wrapped by `RustBuffer` on the java side. Buffers are only returned from rust.
+///
+pub(crate) struct Buffer {}
+
+impl<'a> Convertible<Java<'a>> for Buffer {
+    fn native_to_transferable(
+        &self,
+        origin: String,
+        _direction: Direction,
+    ) -> Tokens<'static, Java<'a>> {
+        toks_f!("{}", origin)
+    }
+
+    fn transferable_to_native(
+        &self,
+        origin: String,
+        _direction: Direction,
+    ) -> Tokens<'static, Java<'a>> {
+        toks!("new ", self.native_type(), "(", origin, ")")
+    }
+
+    fn rust_to_transferable(&self, origin: TokenStream, _direction: Direction) -> TokenStream {
+        // The concrete type is erased, so the bridge releases it without naming it.
+        quote! {
+            {
+                let buffer: Box<Box<dyn AsRef<[u8]> + Send>> = Box::new(Box::new(#origin));
+                Box::into_raw(buffer) as i64
+            }
+        }
+    }
+
+    fn transferable_to_rust(&self, _origin: TokenStream, _direction: Direction) -> TokenStream {
+        quote! {}
+    }
+
+    fn native_type(&self) -> Java<'a> {
+        java::local(BUFFER_CLASS)
+    }
+
+    fn native_transferable_type(&self, _direction: Direction) -> Java<'a> {
+        java::LONG
+    }
+
+    fn rust_transferable_type(&self, _direction: Direction) -> TokenStream {
+        quote! {i64}
+    }
+
+    fn quote_common_in_bridge(&self) -> TokenStream {
+        quote! {}
+    }
+
+    fn quote_common_in_native(&self) -> Tokens<'static, Java<'a>> {
+        Tokens::new()
+    }
+
+    fn quote_in_common_rs(&self) -> TokenStream {
+        quote! {}
+    }
+}
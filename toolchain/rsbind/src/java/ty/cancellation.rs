@@ -0,0 +1,75 @@
+use proc_macro2::TokenStream;
+use rstgen::{java, Java, Tokens};
+
+use crate::base::lang::{Convertible, Direction};
+use crate::ident;
+use crate::java::cancellation::CANCELLATION_CLASS;
+
+///
+/// `CancellationToken` is created by the java class and transferred as the handle,
+/// rust receives a clone sharing the cancellation. Tokens are only passed to rust.
+///
+pub(crate) struct Cancellation {}
+
+impl<'a> Convertible<Java<'a>> for Cancellation {
+    fn native_to_transferable(
+        &self,
+        origin: String,
+        _direction: Direction,
+    ) -> Tokens<'static, Java<'a>> {
+        toks_f!("{}.handle()", origin)
+    }
+
+    fn transferable_to_native(
+        &self,
+        _origin: String,
+        _direction: Direction,
+    ) -> Tokens<'static, Java<'a>> {
+        Tokens::new()
+    }
+
+    fn rust_to_transferable(&self, _origin: TokenStream, _direction: Direction) -> TokenStream {
+        quote! {}
+    }
+
+    fn transferable_to_rust(&self, origin: TokenStream, _direction: Direction) -> TokenStream {
+        quote! {
+            unsafe { (*(#origin as *const tokio_util::sync::CancellationToken)).clone() }
+        }
+    }
+
+    fn transferable_to_rust_borrowed(
+        &self,
+        origin: TokenStream,
+        name: &str,
+    ) -> Option<TokenStream> {
+        let name = ident!(name);
+        Some(quote! {
+            let #name: &tokio_util::sync::CancellationToken = unsafe { &*(#origin as *const tokio_util::sync::CancellationToken) };
+        })
+    }
+
+    fn native_type(&self) -> Java<'a> {
+        java::local(CANCELLATION_CLASS)
+    }
+
+    fn native_transferable_type(&self, _direction: Direction) -> Java<'a> {
+        java::LONG
+    }
+
+    fn rust_transferable_type(&self, _direction: Direction) -> TokenStream {
+        quote! {i64}
+    }
+
+    fn quote_common_in_bridge(&self) -> TokenStream {
+        quote! {}
+    }
+
+    fn quote_common_in_native(&self) -> Tokens<'static, Java<'a>> {
+        Tokens::new()
+    }
+
+    fn quote_in_common_rs(&self) -> TokenStream {
+        quote! {}
+    }
+}
@@ -0,0 +1,89 @@
+use proc_macro2::TokenStream;
+use rstgen::{java, Java, Tokens};
+
+use crate::ast::types::AstType;
+use crate::base::lang::{Convertible, Direction};
+use crate::ident;
+
+///
+/// Enums are transferred by their discriminant.
+///
+pub(crate) struct Enum {
+    pub(crate) ty: AstType,
+}
+
+impl<'a> Convertible<Java<'a>> for Enum {
+    fn native_to_transferable(
+        &self,
+        origin: String,
+        _direction: Direction,
+    ) -> Tokens<'static, Java<'a>> {
+        toks_f!("{}.value", origin)
+    }
+
+    fn transferable_to_native(
+        &self,
+        origin: String,
+        _direction: Direction,
+    ) -> Tokens<'static, Java<'a>> {
+        toks!(self.native_type(), ".fromValue(", origin, ")")
+    }
+
+    fn rust_to_transferable(&self, origin: TokenStream, _direction: Direction) -> TokenStream {
+        quote! {
+            #origin as i32
+        }
+    }
+
+    fn transferable_to_rust(&self, origin: TokenStream, direction: Direction) -> TokenStream {
+        let (enum_name, proxy_enum) = match self.ty.clone() {
+            AstType::Enum(ref base) => (
+                ident!(&base.origin),
+                ident!(&format!("Proxy{}", &base.origin)),
+            ),
+            _ => return quote! {},
+        };
+        match direction {
+            Direction::Down => {
+                quote! {
+                    #enum_name::from(#proxy_enum(#origin))
+                }
+            }
+            Direction::Up => {
+                quote! {
+                    match #origin {
+                        Ok(JValue::Int(value)) => #enum_name::from(#proxy_enum(value)),
+                        _ => panic!("Wrong enum type.")
+                    }
+                }
+            }
+        }
+    }
+
+    fn native_type(&self) -> Java<'a> {
+        match self.ty.clone() {
+            AstType::Enum(origin) => java::local(origin.origin),
+            _ => java::local(""),
+        }
+    }
+
+    fn native_transferable_type(&self, _direction: Direction) -> Java<'a> {
+        java::INTEGER
+    }
+
+    fn rust_transferable_type(&self, _direction: Direction) -> TokenStream {
+        quote! {i32}
+    }
+
+    fn quote_common_in_bridge(&self) -> TokenStream {
+        quote! {}
+    }
+
+    fn quote_common_in_native(&self) -> Tokens<'static, Java<'a>> {
+        Tokens::new()
+    }
+
+    fn quote_in_common_rs(&self) -> TokenStream {
+        quote! {}
+    }
+}
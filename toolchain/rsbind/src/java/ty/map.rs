@@ -0,0 +1,164 @@
+use proc_macro2::TokenStream;
+use rstgen::{java, Java, Tokens};
+
+use crate::ast::types::AstType;
+use crate::base::lang::{Convertible, Direction};
+use crate::java::ty::proxy::{has_proxy, quote_from_proxy, quote_into_proxy, quote_proxy_ty};
+use crate::java::types::JavaType;
+
+///
+/// Maps are transferred as json objects, keys are turned into strings by json.
+///
+pub(crate) struct Map {
+    pub(crate) ty: AstType,
+}
+
+impl Map {
+    fn type_token<'a>(&self) -> Tokens<'static, Java<'a>> {
+        let type_token = java::imported("com.google.gson.reflect", "TypeToken");
+        toks!(
+            "new ",
+            type_token,
+            "<",
+            self.native_type(),
+            ">(){}.getType()"
+        )
+    }
+}
+
+impl<'a> Convertible<Java<'a>> for Map {
+    fn native_to_transferable(
+        &self,
+        origin: String,
+        _direction: Direction,
+    ) -> Tokens<'static, Java<'a>> {
+        let json_cls = java::imported("com.google.gson", "Gson");
+        toks!(
+            "new ",
+            json_cls,
+            "().toJson(",
+            origin,
+            ", ",
+            self.type_token(),
+            ")"
+        )
+    }
+
+    fn transferable_to_native(
+        &self,
+        origin: String,
+        _direction: Direction,
+    ) -> Tokens<'static, Java<'a>> {
+        let json_cls = java::imported("com.google.gson", "Gson");
+        toks!(
+            "new ",
+            json_cls,
+            "().fromJson(",
+            origin,
+            ", ",
+            self.type_token(),
+            ")"
+        )
+    }
+
+    fn rust_to_transferable(&self, origin: TokenStream, direction: Direction) -> TokenStream {
+        let value = match self.ty.clone() {
+            AstType::Map(_, ref key, ref value) if has_proxy(key) || has_proxy(value) => {
+                let key_ty = quote_proxy_ty(key);
+                let proxy_ty = quote_proxy_ty(value);
+                let key_each = quote_into_proxy(quote! {key}, key);
+                let each = quote_into_proxy(quote! {value}, value);
+                quote! {
+                    #origin.into_iter()
+                        .map(|(key, value)| (#key_each, #each))
+                        .collect::<std::collections::HashMap<#key_ty, #proxy_ty>>()
+                }
+            }
+            _ => quote! {#origin},
+        };
+
+        match direction {
+            Direction::Down => {
+                quote! {{
+                    let json = serde_json::to_string(&#value);
+                    env.new_string(json.unwrap()).expect("Couldn't create java string").into_inner()
+                }}
+            }
+            Direction::Up => {
+                quote! {{
+                    let json = serde_json::to_string(&#value);
+                    env.new_string(json.unwrap()).expect("Couldn't create java string").into()
+                }}
+            }
+        }
+    }
+
+    fn transferable_to_rust(&self, origin: TokenStream, direction: Direction) -> TokenStream {
+        let value_get = match direction {
+            Direction::Down => {
+                quote! {}
+            }
+            Direction::Up => {
+                quote! {
+                    let #origin = match #origin {
+                        Ok(JValue::Object(value)) => JString::from(value),
+                        _ => panic!("Wrong map type.")
+                    };
+                }
+            }
+        };
+
+        match self.ty.clone() {
+            AstType::Map(_, ref key, ref value) if has_proxy(key) || has_proxy(value) => {
+                let key_ty = quote_proxy_ty(key);
+                let proxy_ty = quote_proxy_ty(value);
+                let key_each = quote_from_proxy(quote! {key}, key);
+                let each = quote_from_proxy(quote! {value}, value);
+                quote! {{
+                    #value_get
+                    let json: String = env.get_string(#origin).expect("Couldn't get java string!").into();
+                    let proxy: std::collections::HashMap<#key_ty, #proxy_ty> = serde_json::from_str(&json).unwrap();
+                    proxy.into_iter().map(|(key, value)| (#key_each, #each)).collect()
+                }}
+            }
+            _ => {
+                quote! {{
+                    #value_get
+                    let json: String = env.get_string(#origin).expect("Couldn't get java string!").into();
+                    serde_json::from_str(&json).unwrap()
+                }}
+            }
+        }
+    }
+
+    fn native_type(&self) -> Java<'a> {
+        Java::from(JavaType::new(self.ty.clone()))
+    }
+
+    fn native_transferable_type(&self, _direction: Direction) -> Java<'a> {
+        java::imported("java.lang", "String")
+    }
+
+    fn rust_transferable_type(&self, direction: Direction) -> TokenStream {
+        match direction {
+            Direction::Down => {
+                quote! {JString}
+            }
+            Direction::Up => {
+                quote! {jstring}
+            }
+        }
+    }
+
+    fn quote_common_in_bridge(&self) -> TokenStream {
+        quote! {}
+    }
+
+    fn quote_common_in_native(&self) -> Tokens<'static, Java<'a>> {
+        Tokens::new()
+    }
+
+    fn quote_in_common_rs(&self) -> TokenStream {
+        quote! {}
+    }
+}
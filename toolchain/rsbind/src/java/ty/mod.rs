@@ -1,8 +1,18 @@
 pub(crate) mod basic;
+pub(crate) mod buffer;
 pub(crate) mod callback;
+pub(crate) mod cancellation;
+pub(crate) mod enum_;
+pub(crate) mod map;
+pub(crate) mod newtype;
+pub(crate) mod object;
+pub(crate) mod optional;
+pub(crate) mod proxy;
 pub(crate) mod str;
 pub(crate) mod struct_;
+pub(crate) mod tuple;
 pub(crate) mod vec_byte;
 pub(crate) mod vec_default;
+pub(crate) mod vec_nested;
 pub(crate) mod vec_struct;
 pub(crate) mod void;
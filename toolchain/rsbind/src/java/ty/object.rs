@@ -0,0 +1,65 @@
+use proc_macro2::TokenStream;
+use rstgen::{java, Java, Tokens};
+
+use crate::ast::types::AstType;
+use crate::base::lang::{Convertible, Direction};
+
+///
+/// `Box<dyn Trait>` implemented by rust is boxed again and transferred as the handle,
+/// which is held by the generated class of the trait. Objects are only returned from rust.
+///
+pub(crate) struct Object {
+    pub(crate) ty: AstType,
+}
+
+impl<'a> Convertible<Java<'a>> for Object {
+    fn native_to_transferable(
+        &self,
+        origin: String,
+        _direction: Direction,
+    ) -> Tokens<'static, Java<'a>> {
+        toks_f!("{}", origin)
+    }
+
+    fn transferable_to_native(
+        &self,
+        origin: String,
+        _direction: Direction,
+    ) -> Tokens<'static, Java<'a>> {
+        toks!("new ", self.native_type(), "(", origin, ")")
+    }
+
+    fn rust_to_transferable(&self, origin: TokenStream, _direction: Direction) -> TokenStream {
+        quote! {
+            Box::into_raw(Box::new(#origin)) as i64
+        }
+    }
+
+    fn transferable_to_rust(&self, _origin: TokenStream, _direction: Direction) -> TokenStream {
+        quote! {}
+    }
+
+    fn native_type(&self) -> Java<'a> {
+        java::local(self.ty.origin())
+    }
+
+    fn native_transferable_type(&self, _direction: Direction) -> Java<'a> {
+        java::LONG
+    }
+
+    fn rust_transferable_type(&self, _direction: Direction) -> TokenStream {
+        quote! {i64}
+    }
+
+    fn quote_common_in_bridge(&self) -> TokenStream {
+        quote! {}
+    }
+
+    fn quote_common_in_native(&self) -> Tokens<'static, Java<'a>> {
+        Tokens::new()
+    }
+
+    fn quote_in_common_rs(&self) -> TokenStream {
+        quote! {}
+    }
+}
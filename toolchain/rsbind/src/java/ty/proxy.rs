@@ -0,0 +1,111 @@
+use proc_macro2::TokenStream;
+
+use crate::ast::types::AstBaseType;
+use crate::ident;
+
+///
+/// Structs and enums are serialized by their proxies, so are the unsigned integers kept as signed
+/// java types, which gson writes signed. Values without them inside can be serialized directly.
+///
+pub(crate) fn has_proxy(base: &AstBaseType) -> bool {
+    match base {
+        AstBaseType::Struct(_) | AstBaseType::Enum(_) => true,
+        _ if signed_proxy(base).is_some() => true,
+        AstBaseType::Vec(ref inner) | AstBaseType::Optional(ref inner) => has_proxy(inner),
+        _ => false,
+    }
+}
+
+///
+/// The signed type of the same size which java keeps an unsigned integer as, like `i8` for `u8`.
+///
+fn signed_proxy(base: &AstBaseType) -> Option<&'static str> {
+    match base {
+        AstBaseType::Byte(ref origin) if origin == "u8" => Some("i8"),
+        AstBaseType::Short(ref origin) if origin == "u16" => Some("i16"),
+        AstBaseType::Int(ref origin) if origin == "u32" => Some("i32"),
+        AstBaseType::Long(ref origin) if origin == "u64" => Some("i64"),
+        _ => None,
+    }
+}
+
+///
+/// The serializable type of a value, structs and enums inside are replaced by their proxies.
+///
+pub(crate) fn quote_proxy_ty(base: &AstBaseType) -> TokenStream {
+    match base {
+        AstBaseType::Struct(ref origin) | AstBaseType::Enum(ref origin) => {
+            let proxy_struct = ident!(&format!("Proxy{}", &origin.origin));
+            quote!(#proxy_struct)
+        }
+        AstBaseType::Vec(ref inner) => {
+            let inner_ty = quote_proxy_ty(inner);
+            quote!(Vec<#inner_ty>)
+        }
+        AstBaseType::Optional(ref inner) => {
+            let inner_ty = quote_proxy_ty(inner);
+            quote!(Option<#inner_ty>)
+        }
+        _ => {
+            let origin = signed_proxy(base).map_or_else(|| base.origin(), str::to_owned);
+            let ident = ident!(&origin);
+            quote!(#ident)
+        }
+    }
+}
+
+///
+/// Convert a value to its serializable type, going through collections recursively.
+///
+pub(crate) fn quote_into_proxy(value: TokenStream, base: &AstBaseType) -> TokenStream {
+    if !has_proxy(base) {
+        return value;
+    }
+
+    match base {
+        AstBaseType::Struct(_) | AstBaseType::Enum(_) => {
+            let proxy_ty = quote_proxy_ty(base);
+            quote! {#proxy_ty::from(#value)}
+        }
+        AstBaseType::Vec(ref inner) => {
+            let each = quote_into_proxy(quote! {each}, inner);
+            quote! {#value.into_iter().map(|each| #each).collect::<Vec<_>>()}
+        }
+        AstBaseType::Optional(ref inner) => {
+            let each = quote_into_proxy(quote! {each}, inner);
+            quote! {#value.map(|each| #each)}
+        }
+        _ => {
+            let proxy_ty = quote_proxy_ty(base);
+            quote! {#value as #proxy_ty}
+        }
+    }
+}
+
+///
+/// Convert a deserialized value back, the reverse of `quote_into_proxy`.
+///
+pub(crate) fn quote_from_proxy(value: TokenStream, base: &AstBaseType) -> TokenStream {
+    if !has_proxy(base) {
+        return value;
+    }
+
+    match base {
+        AstBaseType::Struct(ref origin) | AstBaseType::Enum(ref origin) => {
+            let origin_ty = ident!(&origin.origin);
+            quote! {#origin_ty::from(#value)}
+        }
+        AstBaseType::Vec(ref inner) => {
+            let each = quote_from_proxy(quote! {each}, inner);
+            quote! {#value.into_iter().map(|each| #each).collect::<Vec<_>>()}
+        }
+        AstBaseType::Optional(ref inner) => {
+            let each = quote_from_proxy(quote! {each}, inner);
+            quote! {#value.map(|each| #each)}
+        }
+        _ => {
+            let origin_ty = ident!(&base.origin());
+            quote! {#value as #origin_ty}
+        }
+    }
+}
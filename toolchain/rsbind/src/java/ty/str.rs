@@ -2,6 +2,7 @@ use proc_macro2::TokenStream;
 use rstgen::{java, Java, Tokens};
 
 use crate::base::lang::{Convertible, Direction};
+use crate::ident;
 
 pub(crate) struct Str {}
 
@@ -58,6 +59,20 @@ impl<'a> Convertible<Java<'a>> for Str {
         }
     }
 
+    fn transferable_to_rust_borrowed(
+        &self,
+        origin: TokenStream,
+        name: &str,
+    ) -> Option<TokenStream> {
+        // The modified utf-8 from jvm is borrowed if it is the same as utf-8.
+        let name = ident!(name);
+        Some(quote! {
+            let #name = env.get_string(#origin).expect("Couldn't get java string!");
+            let #name = std::borrow::Cow::from(&#name);
+            let #name: &str = &#name;
+        })
+    }
+
     fn native_type(&self) -> Java<'a> {
         java::imported("java.lang", "String")
     }
@@ -15,8 +15,17 @@ impl<'a> Convertible<Java<'a>> for Struct {
         origin: String,
         _direction: Direction,
     ) -> Tokens<'static, Java<'a>> {
+        // The declared class is needed to find the adapter of enums with data.
         let json_cls = java::imported("com.google.gson", "Gson");
-        toks!("new ", json_cls, "().toJson(", origin, ")")
+        toks!(
+            "new ",
+            json_cls,
+            "().toJson(",
+            origin,
+            ", ",
+            self.ty.origin(),
+            ".class)"
+        )
     }
 
     fn transferable_to_native(
@@ -0,0 +1,174 @@
+use proc_macro2::{Ident, Literal, TokenStream};
+use rstgen::{java, Java, Tokens};
+
+use crate::ast::types::{AstBaseType, AstType};
+use crate::base::lang::{Convertible, Direction};
+use crate::ident;
+use crate::java::ty::proxy::{quote_from_proxy, quote_into_proxy, quote_proxy_ty};
+use crate::java::types::JavaType;
+
+///
+/// Tuples are transferred as json objects whose keys are the indexes like `_0`,
+/// the same as the fields of the generated `Tuple2` classes.
+///
+pub(crate) struct Tuple {
+    pub(crate) ty: AstType,
+}
+
+impl Tuple {
+    fn elems(&self) -> Vec<AstBaseType> {
+        match self.ty.clone() {
+            AstType::Tuple(elems) => elems,
+            _ => vec![],
+        }
+    }
+
+    fn type_token<'a>(&self) -> Tokens<'static, Java<'a>> {
+        let type_token = java::imported("com.google.gson.reflect", "TypeToken");
+        toks!(
+            "new ",
+            type_token,
+            "<",
+            self.native_type(),
+            ">(){}.getType()"
+        )
+    }
+}
+
+impl<'a> Convertible<Java<'a>> for Tuple {
+    fn native_to_transferable(
+        &self,
+        origin: String,
+        _direction: Direction,
+    ) -> Tokens<'static, Java<'a>> {
+        let json_cls = java::imported("com.google.gson", "Gson");
+        toks!(
+            "new ",
+            json_cls,
+            "().toJson(",
+            origin,
+            ", ",
+            self.type_token(),
+            ")"
+        )
+    }
+
+    fn transferable_to_native(
+        &self,
+        origin: String,
+        _direction: Direction,
+    ) -> Tokens<'static, Java<'a>> {
+        let json_cls = java::imported("com.google.gson", "Gson");
+        toks!(
+            "new ",
+            json_cls,
+            "().fromJson(",
+            origin,
+            ", ",
+            self.type_token(),
+            ")"
+        )
+    }
+
+    fn rust_to_transferable(&self, origin: TokenStream, direction: Direction) -> TokenStream {
+        let elems = self.elems();
+        let names = (0..elems.len())
+            .map(|index| ident!(&format!("elem_{}", index)))
+            .collect::<Vec<Ident>>();
+        let keys = (0..elems.len())
+            .map(|index| Literal::string(&format!("_{}", index)))
+            .collect::<Vec<Literal>>();
+        let values = elems
+            .iter()
+            .zip(names.iter())
+            .map(|(elem, name)| quote_into_proxy(quote! {#name}, elem))
+            .collect::<Vec<TokenStream>>();
+        let json = quote! {{
+            let (#(#names,)*) = #origin;
+            serde_json::json!({#(#keys: (#values)),*}).to_string()
+        }};
+
+        match direction {
+            Direction::Down => {
+                quote! {{
+                    let json = #json;
+                    env.new_string(json).expect("Couldn't create java string").into_inner()
+                }}
+            }
+            Direction::Up => {
+                quote! {{
+                    let json = #json;
+                    env.new_string(json).expect("Couldn't create java string").into()
+                }}
+            }
+        }
+    }
+
+    fn transferable_to_rust(&self, origin: TokenStream, direction: Direction) -> TokenStream {
+        let value_get = match direction {
+            Direction::Down => {
+                quote! {}
+            }
+            Direction::Up => {
+                quote! {
+                    let #origin = match #origin {
+                        Ok(JValue::Object(value)) => JString::from(value),
+                        _ => panic!("Wrong tuple type.")
+                    };
+                }
+            }
+        };
+
+        let values = self
+            .elems()
+            .iter()
+            .enumerate()
+            .map(|(index, elem)| {
+                let key = Literal::string(&format!("_{}", index));
+                let proxy_ty = quote_proxy_ty(elem);
+                let value = quote_from_proxy(quote! {proxy}, elem);
+                quote! {{
+                    let proxy: #proxy_ty = serde_json::from_value(value[#key].take()).unwrap();
+                    #value
+                }}
+            })
+            .collect::<Vec<TokenStream>>();
+        quote! {{
+            #value_get
+            let json: String = env.get_string(#origin).expect("Couldn't get java string!").into();
+            let mut value: serde_json::Value = serde_json::from_str(&json).unwrap();
+            (#(#values,)*)
+        }}
+    }
+
+    fn native_type(&self) -> Java<'a> {
+        Java::from(JavaType::new(self.ty.clone()))
+    }
+
+    fn native_transferable_type(&self, _direction: Direction) -> Java<'a> {
+        java::imported("java.lang", "String")
+    }
+
+    fn rust_transferable_type(&self, direction: Direction) -> TokenStream {
+        match direction {
+            Direction::Down => {
+                quote! {JString}
+            }
+            Direction::Up => {
+                quote! {jstring}
+            }
+        }
+    }
+
+    fn quote_common_in_bridge(&self) -> TokenStream {
+        quote! {}
+    }
+
+    fn quote_common_in_native(&self) -> Tokens<'static, Java<'a>> {
+        Tokens::new()
+    }
+
+    fn quote_in_common_rs(&self) -> TokenStream {
+        quote! {}
+    }
+}
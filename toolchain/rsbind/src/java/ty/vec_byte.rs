@@ -3,6 +3,7 @@ use rstgen::{Java, Tokens};
 
 use crate::ast::types::{AstBaseType, AstType};
 use crate::base::lang::{Convertible, Direction};
+use crate::ident;
 use crate::java::types::JavaType;
 
 pub(crate) struct VecByte {
@@ -93,6 +94,25 @@ impl<'a> Convertible<Java<'a>> for VecByte {
         }
     }
 
+    fn transferable_to_rust_borrowed(
+        &self,
+        origin: TokenStream,
+        name: &str,
+    ) -> Option<TokenStream> {
+        let base = match self.ty {
+            AstType::Vec(AstBaseType::Byte(ref base)) => ident!(base),
+            _ => return None,
+        };
+        // The elements are pinned or copied by jvm, and released without copying back.
+        let name = ident!(name);
+        Some(quote! {
+            let #name = env.get_byte_array_elements(#origin, jni::objects::ReleaseMode::NoCopyBack).unwrap();
+            let #name: &[#base] = unsafe {
+                std::slice::from_raw_parts(#name.as_ptr() as (*const #base), #name.size().unwrap() as usize)
+            };
+        })
+    }
+
     fn native_type(&self) -> Java<'a> {
         JavaType::new(AstType::Byte("i8".to_string())).to_array()
     }
@@ -3,12 +3,22 @@ use rstgen::{java, Java, Tokens};
 
 use crate::ast::types::{AstBaseType, AstType};
 use crate::base::lang::{Convertible, Direction};
+use crate::java::ty::proxy::{has_proxy, quote_from_proxy, quote_into_proxy, quote_proxy_ty};
 use crate::java::types::JavaType;
 
 pub(crate) struct VecDefault {
     pub(crate) ty: AstType,
 }
 
+impl VecDefault {
+    fn base(&self) -> AstBaseType {
+        match self.ty.clone() {
+            AstType::Vec(base) => AstBaseType::Vec(Box::new(base)),
+            _ => AstBaseType::Void,
+        }
+    }
+}
+
 impl<'a> Convertible<Java<'a>> for VecDefault {
     fn native_to_transferable(
         &self,
@@ -33,10 +43,14 @@ impl<'a> Convertible<Java<'a>> for VecDefault {
                 AstBaseType::Long(_) => java::LONG,
                 AstBaseType::Float(_) => java::FLOAT,
                 AstBaseType::Double(_) => java::DOUBLE,
+                AstBaseType::BigInt(_) => java::imported("java.math", "BigInteger"),
                 AstBaseType::String => java::imported("java.lang", "String"),
                 AstBaseType::Void => java::VOID,
-                AstBaseType::Callback(ref origin) | AstBaseType::Struct(ref origin) => {
-                    java::local(origin.origin.clone())
+                AstBaseType::Callback(ref origin)
+                | AstBaseType::Struct(ref origin)
+                | AstBaseType::Enum(ref origin) => java::local(origin.origin.clone()),
+                AstBaseType::Vec(_) | AstBaseType::Optional(_) => {
+                    Java::from(JavaType::new(AstType::from(base.clone())))
                 }
             };
             let json = java::imported("com.google.gson", "Gson");
@@ -55,16 +69,18 @@ impl<'a> Convertible<Java<'a>> for VecDefault {
     }
 
     fn rust_to_transferable(&self, origin: TokenStream, direction: Direction) -> TokenStream {
+        let value = quote_into_proxy(origin, &self.base());
+
         match direction {
             Direction::Down => {
                 quote! {{
-                    let json = serde_json::to_string(&#origin);
+                    let json = serde_json::to_string(&#value);
                     env.new_string(json.unwrap()).expect("Couldn't create java string").into_inner()
                 }}
             }
             Direction::Up => {
                 quote! {{
-                    let json = serde_json::to_string(&#origin);
+                    let json = serde_json::to_string(&#value);
                     env.new_string(json.unwrap()).expect("Couldn't create java string").into()
                 }}
             }
@@ -85,6 +101,18 @@ impl<'a> Convertible<Java<'a>> for VecDefault {
                 }
             }
         };
+        let base = self.base();
+        if has_proxy(&base) {
+            let proxy_ty = quote_proxy_ty(&base);
+            let value = quote_from_proxy(quote! {proxy}, &base);
+            return quote! {{
+                #value_get
+                let json: String = env.get_string(#origin).expect("Couldn't get java string!").into();
+                let proxy: #proxy_ty = serde_json::from_str(&json).unwrap();
+                #value
+            }};
+        }
+
         quote! {{
             #value_get
             let json: String = env.get_string(#origin).expect("Couldn't get java string!").into();
@@ -0,0 +1,126 @@
+use proc_macro2::TokenStream;
+use rstgen::{java, Java, Tokens};
+
+use crate::ast::types::{AstBaseType, AstType};
+use crate::base::lang::{Convertible, Direction};
+use crate::java::ty::proxy::{quote_from_proxy, quote_into_proxy, quote_proxy_ty};
+use crate::java::types::JavaType;
+
+///
+/// Vectors of vectors or optionals are transferred as json arrays of arrays,
+/// like `Vec<Vec<i32>>` becomes `Integer[][]`.
+///
+pub(crate) struct VecNested {
+    pub(crate) ty: AstType,
+}
+
+impl VecNested {
+    fn base(&self) -> AstBaseType {
+        match self.ty.clone() {
+            AstType::Vec(base) => AstBaseType::Vec(Box::new(base)),
+            _ => AstBaseType::Void,
+        }
+    }
+}
+
+impl<'a> Convertible<Java<'a>> for VecNested {
+    fn native_to_transferable(
+        &self,
+        origin: String,
+        _direction: Direction,
+    ) -> Tokens<'static, Java<'a>> {
+        let json_cls = java::imported("com.google.gson", "Gson");
+        toks!("new ", json_cls, "().toJson(", origin, ")")
+    }
+
+    fn transferable_to_native(
+        &self,
+        origin: String,
+        _direction: Direction,
+    ) -> Tokens<'static, Java<'a>> {
+        let json_cls = java::imported("com.google.gson", "Gson");
+        toks!(
+            "new ",
+            json_cls,
+            "().fromJson(",
+            origin,
+            ", ",
+            self.native_type(),
+            ".class)"
+        )
+    }
+
+    fn rust_to_transferable(&self, origin: TokenStream, direction: Direction) -> TokenStream {
+        let value = quote_into_proxy(origin, &self.base());
+        match direction {
+            Direction::Down => {
+                quote! {{
+                    let json = serde_json::to_string(&#value);
+                    env.new_string(json.unwrap()).expect("Couldn't create java string").into_inner()
+                }}
+            }
+            Direction::Up => {
+                quote! {{
+                    let json = serde_json::to_string(&#value);
+                    env.new_string(json.unwrap()).expect("Couldn't create java string").into()
+                }}
+            }
+        }
+    }
+
+    fn transferable_to_rust(&self, origin: TokenStream, direction: Direction) -> TokenStream {
+        let value_get = match direction {
+            Direction::Down => {
+                quote! {}
+            }
+            Direction::Up => {
+                quote! {
+                    let #origin = match #origin {
+                        Ok(JValue::Object(value)) => JString::from(value),
+                        _ => panic!("Wrong vec nested type.")
+                    };
+                }
+            }
+        };
+
+        let proxy_ty = quote_proxy_ty(&self.base());
+        let value = quote_from_proxy(quote! {proxy}, &self.base());
+        quote! {{
+            #value_get
+            let json: String = env.get_string(#origin).expect("Couldn't get java string!").into();
+            let proxy: #proxy_ty = serde_json::from_str(&json).unwrap();
+            #value
+        }}
+    }
+
+    fn native_type(&self) -> Java<'a> {
+        Java::from(JavaType::new(self.ty.clone()))
+    }
+
+    fn native_transferable_type(&self, _direction: Direction) -> Java<'a> {
+        java::imported("java.lang", "String")
+    }
+
+    fn rust_transferable_type(&self, direction: Direction) -> TokenStream {
+        match direction {
+            Direction::Down => {
+                quote! {JString}
+            }
+            Direction::Up => {
+                quote! {jstring}
+            }
+        }
+    }
+
+    fn quote_common_in_bridge(&self) -> TokenStream {
+        quote! {}
+    }
+
+    fn quote_common_in_native(&self) -> Tokens<'static, Java<'a>> {
+        Tokens::new()
+    }
+
+    fn quote_in_common_rs(&self) -> TokenStream {
+        quote! {}
+    }
+}
@@ -26,7 +26,9 @@ impl<'a> Convertible<Java<'a>> for VecStruct {
         _direction: Direction,
     ) -> Tokens<'static, Java<'a>> {
         let json = java::imported("com.google.gson", "Gson");
-        if let AstType::Vec(AstBaseType::Struct(ref base)) = self.ty.clone() {
+        if let AstType::Vec(AstBaseType::Struct(ref base))
+        | AstType::Vec(AstBaseType::Enum(ref base)) = self.ty.clone()
+        {
             return toks!(
                 "new ",
                 json,
@@ -43,7 +45,8 @@ impl<'a> Convertible<Java<'a>> for VecStruct {
 
     fn rust_to_transferable(&self, origin: TokenStream, direction: Direction) -> TokenStream {
         match self.ty.clone() {
-            AstType::Vec(AstBaseType::Struct(ref base)) => {
+            AstType::Vec(AstBaseType::Struct(ref base))
+            | AstType::Vec(AstBaseType::Enum(ref base)) => {
                 let proxy_struct = ident!(&format!("Proxy{}", &base.origin));
                 match direction {
                     Direction::Down => {
@@ -84,7 +87,8 @@ impl<'a> Convertible<Java<'a>> for VecStruct {
         };
 
         match self.ty.clone() {
-            AstType::Vec(AstBaseType::Struct(ref base)) => {
+            AstType::Vec(AstBaseType::Struct(ref base))
+            | AstType::Vec(AstBaseType::Enum(ref base)) => {
                 let proxy_struct_name = ident!(&format!("Proxy{}", &base.origin));
                 let real_struct_name = ident!(&base.origin);
                 quote! {{
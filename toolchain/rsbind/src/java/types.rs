@@ -4,6 +4,8 @@ use rstgen::{java, Custom, Formatter, Java, Tokens};
 
 use crate::ast::types::{AstBaseType, AstType};
 use crate::errors::*;
+use crate::java::buffer::BUFFER_CLASS;
+use crate::java::cancellation::CANCELLATION_CLASS;
 
 #[derive(Clone)]
 pub(crate) struct JavaType {
@@ -25,18 +27,59 @@ impl JavaType {
         self.to_java_array(base_name, true)
     }
 
+    pub(crate) fn to_boxed(&self) -> Java<'static> {
+        Java::from(self.clone()).as_boxed()
+    }
+
+    ///
+    /// The exception thrown for the error of `Result<T, E>`.
+    ///
+    pub(crate) fn to_exception(&self) -> Option<Java<'static>> {
+        self.ast_type
+            .err_type()
+            .map(|err| java::local(exception_name(&err)))
+    }
+
+    ///
+    /// The `CompletableFuture` completed with the result of an async method.
+    ///
+    pub(crate) fn to_future(&self) -> Java<'static> {
+        let value = match self.ast_type.ok_type() {
+            AstType::Void => java::imported("java.lang", "Void"),
+            ok => JavaType::new(ok).to_boxed(),
+        };
+        java::imported("java.util.concurrent", "CompletableFuture").with_arguments(vec![value])
+    }
+
     pub(crate) fn to_transfer(&self) -> Java<'static> {
         match self.ast_type.clone() {
             AstType::Boolean => java::INTEGER,
             AstType::Vec(AstBaseType::Byte(_)) => Java::from(self.clone()),
             AstType::Vec(_) => java::imported("java.lang", "String"),
+            AstType::Optional(_) => java::imported("java.lang", "String"),
+            AstType::Map(_, _, _) => java::imported("java.lang", "String"),
+            AstType::Result(_, _) => JavaType::new(self.ast_type.ok_type()).to_transfer(),
             AstType::Struct(_) => java::imported("java.lang", "String"),
+            AstType::Tuple(_) => java::imported("java.lang", "String"),
+            AstType::Newtype(_, _) => JavaType::new(self.ast_type.unwrap_newtype()).to_transfer(),
             AstType::Callback(_) => java::LONG,
+            AstType::Enum(_) => java::INTEGER,
+            AstType::BigInt(_) => java::LONG,
+            AstType::Buffer(_) => java::LONG,
+            AstType::Cancellation(_) => java::LONG,
+            AstType::Object(_) => java::LONG,
             _ => Java::from(self.clone()),
         }
     }
 
     pub(crate) fn to_java_array(&self, java: Java<'static>, boxed: bool) -> Java<'static> {
+        // Arrays of imported classes stay imported, `to_java_file` drops the `[]` from the import.
+        if let Java::Class(_) = java {
+            if let (Some(package), Some(&[])) = (java.package(), java.arguments()) {
+                return java::imported(package, format!("{}[]", java.name()));
+            }
+        }
+
         let mut base_str = String::new();
         {
             let mut formatter = Formatter::new(&mut base_str);
@@ -47,6 +90,30 @@ impl JavaType {
         let _ = base_str.write_str("[]");
         java::local(base_str)
     }
+
+    ///
+    /// The tuple class like `Tuple2<Integer, String>`, elements are boxed as generic arguments.
+    ///
+    pub(crate) fn to_tuple(elems: &[AstBaseType]) -> Java<'static> {
+        let elem_strs = elems
+            .iter()
+            .map(|elem| {
+                let mut elem_str = String::new();
+                {
+                    let mut formatter = Formatter::new(&mut elem_str);
+                    let mut extra = java::Extra::default();
+                    let java = Java::from(JavaType::new(AstType::from(elem.clone())));
+                    let _ = java.format(&mut formatter, &mut extra, 1);
+                }
+                elem_str
+            })
+            .collect::<Vec<String>>();
+        java::local(format!(
+            "{}<{}>",
+            tuple_name(elems.len()),
+            elem_strs.join(", ")
+        ))
+    }
 }
 
 impl From<JavaType> for Java<'static> {
@@ -59,21 +126,53 @@ impl From<JavaType> for Java<'static> {
             AstType::Long(_) => java::LONG,
             AstType::Float(_) => java::FLOAT,
             AstType::Double(_) => java::DOUBLE,
+            AstType::BigInt(_) => java::imported("java.math", "BigInteger"),
             AstType::String => java::imported("java.lang", "String"),
             AstType::Vec(ref base) => match base {
-                AstBaseType::Struct(_sub) => JavaType::new(AstType::from(base.clone())).to_array(),
+                AstBaseType::Struct(_sub) | AstBaseType::Enum(_sub) => {
+                    JavaType::new(AstType::from(base.clone())).to_array()
+                }
                 // Byte array is not transferred by json, so we don't use boxed array.
                 AstBaseType::Byte(_) => JavaType::new(AstType::from(base.clone())).to_array(),
                 // Why we use boxed array, because we use json to transfer array,
                 // and it is translated to list, and then we need to change it to array(boxed).
                 _ => JavaType::new(AstType::from(base.clone())).to_boxed_array(),
             },
+            // Optional values are nullable, so primitives must be boxed.
+            AstType::Optional(ref base) => JavaType::new(AstType::from(base.clone())).to_boxed(),
+            // Keys and values in a map must be boxed.
+            AstType::Map(_, ref key, ref value) => java::imported("java.util", "Map")
+                .with_arguments(vec![
+                    JavaType::new(AstType::from(key.clone())).to_boxed(),
+                    JavaType::new(AstType::from(value.clone())).to_boxed(),
+                ]),
+            AstType::Result(_, _) => Java::from(JavaType::new(item.ast_type.ok_type())),
             AstType::Void => java::VOID,
-            AstType::Callback(origin) | AstType::Struct(origin) => java::local(origin.origin),
+            AstType::Tuple(ref elems) => JavaType::to_tuple(elems),
+            AstType::Newtype(_, ref inner) => {
+                Java::from(JavaType::new(AstType::from(inner.clone())))
+            }
+            AstType::Callback(origin)
+            | AstType::Struct(origin)
+            | AstType::Enum(origin)
+            | AstType::Object(origin) => java::local(origin.origin),
+            AstType::Buffer(_) => java::local(BUFFER_CLASS),
+            AstType::Cancellation(_) => java::local(CANCELLATION_CLASS),
         }
     }
 }
 
+pub(crate) fn exception_name(err: &AstBaseType) -> String {
+    match err {
+        AstBaseType::String => "RustException".to_string(),
+        _ => format!("{}Exception", err.origin()),
+    }
+}
+
+pub(crate) fn tuple_name(arity: usize) -> String {
+    format!("Tuple{}", arity)
+}
+
 pub(crate) fn to_java_file(pkg: &str, tokens: Tokens<Java>) -> Result<String> {
     let mut buf = String::new();
     {
@@ -82,5 +181,30 @@ pub(crate) fn to_java_file(pkg: &str, tokens: Tokens<Java>) -> Result<String> {
         extra.package(pkg.as_ref());
         java::Java::write_file(tokens, &mut formatter, &mut extra, 0)?;
     }
-    Ok(buf)
+    Ok(strip_array_imports(&buf))
+}
+
+///
+/// Turn the imports of array classes like `import java.math.BigInteger[];` into the imports of their elements.
+///
+fn strip_array_imports(file: &str) -> String {
+    let mut imports = vec![];
+    let mut lines = vec![];
+    for line in file.lines() {
+        if line.starts_with("import ") {
+            let import = line.replace("[]", "");
+            if imports.contains(&import) {
+                continue;
+            }
+            imports.push(import.clone());
+            lines.push(import);
+        } else {
+            lines.push(line.to_owned());
+        }
+    }
+    let mut result = lines.join("\n");
+    if file.ends_with('\n') {
+        result.push('\n');
+    }
+    result
 }
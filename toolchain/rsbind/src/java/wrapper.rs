@@ -5,7 +5,8 @@ use rstgen::{java, IntoTokens, Java, Tokens};
 use crate::ast::contract::desc::{MethodDesc, TraitDesc};
 use crate::ast::types::AstType;
 use crate::errors::*;
-use crate::java::types::{to_java_file, JavaType};
+use crate::java::panic::PANIC_CLASS;
+use crate::java::types::{exception_name, to_java_file, JavaType};
 
 pub(crate) struct WrapperGen<'a> {
     pub desc: &'a TraitDesc,
@@ -43,7 +44,15 @@ impl<'a> WrapperGen<'a> {
         let mut m = java::Method::new(method.name.to_lower_camel_case());
         m.modifiers = vec![Modifier::Public];
         let return_ty = JavaType::new(method.return_type.clone());
-        m.returns = Java::from(return_ty);
+        if method.is_async {
+            // Errors of async methods complete the future exceptionally.
+            m.returns = return_ty.to_future();
+        } else {
+            m.throws = return_ty
+                .to_exception()
+                .map(|exception| exception.into_tokens());
+            m.returns = Java::from(return_ty);
+        }
 
         for arg in method.args.clone().into_iter() {
             // Add arguments
@@ -66,7 +75,11 @@ impl<'a> WrapperGen<'a> {
         method_body: &mut Tokens<Java>,
         method: &MethodDesc,
     ) -> Result<()> {
-        match method.return_type.clone() {
+        if method.is_async {
+            return self.fill_async_method_body(inner_cls_name, method_body, method);
+        }
+
+        match method.return_type.ok_type() {
             AstType::Void => {
                 push_f!(
                     method_body,
@@ -95,4 +108,119 @@ impl<'a> WrapperGen<'a> {
         method_body.append(toks!(");"));
         Ok(())
     }
+
+    ///
+    /// Async methods return a future, which is completed by the completion callback passed to rust.
+    ///
+    fn fill_async_method_body(
+        &self,
+        inner_cls_name: String,
+        method_body: &mut Tokens<Java>,
+        method: &MethodDesc,
+    ) -> Result<()> {
+        let future = JavaType::new(method.return_type.clone()).to_future();
+        let tokens = method
+            .args
+            .iter()
+            .filter(|arg| matches!(arg.ty, AstType::Cancellation(_)))
+            .collect::<Vec<_>>();
+        if tokens.is_empty() {
+            push!(
+                method_body,
+                "final ",
+                future.clone(),
+                " resultFuture = new ",
+                future,
+                "();"
+            );
+        } else {
+            // Cancelling the future cancels the tokens passed to the rust future, after the future
+            // is cancelled so the result completed by rust is dropped.
+            push!(
+                method_body,
+                "final ",
+                future.clone(),
+                " resultFuture = new ",
+                future,
+                "() {"
+            );
+            method_body.nested({
+                let mut cancel: Tokens<Java> = Tokens::new();
+                push!(cancel, "@Override");
+                push!(
+                    cancel,
+                    "public boolean cancel(boolean mayInterruptIfRunning) {"
+                );
+                nested!(
+                    cancel,
+                    "boolean cancelled = super.cancel(mayInterruptIfRunning);"
+                );
+                for arg in tokens.iter() {
+                    nested_f!(cancel, "{}.cancel();", &arg.name);
+                }
+                nested!(cancel, "return cancelled;");
+                push!(cancel, "}");
+                cancel
+            });
+            push!(method_body, "};");
+        }
+
+        let args = method
+            .args
+            .iter()
+            .map(|arg| format!("{}, ", &arg.name))
+            .collect::<String>();
+        push_f!(
+            method_body,
+            "{}.{}({}new {}() {{",
+            inner_cls_name,
+            method.name.to_lower_camel_case(),
+            args,
+            method.completion_name(self.desc)
+        );
+
+        let mut completion: Tokens<Java> = Tokens::new();
+        let ok_type = method.return_type.ok_type();
+        if ok_type == AstType::Void {
+            push!(completion, "public void onComplete() {");
+            nested!(completion, "resultFuture.complete(null);");
+        } else {
+            push!(
+                completion,
+                "public void onComplete(",
+                Java::from(JavaType::new(ok_type)),
+                " value) {"
+            );
+            nested!(completion, "resultFuture.complete(value);");
+        }
+        push!(completion, "}");
+
+        if let Some(err) = method.return_type.err_type() {
+            push!(
+                completion,
+                "public void onError(",
+                Java::from(JavaType::new(AstType::from(err.clone()))),
+                " error) {"
+            );
+            nested!(
+                completion,
+                "resultFuture.completeExceptionally(new ",
+                exception_name(&err),
+                "(error));"
+            );
+            push!(completion, "}");
+        }
+        push!(completion, "public void onPanic(String message) {");
+        nested!(
+            completion,
+            "resultFuture.completeExceptionally(new ",
+            PANIC_CLASS,
+            "(message, null));"
+        );
+        push!(completion, "}");
+        method_body.nested(completion);
+        push!(method_body, "});");
+        push!(method_body, "return resultFuture;");
+        Ok(())
+    }
 }
@@ -199,6 +199,8 @@ impl Bind {
                 let ast_result = AstResult {
                     traits: HashMap::new(),
                     structs: HashMap::new(),
+                    enums: HashMap::new(),
+                    consts: HashMap::new(),
                     imps: vec![],
                 };
                 Ok(ast_result)
@@ -237,6 +239,7 @@ impl Bind {
             crate_name,
             ast_result,
             jar,
+            config.as_ref().and_then(|config| config.executor()),
         );
 
         match self.action {
@@ -282,6 +285,7 @@ impl Bind {
             crate_name,
             ast_result,
             mac,
+            config.as_ref().and_then(|config| config.executor()),
         );
 
         match self.action {
@@ -329,6 +333,7 @@ impl Bind {
             crate_name,
             ast_result,
             ios,
+            config.as_ref().and_then(|config| config.executor()),
         );
 
         match self.action {
@@ -376,6 +381,7 @@ impl Bind {
             crate_name,
             ast_result,
             android,
+            config.as_ref().and_then(|config| config.executor()),
         );
 
         match self.action {
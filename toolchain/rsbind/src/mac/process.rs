@@ -12,7 +12,7 @@ use crate::base::process::BuildProcess;
 use crate::bridge::prj::Unpack;
 use crate::errors::ErrorKind::*;
 use crate::errors::*;
-use crate::swift::SwiftGen;
+use crate::swift::{SwiftGen, C_HEADER};
 use crate::unzip;
 
 use super::config::Mac;
@@ -25,9 +25,11 @@ pub(crate) struct MacProcess<'a> {
     host_crate_name: &'a str,
     ast_result: &'a AstResult,
     config: Option<Mac>,
+    executor: Option<String>,
 }
 
 impl<'a> MacProcess<'a> {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         origin_prj_path: &'a Path,
         dest_prj_path: &'a Path,
@@ -36,6 +38,7 @@ impl<'a> MacProcess<'a> {
         host_crate_name: &'a str,
         ast_result: &'a AstResult,
         config: Option<Mac>,
+        executor: Option<String>,
     ) -> Self {
         MacProcess {
             origin_prj_path,
@@ -45,6 +48,7 @@ impl<'a> MacProcess<'a> {
             host_crate_name,
             ast_result,
             config,
+            executor,
         }
     }
 }
@@ -59,6 +63,7 @@ impl<'a> MacProcess<'a> {
         let output_file = self.header_path.join("ffi.h").display().to_string();
 
         let config = Config {
+            header: Some(String::from(C_HEADER)),
             namespace: Some(String::from("ffi")),
             language: Language::C,
             ..Default::default()
@@ -99,6 +104,8 @@ impl<'a> BuildProcess for MacProcess<'a> {
             host_crate: self.host_crate_name,
             buf,
             features: &self.config().features(),
+            has_stream: self.ast_result.has_stream(),
+            has_cancellation: self.ast_result.has_cancellation(),
         };
 
         unpack.unpack()?;
@@ -108,6 +115,7 @@ impl<'a> BuildProcess for MacProcess<'a> {
         SwiftGen {
             crate_name: self.host_crate_name.to_string(),
             ast: self.ast_result.clone(),
+            executor: self.executor.clone(),
         }
         .gen_bridge(&bridge_c_src_path)?;
 
@@ -216,6 +224,7 @@ impl<'a> BuildProcess for MacProcess<'a> {
         SwiftGen {
             crate_name: self.host_crate_name.to_string(),
             ast: self.ast_result.clone(),
+            executor: self.executor.clone(),
         }
         .gen_native(&swift_gen_path)?;
 
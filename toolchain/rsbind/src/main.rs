@@ -62,7 +62,8 @@ fn main() {
         }
     };
 
-    gen::Bind::from(path, target_enum, action_enum)
-        .gen_all()
-        .expect("generate failed");
+    if let Err(err) = gen::Bind::from(path, target_enum, action_enum).gen_all() {
+        eprintln!("generate failed: {}", err);
+        process::exit(1);
+    }
 }
@@ -2,8 +2,15 @@ use crate::ast::AstResult;
 use crate::base::artifact::{NativeCodeGen, NativeGenStrategy};
 use crate::errors::*;
 use crate::swift::callback::{CallbackGen, InternalCallbackGen};
+use crate::swift::cancellation::{CancellationGen, CANCELLATION_CLASS};
+use crate::swift::constants::{ConstantsGen, CONSTANTS_CLASS};
+use crate::swift::dispatcher::{DispatcherGen, DISPATCHER_CLASS};
+use crate::swift::enum_::EnumGen;
+use crate::swift::error::ErrorGen;
 use crate::swift::internal::TraitGen;
 use crate::swift::manager::ManagerGen;
+use crate::swift::object::ObjectGen;
+use crate::swift::panic::{PanicGen, PANIC_ERROR};
 use crate::swift::protocol::ProtocolGen;
 use crate::swift::struct_::StructGen;
 use crate::swift::wrapper::WrapperGen;
@@ -34,7 +41,31 @@ impl<'a> SwiftCodeGen<'a> {
             }),
             gen_wrapper_trait: Box::new(|_ctx, desc| WrapperGen { desc }.gen()),
             gen_trait: Box::new(|_ctx, desc| ProtocolGen { desc }.gen()),
+            gen_object: Box::new(|_ctx, desc| ObjectGen { desc }.gen()),
             gen_struct: Box::new(|_ctx, desc| StructGen { desc }.gen()),
+            gen_enum: Box::new(|_ctx, desc| EnumGen { desc }.gen()),
+            gen_error: Box::new(|_ctx, err| ErrorGen { err }.gen()),
+            // Tuples are native types in swift.
+            gen_tuple: None,
+            gen_buffer: None,
+            gen_subscription: None,
+            gen_cancellation: Box::new(|_ctx| {
+                let content = CancellationGen {}.gen()?;
+                Ok((CANCELLATION_CLASS.to_owned(), content))
+            }),
+            gen_dispatcher: Box::new(|_ctx| {
+                let content = DispatcherGen {}.gen()?;
+                Ok((DISPATCHER_CLASS.to_owned(), content))
+            }),
+            gen_panic: Some(Box::new(|_ctx| {
+                let content = PanicGen {}.gen()?;
+                Ok((PANIC_ERROR.to_owned(), content))
+            })),
+            gen_cleaner: None,
+            gen_constants: Box::new(|_ctx, consts| {
+                let content = ConstantsGen { consts }.gen()?;
+                Ok((CONSTANTS_CLASS.to_owned(), content))
+            }),
             gen_manager: Box::new(|ctx| ManagerGen { ast: ctx.ast }.gen()),
         };
 
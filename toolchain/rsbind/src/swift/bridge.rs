@@ -1,22 +1,233 @@
 use crate::ast::contract::desc::*;
 use crate::ast::types::*;
+use crate::base::bridge::{
+    quote_base_ty, quote_callback_impls, quote_enum_proxy_convert, quote_object_ty,
+    quote_struct_members, quote_tuple_ty, quote_variant_pattern, quote_variant_value,
+};
 use crate::base::lang::{
-    BridgeContext, CallbackContext, Convertible, Direction, LangImp, MethodContext, ModContext,
-    ServiceContext, StructContext,
+    BridgeContext, CallbackContext, Convertible, Direction, EnumContext, LangImp, MethodContext,
+    ModContext, ServiceContext, StructContext,
 };
 use crate::errors::*;
 use crate::ident;
 use crate::swift::converter::SwiftConvert;
+use heck::ToSnakeCase;
 use proc_macro2::{Ident, TokenStream};
 use rstgen::swift::Swift;
 
-fn c_pointers_to_callback_convert(callback_desc: &TraitDesc) -> Result<TokenStream> {
+///
+/// Enums with data are transferred as a struct holding the tag of the variant and a pointer
+/// to the fields of each variant, only the pointer of the current variant is not null.
+/// The receiver takes the fields and releases the pointer by `free_ptr`.
+///
+fn quote_data_enum_proxy(enum_: &EnumDesc) -> TokenStream {
+    let proxy_enum_str = format!("Proxy{}", &enum_.name);
+    let proxy_enum_name = ident!(&proxy_enum_str);
+    let origin_enum_name = ident!(&enum_.name);
+    let free_payload_fn = ident!(&format!("free_{}_payload", &proxy_enum_str));
+    let unknown_msg = format!("Unknown tag {{}} for enum {}.", &enum_.name);
+
+    let mut payload_structs = vec![];
+    let mut payload_names = vec![];
+    let mut payload_tys = vec![];
+    let mut origin_to_proxy = vec![];
+    let mut proxy_to_origin = vec![];
+    let mut free_payloads = vec![];
+    for variant in enum_.variants.iter() {
+        let tag = variant.value;
+        let origin_pattern = quote_variant_pattern(enum_, variant);
+        if variant.fields.is_empty() {
+            origin_to_proxy.push(quote! {
+                #origin_pattern => {
+                    proxy.tag = #tag;
+                }
+            });
+            proxy_to_origin.push(quote! {#tag => #origin_pattern});
+            continue;
+        }
+
+        let payload_name = ident!(&variant.name.to_snake_case());
+        let payload_ty = ident!(&format!("{}{}", &proxy_enum_str, &variant.name));
+        let names = variant
+            .fields
+            .iter()
+            .map(|field| ident!(&field.name))
+            .collect::<Vec<Ident>>();
+        let tys = variant
+            .fields
+            .iter()
+            .map(|field| {
+                SwiftConvert {
+                    ty: field.ty.clone(),
+                }
+                .rust_transferable_type(Direction::Down)
+            })
+            .collect::<Vec<TokenStream>>();
+        let to_proxy_converts = variant
+            .fields
+            .iter()
+            .map(|field| {
+                let name = ident!(&field.name);
+                SwiftConvert {
+                    ty: field.ty.clone(),
+                }
+                .rust_to_transferable(quote! {#name}, Direction::Down)
+            })
+            .collect::<Vec<TokenStream>>();
+        let to_origin_converts = variant
+            .fields
+            .iter()
+            .map(|field| {
+                let name = ident!(&field.name);
+                SwiftConvert {
+                    ty: field.ty.clone(),
+                }
+                .transferable_to_rust(quote! {payload.#name}, Direction::Down)
+            })
+            .collect::<Vec<TokenStream>>();
+        let origin_value = quote_variant_value(enum_, variant, &to_origin_converts);
+
+        payload_structs.push(quote! {
+            #[repr(C)]
+            #[derive(Clone)]
+            pub struct #payload_ty {
+                #(pub #names: #tys),*
+            }
+        });
+        origin_to_proxy.push(quote! {
+            #origin_pattern => {
+                proxy.tag = #tag;
+                proxy.#payload_name = Box::into_raw(Box::new(#payload_ty {
+                    #(#names: #to_proxy_converts),*
+                }));
+            }
+        });
+        proxy_to_origin.push(quote! {
+            #tag => {
+                let payload = unsafe { std::ptr::read(proxy.#payload_name) };
+                (proxy.free_ptr)(proxy.tag, proxy.#payload_name as *mut std::os::raw::c_void);
+                #origin_value
+            }
+        });
+        free_payloads.push(quote! {
+            #tag => {
+                drop(Box::from_raw(ptr as *mut std::mem::MaybeUninit<#payload_ty>));
+            }
+        });
+        payload_names.push(payload_name);
+        payload_tys.push(payload_ty);
+    }
+
+    let proxy_array = quote_proxy_array(&enum_.name);
+
+    quote! {
+        #(#payload_structs)*
+
+        #[repr(C)]
+        #[derive(Clone)]
+        pub struct #proxy_enum_name {
+            pub tag: i32,
+            #(pub #payload_names: *mut #payload_tys,)*
+            pub free_ptr: extern "C" fn(i32, *mut std::os::raw::c_void),
+        }
+
+        impl From<#origin_enum_name> for #proxy_enum_name {
+            fn from(origin: #origin_enum_name) -> Self {
+                let mut proxy = #proxy_enum_name {
+                    tag: 0,
+                    #(#payload_names: std::ptr::null_mut(),)*
+                    free_ptr: #free_payload_fn,
+                };
+                match origin {
+                    #(#origin_to_proxy)*
+                }
+                proxy
+            }
+        }
+
+        impl From<#proxy_enum_name> for #origin_enum_name {
+            fn from(proxy: #proxy_enum_name) -> Self {
+                match proxy.tag {
+                    #(#proxy_to_origin,)*
+                    _ => panic!(#unknown_msg, proxy.tag),
+                }
+            }
+        }
+
+        extern "C" fn #free_payload_fn(tag: i32, ptr: *mut std::os::raw::c_void) {
+            // The fields are taken by the receiver, so only the memory is released.
+            unsafe {
+                match tag {
+                    #(#free_payloads)*
+                    _ => {}
+                }
+            }
+        }
+
+        #proxy_array
+    }
+}
+
+///
+/// The array of a proxy struct, and the function releasing it.
+///
+fn quote_proxy_array(name: &str) -> TokenStream {
+    let proxy_struct_name = ident!(&format!("Proxy{}", name));
+    let struct_array_str = format!("C{}Array", name);
+    let struct_array_name = ident!(&struct_array_str);
+    let free_proxy_struct_array_fn = ident!(&format!("free_{}", &struct_array_str));
+
+    quote! {
+        #[repr(C)]
+        #[derive(Clone)]
+        pub struct #struct_array_name {
+            pub ptr: *const #proxy_struct_name,
+            pub len: i32,
+            pub cap: i32,
+            pub free_ptr: extern "C" fn(*mut #proxy_struct_name, i32, i32),
+        }
+
+        #[no_mangle]
+        pub extern "C" fn #free_proxy_struct_array_fn(ptr: *mut #proxy_struct_name, len: i32, cap: i32) {
+            let catch_result = catch_unwind(AssertUnwindSafe(|| {
+                unsafe {
+                    // let proxy_vec =
+                    Vec::from_raw_parts(
+                        ptr as *mut #proxy_struct_name,
+                        len as usize,
+                        cap as usize);
+                    // proxy_vec.into_iter().for_each(|each| {#origin_struct_name::from(each);});
+                }
+            }));
+            match catch_result {
+                Ok(_) => {}
+                Err(e) => {
+                    println!("catch_unwind of `rsbind free proxy struct` error: {:?}", e);
+                }
+            };
+        }
+    }
+}
+
+///
+/// Wrap the swift callback behind the model, with `rust_defaults` the methods with default bodies
+/// are left to the rust trait instead of calling swift, which is how the protocol extensions run them.
+///
+fn c_pointers_to_callback_convert(
+    callback_desc: &TraitDesc,
+    rust_defaults: bool,
+) -> Result<TokenStream> {
     let struct_name = &format!("{}_struct", &callback_desc.name);
     let struct_ident = ident!(struct_name);
 
     let mut method_names = Vec::new();
-    let mut callback_methods = TokenStream::new();
+    let mut callback_methods = vec![];
     for method in callback_desc.methods.iter() {
+        if rust_defaults && method.has_default {
+            method_names.push(ident!(&method.name));
+            callback_methods.push(TokenStream::new());
+            continue;
+        }
         println!(
             "quote method {} in callback {}",
             method.name, callback_desc.name
@@ -43,7 +254,7 @@ fn c_pointers_to_callback_convert(callback_desc: &TraitDesc) -> Result<TokenStre
                     };
 
                     {
-                        (*CALLBACK_HASHMAP.write().unwrap()).insert(callback_index, CallbackEnum::#callback_ident(#origin_cb_arg_name));
+                        insert_callback(callback_index, CallbackEnum::#callback_ident(#origin_cb_arg_name));
                     }
                 }
             } else {
@@ -61,8 +272,8 @@ fn c_pointers_to_callback_convert(callback_desc: &TraitDesc) -> Result<TokenStre
                 let #cb_arg_name = #convert;
             };
 
-            let args_convert_each = match cb_arg.ty.clone() {
-                AstType::String | AstType::Vec(_) => {
+            let args_convert_each = match cb_arg.ty.unwrap_newtype() {
+                AstType::String | AstType::Vec(_) | AstType::Optional(_) => {
                     let ptr_arg = ident!(&format!("ptr_{}", &cb_arg.name));
                     quote! {
                         #convert
@@ -100,14 +311,25 @@ fn c_pointers_to_callback_convert(callback_desc: &TraitDesc) -> Result<TokenStre
             .filter(|arg| !matches!(arg.ty, AstType::Void))
             .map(|arg| match arg.ty.clone() {
                 AstType::Vec(vec_inner_name) => {
-                    let vec_innder_ident = ident!(&vec_inner_name.origin());
+                    let vec_innder_ident = quote_base_ty(&vec_inner_name);
                     quote!(Vec<#vec_innder_ident>)
                 }
+                AstType::Optional(optional_inner_name) => {
+                    let optional_inner_ident = quote_base_ty(&optional_inner_name);
+                    quote!(Option<#optional_inner_ident>)
+                }
+                AstType::Map(map, key, value) => {
+                    let map_ident = ident!(&map);
+                    let key_ident = ident!(&key.origin());
+                    let value_ident = quote_base_ty(&value);
+                    quote!(std::collections::#map_ident<#key_ident, #value_ident>)
+                }
                 AstType::Callback(origin) => {
                     has_callback_arg = true;
                     let origin_ident = ident!(&origin.origin);
                     quote!(Box<dyn #origin_ident>)
                 }
+                AstType::Tuple(ref elems) => quote_tuple_ty(elems),
                 _ => {
                     let ident = ident!(&arg.ty.origin());
                     quote!(#ident)
@@ -118,13 +340,24 @@ fn c_pointers_to_callback_convert(callback_desc: &TraitDesc) -> Result<TokenStre
         let ret_ty_tokens = match method.return_type {
             AstType::Void => quote!(()),
             AstType::Vec(ref base) => {
-                let ident = ident!(&base.origin());
+                let ident = quote_base_ty(base);
                 quote!(Vec<#ident>)
             }
+            AstType::Optional(ref base) => {
+                let ident = quote_base_ty(base);
+                quote!(Option<#ident>)
+            }
+            AstType::Map(ref map, ref key, ref value) => {
+                let map_ident = ident!(map);
+                let key_ident = ident!(&key.origin());
+                let value_ident = quote_base_ty(value);
+                quote!(std::collections::#map_ident<#key_ident, #value_ident>)
+            }
             AstType::Callback(ref origin) => {
                 let origin_ident = ident!(&origin.origin);
                 quote!(Box<dyn #origin_ident>)
             }
+            AstType::Tuple(ref elems) => quote_tuple_ty(elems),
             _ => {
                 let ident = ident!(&method.return_type.origin());
                 quote!(#ident)
@@ -159,15 +392,13 @@ fn c_pointers_to_callback_convert(callback_desc: &TraitDesc) -> Result<TokenStre
             }
         };
 
-        callback_methods = quote! {
-            #callback_methods
-            #each_method_tokens
-        };
+        callback_methods.push(each_method_tokens);
 
         method_names.push(method_name);
     }
 
     let callback_struct = quote_callback_struct(callback_desc, struct_name).unwrap();
+    let callback_impls = quote_callback_impls(callback_desc, &struct_ident, &callback_methods);
 
     let callback_ty = ident!(&callback_desc.name);
     let mut method_assign_tokens = TokenStream::new();
@@ -179,8 +410,14 @@ fn c_pointers_to_callback_convert(callback_desc: &TraitDesc) -> Result<TokenStre
     }
 
     // total converting codes.
-    let c_pointers_to_callback_fn_name =
-        ident!(&format!("c_pointers_to_callback_{}", callback_desc.name));
+    let c_pointers_to_callback_fn_name = if rust_defaults {
+        ident!(&format!(
+            "c_pointers_to_default_callback_{}",
+            callback_desc.name
+        ))
+    } else {
+        ident!(&format!("c_pointers_to_callback_{}", callback_desc.name))
+    };
     let callback_model_str = &format!("{}_{}_Model", &callback_desc.mod_name, &callback_desc.name);
     let callback_model_ident = ident!(callback_model_str);
 
@@ -188,9 +425,7 @@ fn c_pointers_to_callback_convert(callback_desc: &TraitDesc) -> Result<TokenStre
         fn #c_pointers_to_callback_fn_name(callback_model: #callback_model_ident) -> Box<dyn #callback_ty> {
             #callback_struct
 
-            impl #callback_ty for #struct_ident {
-                #callback_methods
-            }
+            #callback_impls
 
             impl Drop for  #struct_ident {
                 fn drop(&mut self) {
@@ -209,6 +444,86 @@ fn c_pointers_to_callback_convert(callback_desc: &TraitDesc) -> Result<TokenStre
     })
 }
 
+///
+/// The protocol extensions of callbacks call the rust default implementations through these functions,
+/// with the swift callback passed as a model like an argument.
+///
+fn quote_default_methods(callback: &TraitDesc) -> Result<TokenStream> {
+    let default_methods = callback
+        .methods
+        .iter()
+        .filter(|method| method.has_default && method.supertrait.is_none())
+        .collect::<Vec<&MethodDesc>>();
+    if default_methods.is_empty() {
+        return Ok(quote! {});
+    }
+
+    let mut result = c_pointers_to_callback_convert(callback, true)?;
+    let c_pointers_to_default_callback_fn_name = ident!(&format!(
+        "c_pointers_to_default_callback_{}",
+        &callback.name
+    ));
+    let callback_model_ident = ident!(&format!("{}_{}_Model", &callback.mod_name, &callback.name));
+    for method in default_methods {
+        let fn_name = ident!(&format!(
+            "{}_{}_default_{}",
+            &callback.mod_name, &callback.name, &method.name
+        ));
+        let method_name = ident!(&method.name);
+        let arg_names = method
+            .args
+            .iter()
+            .map(|arg| ident!(&arg.name))
+            .collect::<Vec<Ident>>();
+        let arg_types = method
+            .args
+            .iter()
+            .map(|arg| SwiftConvert { ty: arg.ty.clone() }.rust_transferable_type(Direction::Down))
+            .collect::<Vec<TokenStream>>();
+        let r_arg_names = method
+            .args
+            .iter()
+            .map(|arg| ident!(&format!("r_{}", &arg.name)))
+            .collect::<Vec<Ident>>();
+        let args_convert = method
+            .args
+            .iter()
+            .map(|arg| {
+                let arg_name = ident!(&arg.name);
+                let rust_arg_name = ident!(&format!("r_{}", &arg.name));
+                let convert = SwiftConvert { ty: arg.ty.clone() }
+                    .transferable_to_rust(quote! {#arg_name}, Direction::Down);
+                quote! {
+                    let #rust_arg_name = #convert;
+                }
+            })
+            .collect::<Vec<TokenStream>>();
+        let ret_ty_tokens = SwiftConvert {
+            ty: method.return_type.clone(),
+        }
+        .rust_transferable_type(Direction::Up);
+        let return_convert = SwiftConvert {
+            ty: method.return_type.clone(),
+        }
+        .rust_to_transferable(quote! {result}, Direction::Down);
+
+        result = quote! {
+            #result
+
+            #[no_mangle]
+            pub extern "C" fn #fn_name(callback: #callback_model_ident, #(#arg_names: #arg_types),*) -> #ret_ty_tokens {
+                abort_on_panic(|| {
+                    #(#args_convert)*
+                    let callback = #c_pointers_to_default_callback_fn_name(callback);
+                    let result = callback.#method_name(#(#r_arg_names),*);
+                    #return_convert
+                })
+            }
+        };
+    }
+    Ok(result)
+}
+
 pub(crate) fn callback_to_c_pointers_convert(callback: &TraitDesc) -> Result<TokenStream> {
     let callback_model_str = &format!("{}_{}_Model", &callback.mod_name, &callback.name);
     let callback_model_ident = ident!(callback_model_str);
@@ -283,28 +598,59 @@ pub(crate) fn callback_to_c_pointers_convert(callback: &TraitDesc) -> Result<Tok
                 #method_result
 
                 pub extern "C" fn #ret_method_name(index: i64, #(#arg_names: #arg_types),*) -> #ret_ty_tokens {
-                    #args_convert
-                    let mut callback_index = 0;
-                    let mut result: Option<Box<dyn #return_callback_ident >> = None;
-                    let final_result = {
+                    abort_on_panic(|| {
+                        #args_convert
+                        let mut callback_index = 0;
+                        let mut result: Option<Box<dyn #return_callback_ident >> = None;
+                        let final_result = {
+                            let callback_hashmap = &*CALLBACK_HASHMAP.read().unwrap();
+                            let ret_callback = callback_hashmap.get(&index);
+                            match ret_callback {
+                                Some(ret_callback) => {
+                                    if let CallbackEnum::#callback_ident(ret_callback) = ret_callback {
+                                        result = Some(ret_callback.#method_name(#(#r_arg_names),*));
+                                        callback_index = {
+                                            let mut global_index = CALLBACK_INDEX.write().unwrap();
+                                            let mut index = *global_index;
+                                            if index == i64::MAX {
+                                                *global_index = 0;
+                                                index = 0;
+                                            } else {
+                                                *global_index = index + 1;
+                                                index = index + 1;
+                                            }
+                                            index
+                                        };
+                                        #return_convert
+                                        r_result
+                                    } else {
+                                        panic!("Callback doesn't match for index: {}", index);
+                                    }
+                                }
+                                None => {
+                                    panic!("No callback found for index: {}", index);
+                                }
+                            }
+                        };
+
+                        insert_callback(callback_index, CallbackEnum::#return_callback_ident(result.unwrap()));
+                        final_result
+                    })
+                }
+            };
+        } else {
+            method_result = quote! {
+                #method_result
+
+                pub extern "C" fn #ret_method_name(index: i64, #(#arg_names: #arg_types),*) -> #ret_ty_tokens {
+                    abort_on_panic(|| {
+                        #args_convert
                         let callback_hashmap = &*CALLBACK_HASHMAP.read().unwrap();
                         let ret_callback = callback_hashmap.get(&index);
                         match ret_callback {
                             Some(ret_callback) => {
                                 if let CallbackEnum::#callback_ident(ret_callback) = ret_callback {
-                                    result = Some(ret_callback.#method_name(#(#r_arg_names),*));
-                                    callback_index = {
-                                        let mut global_index = CALLBACK_INDEX.write().unwrap();
-                                        let mut index = *global_index;
-                                        if index == i64::MAX {
-                                            *global_index = 0;
-                                            index = 0;
-                                        } else {
-                                            *global_index = index + 1;
-                                            index = index + 1;
-                                        }
-                                        index
-                                    };
+                                    let mut result = ret_callback.#method_name(#(#r_arg_names),*);
                                     #return_convert
                                     r_result
                                 } else {
@@ -315,34 +661,7 @@ pub(crate) fn callback_to_c_pointers_convert(callback: &TraitDesc) -> Result<Tok
                                 panic!("No callback found for index: {}", index);
                             }
                         }
-                    };
-
-                    (*CALLBACK_HASHMAP.write().unwrap()).insert(callback_index, CallbackEnum::#return_callback_ident(result.unwrap()));
-                    final_result
-                }
-            };
-        } else {
-            method_result = quote! {
-                #method_result
-
-                pub extern "C" fn #ret_method_name(index: i64, #(#arg_names: #arg_types),*) -> #ret_ty_tokens {
-                    #args_convert
-                    let callback_hashmap = &*CALLBACK_HASHMAP.read().unwrap();
-                    let ret_callback = callback_hashmap.get(&index);
-                    match ret_callback {
-                        Some(ret_callback) => {
-                            if let CallbackEnum::#callback_ident(ret_callback) = ret_callback {
-                                let mut result = ret_callback.#method_name(#(#r_arg_names),*);
-                                #return_convert
-                                r_result
-                            } else {
-                                panic!("Callback doesn't match for index: {}", index);
-                            }
-                        }
-                        None => {
-                            panic!("No callback found for index: {}", index);
-                        }
-                    }
+                    })
                 }
             };
         }
@@ -352,7 +671,7 @@ pub(crate) fn callback_to_c_pointers_convert(callback: &TraitDesc) -> Result<Tok
         #method_result
 
         pub extern "C" fn ret_free_callback(index: i64) {
-            (*CALLBACK_HASHMAP.write().unwrap()).remove(&index);
+            abort_on_panic(|| remove_callback(index));
         }
 
         pub extern "C" fn ret_free_ptr(buffer: *mut i8, len: i32, cap: i32) {
@@ -419,14 +738,73 @@ pub(crate) fn quote_callback_struct(callback: &TraitDesc, name: &str) -> Result<
     Ok(callback_struct)
 }
 
+/// Result is returned by writing to ok_out or err_out, and the return value is the status.
+pub(crate) fn quote_result_return_convert(
+    ctx: &MethodContext<Swift<'static>, ()>,
+) -> Result<TokenStream> {
+    let ok_ty = ctx.method.return_type.ok_type();
+    let err_ty = match ctx.method.return_type.err_type() {
+        Some(err) => AstType::from(err),
+        None => {
+            return Err(ErrorKind::GenerateError("not a result return type.".to_string()).into())
+        }
+    };
+
+    let (ok_pattern, ok_write) = if ok_ty == AstType::Void {
+        (quote! {_}, quote! {})
+    } else {
+        let ok_convert =
+            SwiftConvert { ty: ok_ty }.rust_to_transferable(quote! {result}, Direction::Down);
+        (
+            quote! {result},
+            quote! {
+                let r_result = #ok_convert;
+                unsafe { ok_out.write(r_result) };
+            },
+        )
+    };
+    let err_convert =
+        SwiftConvert { ty: err_ty }.rust_to_transferable(quote! {err}, Direction::Down);
+
+    Ok(quote! {
+        match result {
+            Ok(#ok_pattern) => {
+                #ok_write
+                0
+            }
+            Err(err) => {
+                let r_err = #err_convert;
+                unsafe { err_out.write(r_err) };
+                1
+            }
+        }
+    })
+}
+
 pub struct SwiftImp {}
 
 impl LangImp<Swift<'static>, ()> for SwiftImp {
     fn quote_lib_file(&self, context: &BridgeContext<Swift<'static>, ()>) -> Result<TokenStream> {
         let host_crate_underscore = ident!(&context.crate_name.replace("-", "_"));
+        let stream_crate = if context.ast.has_stream() {
+            quote!(
+                extern crate futures_core;
+            )
+        } else {
+            quote!()
+        };
+        let cancellation_crate = if context.ast.has_cancellation() {
+            quote!(
+                extern crate tokio_util;
+            )
+        } else {
+            quote!()
+        };
         Ok(quote! {
             #![allow(warnings)]
             extern crate #host_crate_underscore;
+            #stream_crate
+            #cancellation_crate
             #[macro_use]
             extern crate serde_derive;
             extern crate serde;
@@ -437,8 +815,31 @@ impl LangImp<Swift<'static>, ()> for SwiftImp {
 
     fn quote_common_file(
         &self,
-        _context: &BridgeContext<Swift<'static>, ()>,
+        context: &BridgeContext<Swift<'static>, ()>,
     ) -> Result<TokenStream> {
+        // One generic struct for tuples with the same number of elements.
+        let c_tuples = context
+            .ast
+            .tuple_arities()
+            .into_iter()
+            .map(|arity| {
+                let c_tuple = ident!(&format!("CTuple{}", arity));
+                let params = (0..arity)
+                    .map(|index| ident!(&format!("T{}", index)))
+                    .collect::<Vec<Ident>>();
+                let fields = (0..arity)
+                    .map(|index| ident!(&format!("_{}", index)))
+                    .collect::<Vec<Ident>>();
+                quote! {
+                    #[repr(C)]
+                    #[derive(Clone)]
+                    pub struct #c_tuple<#(#params),*> {
+                        #(pub #fields: #params),*
+                    }
+                }
+            })
+            .collect::<Vec<TokenStream>>();
+
         let int8_free_fn = self.quote_free_rust_array("free_i8_array".to_string(), quote! {i8});
         let int16_free_fn = self.quote_free_rust_array("free_i16_array".to_string(), quote! {i16});
         let int32_free_fn = self.quote_free_rust_array("free_i32_array".to_string(), quote! {i32});
@@ -446,10 +847,51 @@ impl LangImp<Swift<'static>, ()> for SwiftImp {
         let float_free_fn = self.quote_free_rust_array("free_f32_array".to_string(), quote! {f32});
         let double_free_fn = self.quote_free_rust_array("free_f64_array".to_string(), quote! {f64});
 
+        // Tokens are owned by `Cancellable`, rust receives the clones.
+        let cancellation_fns = if context.ast.has_cancellation() {
+            quote! {
+                #[no_mangle]
+                pub extern "C" fn cancellation_new() -> i64 {
+                    abort_on_panic(|| Box::into_raw(Box::new(tokio_util::sync::CancellationToken::new())) as i64)
+                }
+
+                #[no_mangle]
+                pub extern "C" fn cancellation_cancel(handle: i64) {
+                    abort_on_panic(|| unsafe { (*(handle as *const tokio_util::sync::CancellationToken)).cancel() });
+                }
+
+                #[no_mangle]
+                pub extern "C" fn cancellation_is_cancelled(handle: i64) -> i32 {
+                    abort_on_panic(|| {
+                        let token = unsafe { &*(handle as *const tokio_util::sync::CancellationToken) };
+                        if token.is_cancelled() { 1 } else { 0 }
+                    })
+                }
+
+                #[no_mangle]
+                pub extern "C" fn cancellation_free(handle: i64) {
+                    abort_on_panic(|| unsafe { drop(Box::from_raw(handle as *mut tokio_util::sync::CancellationToken)) });
+                }
+            }
+        } else {
+            quote! {}
+        };
+
+        let message_convert = SwiftConvert {
+            ty: AstType::String,
+        }
+        .rust_to_transferable(quote! {message}, Direction::Down);
+
+        let stats_convert = SwiftConvert {
+            ty: AstType::String,
+        };
+        let stats_ty = stats_convert.rust_transferable_type(Direction::Down);
+        let stats_convert = stats_convert.rust_to_transferable(quote! {stats}, Direction::Down);
+
         let tokens = quote! {
             use std::panic::*;
             use std::ffi::CString;
-            use std::os::raw::c_char;
+            use std::os::raw::{c_char, c_void};
             use std::ffi::CStr;
 
             #[repr(C)]
@@ -506,6 +948,34 @@ impl LangImp<Swift<'static>, ()> for SwiftImp {
                 pub free_ptr: extern "C" fn(*mut f64, i32, i32),
             }
 
+            #[repr(C)]
+            #[derive(Clone)]
+            pub struct CArray<T> {
+                pub ptr: * const T,
+                pub len: i32,
+                pub cap: i32,
+                pub free_ptr: extern "C" fn(*mut T, i32, i32),
+            }
+
+            #[repr(C)]
+            #[derive(Clone)]
+            pub struct CMap<K, V> {
+                pub keys: K,
+                pub values: V,
+            }
+
+            /// `bytes::Bytes` returned from rust, the memory is owned by the handle.
+            #[repr(C)]
+            #[derive(Clone)]
+            pub struct CBuffer {
+                pub ptr: * const u8,
+                pub len: i64,
+                pub handle: *mut c_void,
+                pub free_handle: extern "C" fn(*mut c_void),
+            }
+
+            #(#c_tuples)*
+
             #int8_free_fn
             #int16_free_fn
             #int32_free_fn
@@ -513,6 +983,40 @@ impl LangImp<Swift<'static>, ()> for SwiftImp {
             #float_free_fn
             #double_free_fn
 
+            /// Frees the arguments borrowed from swift when the bridge method returns.
+            pub struct FreeOnDrop<F: FnMut()>(pub F);
+
+            impl<F: FnMut()> Drop for FreeOnDrop<F> {
+                fn drop(&mut self) {
+                    (self.0)()
+                }
+            }
+
+            pub extern "C" fn free_c_array<T>(ptr: *mut T, length: i32, cap: i32) {
+                let catch_result = catch_unwind(AssertUnwindSafe(|| {
+                    unsafe { Vec::from_raw_parts(ptr, length as usize, cap as usize); }
+                }));
+                match catch_result {
+                    Ok(_) => {}
+                    Err(e) => {
+                        println!("catch_unwind of `rsbind free_c_array` error: {:?}", e);
+                    }
+                };
+            }
+
+            #[no_mangle]
+            pub extern "C" fn free_buffer(handle: *mut c_void) {
+                let catch_result = catch_unwind(AssertUnwindSafe(|| unsafe {
+                    drop(Box::from_raw(handle as (*mut Box<dyn AsRef<[u8]> + Send>)));
+                }));
+                match catch_result {
+                    Ok(_) => {}
+                    Err(e) => {
+                        println!("catch_unwind of `rsbind free_buffer` error: {:?}", e);
+                    }
+                };
+            }
+
             #[no_mangle]
             pub extern "C" fn free_str(ptr: *mut i8, length: i32, cap: i32) {
                 let catch_result = catch_unwind(AssertUnwindSafe(|| unsafe {
@@ -528,6 +1032,57 @@ impl LangImp<Swift<'static>, ()> for SwiftImp {
                 };
             }
 
+            #[no_mangle]
+            pub extern "C" fn rsbind_callback_stats() -> #stats_ty {
+                abort_on_panic(|| {
+                    let stats = serde_json::to_string(&crate::callback_stats()).unwrap();
+                    #stats_convert
+                })
+            }
+
+            /// The status of a call, written to the `status` argument of the functions of the traits.
+            /// `code` is 0 when the call returned, and 1 when rust panicked, then `message` is
+            /// the panic message released by its `free_ptr`, and the other out arguments are not written.
+            #[repr(C)]
+            pub struct RustStatus {
+                pub code: i32,
+                pub message: CInt8Array,
+            }
+
+            /// Runs a function of the traits, a panic is reported by the status instead of unwinding into swift.
+            pub fn catch_panic<T, F: FnOnce() -> T>(status: *mut RustStatus, f: F) -> Option<T> {
+                let (result, code, message) = match catch_unwind(AssertUnwindSafe(f)) {
+                    Ok(result) => (Some(result), 0, String::new()),
+                    Err(payload) => (None, 1, panic_message(payload).replace('\0', "")),
+                };
+                if !status.is_null() {
+                    let message = #message_convert;
+                    unsafe { status.write(RustStatus { code, message }) };
+                }
+                result
+            }
+
+            /// The message of a caught panic, which is thrown as `RustPanicError` by calls and async methods.
+            pub fn panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+                if let Some(message) = payload.downcast_ref::<&str>() {
+                    message.to_string()
+                } else if let Some(message) = payload.downcast_ref::<String>() {
+                    message.clone()
+                } else {
+                    "rust panicked".to_owned()
+                }
+            }
+
+            /// Runs the functions which can't report errors, like the callbacks implemented by rust,
+            /// a panic aborts the process after printing the message.
+            pub fn abort_on_panic<T, F: FnOnce() -> T>(f: F) -> T {
+                match catch_unwind(AssertUnwindSafe(f)) {
+                    Ok(result) => result,
+                    Err(_) => std::process::abort(),
+                }
+            }
+
+            #cancellation_fns
         };
 
         Ok(tokens)
@@ -547,12 +1102,7 @@ impl LangImp<Swift<'static>, ()> for SwiftImp {
     }
 
     fn quote_common_part(&self, _context: &ModContext<Swift<'static>, ()>) -> Result<TokenStream> {
-        Ok(quote! {
-            lazy_static! {
-                static ref CALLBACK_HASHMAP: Arc<RwLock<HashMap<i64, CallbackEnum>>> =  Arc::new(RwLock::new(HashMap::new()));
-                static ref CALLBACK_INDEX : Arc<RwLock<i64>> = Arc::new(RwLock::new(0));
-            }
-        })
+        Ok(quote! {})
     }
 
     fn quote_method_sig(&self, context: &MethodContext<Swift<'static>, ()>) -> Result<TokenStream> {
@@ -563,7 +1113,7 @@ impl LangImp<Swift<'static>, ()> for SwiftImp {
             &context.method.name
         ));
 
-        let arg_names = context
+        let mut arg_names = context
             .method
             .args
             .iter()
@@ -571,7 +1121,7 @@ impl LangImp<Swift<'static>, ()> for SwiftImp {
             .map(|arg| ident!(&arg.name))
             .collect::<Vec<Ident>>();
 
-        let arg_types = context
+        let mut arg_types = context
             .method
             .args
             .iter()
@@ -579,10 +1129,42 @@ impl LangImp<Swift<'static>, ()> for SwiftImp {
             .map(|arg| SwiftConvert { ty: arg.ty.clone() }.rust_transferable_type(Direction::Down))
             .collect::<Vec<TokenStream>>();
 
-        let ret_ty_tokens = SwiftConvert {
-            ty: context.method.return_type.clone(),
+        // Methods of objects are called on the handle, constructors create it.
+        if context.service_ctx.trait_.is_object && !context.method.is_static {
+            arg_names.insert(0, ident!("handle"));
+            arg_types.insert(0, quote! {i64});
         }
-        .rust_transferable_type(Direction::Up);
+
+        let ret_ty_tokens = match context.method.return_type.err_type() {
+            Some(err) => {
+                let ok_ty = context.method.return_type.ok_type();
+                if ok_ty != AstType::Void {
+                    let ok_ty_tokens =
+                        SwiftConvert { ty: ok_ty }.rust_transferable_type(Direction::Up);
+                    arg_names.push(ident!("ok_out"));
+                    arg_types.push(quote! {*mut #ok_ty_tokens});
+                }
+                let err_ty_tokens = SwiftConvert {
+                    ty: AstType::from(err),
+                }
+                .rust_transferable_type(Direction::Up);
+                arg_names.push(ident!("err_out"));
+                arg_types.push(quote! {*mut #err_ty_tokens});
+                quote! {i32}
+            }
+            None => {
+                let ret_ty = context.method.return_type.clone();
+                if ret_ty != AstType::Void {
+                    let ok_ty_tokens =
+                        SwiftConvert { ty: ret_ty }.rust_transferable_type(Direction::Up);
+                    arg_names.push(ident!("ok_out"));
+                    arg_types.push(quote! {*mut #ok_ty_tokens});
+                }
+                quote! {()}
+            }
+        };
+        arg_names.push(ident!("status"));
+        arg_types.push(quote! {*mut RustStatus});
         let sig_define = quote! {
             #[no_mangle]
             pub extern "C" fn #fun_name(#(#arg_names: #arg_types),*) -> #ret_ty_tokens
@@ -615,44 +1197,46 @@ impl LangImp<Swift<'static>, ()> for SwiftImp {
             })
             .collect::<Vec<TokenStream>>();
 
-        let struct_array_str = format!("C{}Array", &struct_desc.name);
-        let struct_array_name = ident!(&struct_array_str);
+        let proxy_array = quote_proxy_array(&struct_desc.name);
 
-        fn origin_to_proxy_convert(field: &ArgDesc) -> TokenStream {
+        let members = quote_struct_members(struct_desc);
+
+        fn origin_to_proxy_convert(field: &ArgDesc, member: &TokenStream) -> TokenStream {
             let field_name = ident!(&field.name);
             let convert = SwiftConvert {
                 ty: field.ty.clone(),
             }
-            .rust_to_transferable(quote! {origin.#field_name}, Direction::Down);
+            .rust_to_transferable(quote! {origin.#member}, Direction::Down);
             quote! {
                 #field_name : #convert
             }
         }
 
-        fn proxy_to_origin_convert(field: &ArgDesc) -> TokenStream {
+        fn proxy_to_origin_convert(field: &ArgDesc, member: &TokenStream) -> TokenStream {
             let field_name = ident!(&field.name);
             let convert = SwiftConvert {
                 ty: field.ty.clone(),
             }
             .transferable_to_rust(quote! {proxy.#field_name}, Direction::Down);
             quote! {
-                #field_name : #convert
+                #member : #convert
             }
         }
 
         let origin_to_proxy_convert_tokens = struct_desc
             .fields
             .iter()
-            .map(origin_to_proxy_convert)
+            .zip(members.iter())
+            .map(|(field, member)| origin_to_proxy_convert(field, member))
             .collect::<Vec<TokenStream>>();
 
         let proxy_to_origin_convert_tokens = struct_desc
             .fields
             .iter()
-            .map(proxy_to_origin_convert)
+            .zip(members.iter())
+            .map(|(field, member)| proxy_to_origin_convert(field, member))
             .collect::<Vec<TokenStream>>();
 
-        let free_proxy_struct_array_fn = ident!(&format!("free_{}", &struct_array_str));
         let free_proxy_struct_fn = ident!(&format!("free_{}", &proxy_struct_str));
 
         Ok(quote! {
@@ -690,34 +1274,21 @@ impl LangImp<Swift<'static>, ()> for SwiftImp {
                 };
             }
 
-            #[repr(C)]
-            pub struct #struct_array_name {
-                pub ptr: *const #proxy_struct_name,
-                pub len: i32,
-                pub cap: i32,
-                pub free_ptr: extern "C" fn(*mut #proxy_struct_name, i32, i32),
-            }
+            #proxy_array
+        })
+    }
 
-            #[no_mangle]
-            pub extern "C" fn #free_proxy_struct_array_fn(ptr: *mut #proxy_struct_name, len: i32, cap: i32) {
-                let catch_result = catch_unwind(AssertUnwindSafe(|| {
-                    unsafe {
-                        // let proxy_vec =
-                        Vec::from_raw_parts(
-                            ptr as *mut #proxy_struct_name,
-                            len as usize,
-                            cap as usize);
-                        // proxy_vec.into_iter().for_each(|each| {#origin_struct_name::from(each);});
-                    }
-                }));
-                match catch_result {
-                    Ok(_) => {}
-                    Err(e) => {
-                        println!("catch_unwind of `rsbind free proxy struct` error: {:?}", e);
-                    }
-                };
-            }
+    fn quote_for_one_enum(&self, context: &EnumContext<Swift<'static>, ()>) -> Result<TokenStream> {
+        let proxy_enum_name = ident!(&format!("Proxy{}", &context.enum_.name));
+        if !context.enum_.is_c_like() {
+            return Ok(quote_data_enum_proxy(context.enum_));
+        }
 
+        let convert = quote_enum_proxy_convert(context.enum_);
+        Ok(quote! {
+            pub struct #proxy_enum_name(pub i32);
+
+            #convert
         })
     }
 
@@ -734,7 +1305,9 @@ impl LangImp<Swift<'static>, ()> for SwiftImp {
         };
 
         let callback_to_c_pointers_convert_tokens = callback_to_c_pointers_convert(callback)?;
-        let c_pointers_to_callback_convert_tokens = c_pointers_to_callback_convert(callback)?;
+        let c_pointers_to_callback_convert_tokens =
+            c_pointers_to_callback_convert(callback, false)?;
+        let default_tokens = quote_default_methods(callback)?;
 
         Ok(quote! {
             #callback_struct_tokens
@@ -742,6 +1315,26 @@ impl LangImp<Swift<'static>, ()> for SwiftImp {
             #callback_to_c_pointers_convert_tokens
 
             #c_pointers_to_callback_convert_tokens
+
+            #default_tokens
+        })
+    }
+
+    fn quote_free_object(
+        &self,
+        context: &ServiceContext<Swift<'static>, ()>,
+    ) -> Result<TokenStream> {
+        let object_ty = quote_object_ty(context);
+        let free_fn = ident!(&format!(
+            "{}_{}_free_object",
+            &context.trait_.mod_name, &context.trait_.name
+        ));
+
+        Ok(quote! {
+            #[no_mangle]
+            pub extern "C" fn #free_fn(handle: i64) {
+                abort_on_panic(|| unsafe { drop(Box::from_raw(handle as *mut #object_ty)) });
+            }
         })
     }
 
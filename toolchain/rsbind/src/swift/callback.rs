@@ -1,12 +1,15 @@
-use heck::ToLowerCamelCase;
-use rstgen::swift::{Argument, Class, Method, Modifier, Protocol, Swift};
+use heck::{ToLowerCamelCase, ToUpperCamelCase};
+use rstgen::swift::{Argument, Class, Extension, Method, Modifier, Protocol, Swift};
 use rstgen::{swift, IntoTokens, Tokens};
 
 use crate::ast::contract::desc::{ArgDesc, MethodDesc, TraitDesc};
+use crate::ast::types::AstType;
 use crate::base::lang::{Convertible, Direction};
 use crate::errors::*;
 use crate::swift::converter::SwiftConvert;
-use crate::swift::types::{to_swift_file, SwiftType};
+use crate::swift::dispatcher::DISPATCHER_CLASS;
+use crate::swift::panic::PANIC_ERROR;
+use crate::swift::types::{completion_handler, to_swift_file, SwiftType};
 
 pub(crate) struct CallbackGen<'a> {
     pub desc: &'a TraitDesc,
@@ -16,8 +19,20 @@ impl<'a> CallbackGen<'a> {
     pub fn gen(&self) -> Result<String> {
         let mut protocol = Protocol::new(self.desc.name.clone());
         protocol.modifiers = vec![Modifier::Public];
-
-        for method in self.desc.methods.iter() {
+        for supertrait in self.desc.supertraits.iter() {
+            protocol.extends.append(swift::local(supertrait.clone()));
+        }
+        // Default methods call the default implementation of the rust trait.
+        let mut extension = Extension::new(swift::local(self.desc.name.clone()));
+        extension.modifiers = vec![Modifier::Public];
+
+        // Inherited methods are declared by the protocols of the supertraits.
+        for method in self
+            .desc
+            .methods
+            .iter()
+            .filter(|method| method.supertrait.is_none())
+        {
             let mut m = Method::new(method.name.to_lower_camel_case());
             m.modifiers = vec![];
             m.returns = Some(Swift::from(SwiftType::new(method.return_type.clone())));
@@ -26,10 +41,112 @@ impl<'a> CallbackGen<'a> {
                 let argument = swift::Argument::new(arg_ty, arg.name.as_ref());
                 m.arguments.push(argument)
             }
+
+            if method.has_default {
+                let mut default = m.clone();
+                let args = method
+                    .args
+                    .iter()
+                    .map(|arg| format!(", {}: {}", &arg.name, &arg.name))
+                    .collect::<String>();
+                push_f!(
+                    default.body,
+                    "return Internal{}.default{}(callback: self{})",
+                    &self.desc.name,
+                    method.name.to_upper_camel_case(),
+                    args
+                );
+                extension.methods.push(default);
+            }
             protocol.methods.push(m);
         }
 
-        to_swift_file(protocol.into_tokens())
+        let mut tokens = Tokens::new();
+        tokens.push(protocol.into_tokens());
+        if !extension.methods.is_empty() {
+            tokens.push(extension.into_tokens());
+        }
+        if self.desc.is_completion {
+            tokens.push(self.quote_completion_handler());
+        }
+        to_swift_file(tokens)
+    }
+
+    ///
+    /// The completion callback of an async method, which calls the completion handler closure.
+    ///
+    fn quote_completion_handler(&self) -> Tokens<'static, Swift<'static>> {
+        let arg_of = |name: &str| {
+            self.desc
+                .methods
+                .iter()
+                .find(|method| method.name == name)
+                .and_then(|method| method.args.first())
+        };
+        let value = arg_of("on_complete");
+        let error = arg_of("on_error");
+        let ok = value.map_or(AstType::Void, |arg| arg.ty.clone());
+        let handler = completion_handler(ok);
+        let type_str = |ty: &AstType| format!("{}", toks!(Swift::from(SwiftType::new(ty.clone()))));
+
+        let mut class = Tokens::new();
+        push_f!(
+            class,
+            "internal class {}Handler : {} {{",
+            &self.desc.name,
+            &self.desc.name
+        );
+        let mut members: Vec<Tokens<Swift>> = vec![];
+        members.push(toks_f!("private let handler : {}", toks!(handler.clone())));
+
+        let mut init = Tokens::new();
+        push_f!(init, "init(handler : @escaping {}) {{", toks!(handler));
+        nested!(init, "self.handler = handler");
+        push!(init, "}");
+        members.push(init);
+
+        let mut complete = Tokens::new();
+        match value {
+            Some(value) => {
+                push_f!(
+                    complete,
+                    "func onComplete(value : {}) {{",
+                    type_str(&value.ty)
+                );
+                nested!(complete, "handler(.success(value))");
+            }
+            None => {
+                push!(complete, "func onComplete() {");
+                nested!(complete, "handler(.success(()))");
+            }
+        }
+        push!(complete, "}");
+        members.push(complete);
+
+        if let Some(error) = error {
+            let mut fail = Tokens::new();
+            push_f!(fail, "func onError(error : {}) {{", type_str(&error.ty));
+            if error.ty == AstType::String {
+                nested!(fail, "handler(.failure(RustError(message: error)))");
+            } else {
+                nested!(fail, "handler(.failure(error))");
+            }
+            push!(fail, "}");
+            members.push(fail);
+        }
+
+        let mut panic = Tokens::new();
+        push!(panic, "func onPanic(message : String) {");
+        nested_f!(
+            panic,
+            "handler(.failure({}(message: message)))",
+            PANIC_ERROR
+        );
+        push!(panic, "}");
+        members.push(panic);
+        class.nested(members.into_tokens().join_line_spacing());
+        push!(class, "}");
+        class
     }
 }
 
@@ -54,6 +171,7 @@ impl<'a> InternalCallbackGen<'a> {
 
         self.fill_callback_to_model(&mut class)?;
         self.fill_model_to_callback(&mut class)?;
+        self.fill_default_methods(&mut class)?;
 
         tokens.push(class.into_tokens());
 
@@ -143,6 +261,59 @@ impl<'a> InternalCallbackGen<'a> {
         Ok(())
     }
 
+    ///
+    /// The protocol extension calls the default implementation of the rust trait,
+    /// with the callback passed as a model like an argument, so rust can call its other methods.
+    ///
+    fn fill_default_methods(&self, class: &mut Class<'a>) -> Result<()> {
+        for method in self
+            .desc
+            .methods
+            .iter()
+            .filter(|method| method.has_default && method.supertrait.is_none())
+        {
+            let mut m = Method::new(format!("default{}", method.name.to_upper_camel_case()));
+            m.modifiers = vec![Modifier::Internal, Modifier::Static];
+            m.arguments.push(Argument::new(
+                swift::local(self.desc.name.clone()),
+                "callback",
+            ));
+            for arg in method.args.iter() {
+                m.arguments.push(Argument::new(
+                    SwiftConvert { ty: arg.ty.clone() }.native_type(),
+                    arg.name.clone(),
+                ));
+            }
+            m.returns(
+                SwiftConvert {
+                    ty: method.return_type.clone(),
+                }
+                .native_type(),
+            );
+
+            push!(m.body, "let model = callbackToModel(callback: callback)");
+            let mut call = format!(
+                "let result = {}_{}_default_{}(model",
+                &self.desc.mod_name, &self.desc.name, &method.name
+            );
+            for arg in method.args.iter() {
+                let convert = SwiftConvert { ty: arg.ty.clone() }
+                    .native_to_transferable(arg.name.clone(), Direction::Down);
+                push_f!(m.body, "let s_{} = {}", arg.name, convert);
+                call = format!("{}, s_{}", call, &arg.name);
+            }
+            push_f!(m.body, "{})", call);
+            let convert = SwiftConvert {
+                ty: method.return_type.clone(),
+            }
+            .transferable_to_native("result".to_string(), Direction::Down);
+            push_f!(m.body, "return {}", convert);
+            class.methods.push(m);
+        }
+
+        Ok(())
+    }
+
     fn fill_call_native_method(
         &self,
         method_body: &mut Tokens<Swift>,
@@ -301,6 +472,18 @@ impl<'a> InternalCallbackGen<'a> {
 
         cb_method_call = format!("{})", &cb_method_call);
 
+        // Only methods without return value are dispatched, rust doesn't wait for them.
+        if let Some(ref dispatch) = cb_method.dispatch {
+            nested_f!(
+                method_body,
+                "let result : () = {}.execute(\"{}\") {{ origin_callback.{}{} }}",
+                DISPATCHER_CLASS,
+                dispatch,
+                cb_method.name.to_lower_camel_case(),
+                cb_method_call
+            );
+            return Ok(());
+        }
         nested_f!(
             method_body,
             "let result = origin_callback.{}{}",
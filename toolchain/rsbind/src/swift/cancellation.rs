@@ -0,0 +1,54 @@
+use rstgen::swift::Swift;
+use rstgen::{IntoTokens, Tokens};
+
+use crate::errors::*;
+use crate::swift::types::to_swift_file;
+
+pub(crate) const CANCELLATION_CLASS: &str = "Cancellable";
+
+///
+/// Generate the class holding a `CancellationToken` created by rust, which is passed to contract
+/// methods and cancelled by native code. The token is released in `deinit`, the clones passed to rust
+/// still share the cancellation.
+///
+pub(crate) struct CancellationGen {}
+
+impl CancellationGen {
+    pub(crate) fn gen(&self) -> Result<String> {
+        let mut class: Tokens<Swift> = Tokens::new();
+        push_f!(class, "public final class {} {{", CANCELLATION_CLASS);
+        class.nested({
+            let mut members: Vec<Tokens<Swift>> = vec![];
+            members.push(toks!("internal let handle : Int64"));
+
+            let mut init = Tokens::new();
+            push!(init, "public init() {");
+            nested!(init, "self.handle = cancellation_new()");
+            push!(init, "}");
+            members.push(init);
+
+            let mut cancel = Tokens::new();
+            push!(cancel, "public func cancel() {");
+            nested!(cancel, "cancellation_cancel(handle)");
+            push!(cancel, "}");
+            members.push(cancel);
+
+            let mut cancelled = Tokens::new();
+            push!(cancelled, "public var isCancelled : Bool {");
+            nested!(cancelled, "return cancellation_is_cancelled(handle) != 0");
+            push!(cancelled, "}");
+            members.push(cancelled);
+
+            let mut deinit = Tokens::new();
+            push!(deinit, "deinit {");
+            nested!(deinit, "cancellation_free(handle)");
+            push!(deinit, "}");
+            members.push(deinit);
+
+            members.into_tokens().join_line_spacing()
+        });
+        push!(class, "}");
+
+        to_swift_file(class)
+    }
+}
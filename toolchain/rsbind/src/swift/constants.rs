@@ -0,0 +1,63 @@
+use heck::ToLowerCamelCase;
+use rstgen::swift::Swift;
+use rstgen::Tokens;
+
+use crate::ast::contract::desc::{ConstDesc, ConstValue};
+use crate::ast::types::AstType;
+use crate::errors::*;
+use crate::swift::types::{to_swift_file, SwiftType};
+
+pub(crate) const CONSTANTS_CLASS: &str = "RustConstants";
+
+///
+/// Generate the caseless enum holding the `pub const` values of the contracts as `static let` members.
+///
+pub(crate) struct ConstantsGen<'a> {
+    pub consts: &'a [ConstDesc],
+}
+
+impl<'a> ConstantsGen<'a> {
+    pub(crate) fn gen(&self) -> Result<String> {
+        let mut constants: Tokens<Swift> = Tokens::new();
+        push_f!(constants, "public enum {} {{", CONSTANTS_CLASS);
+        for desc in self.consts.iter() {
+            let ty = format!("{}", toks!(Swift::from(SwiftType::new(desc.ty.clone()))));
+            nested_f!(
+                constants,
+                "public static let {}: {} = {}",
+                desc.name.to_lower_camel_case(),
+                ty,
+                quote_value(desc)
+            );
+        }
+        push!(constants, "}");
+
+        to_swift_file(constants)
+    }
+}
+
+fn quote_value(desc: &ConstDesc) -> String {
+    match (&desc.ty, &desc.value) {
+        (AstType::Float(_), ConstValue::Float(value)) => format!("{:?}", *value as f32),
+        (_, ConstValue::Float(value)) => format!("{:?}", value),
+        (_, ConstValue::Int(value)) => value.to_string(),
+        (_, ConstValue::Bool(value)) => value.to_string(),
+        (_, ConstValue::String(value)) => format!("\"{}\"", escape(value)),
+    }
+}
+
+fn escape(value: &str) -> String {
+    let mut escaped = String::new();
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{{{:x}}}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
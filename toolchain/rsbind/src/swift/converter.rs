@@ -5,11 +5,21 @@ use rstgen::Tokens;
 use crate::ast::types::{AstBaseType, AstType};
 use crate::base::lang::{Convertible, Direction};
 use crate::swift::ty::basic::{Basic, Bool};
+use crate::swift::ty::buffer::Buffer;
 use crate::swift::ty::callback::Callback;
+use crate::swift::ty::cancellation::Cancellation;
+use crate::swift::ty::enum_::Enum;
+use crate::swift::ty::map::Map;
+use crate::swift::ty::newtype::Newtype;
+use crate::swift::ty::object::Object;
+use crate::swift::ty::optional::Optional;
 use crate::swift::ty::str::Str;
 use crate::swift::ty::struct_::Struct;
+use crate::swift::ty::tuple::Tuple;
 use crate::swift::ty::vec_base::VecBase;
 use crate::swift::ty::vec_default::VecDefault;
+use crate::swift::ty::vec_enum::VecEnum;
+use crate::swift::ty::vec_nested::VecNested;
 use crate::swift::ty::vec_struct::VecStruct;
 use crate::swift::ty::void::Void;
 
@@ -19,10 +29,20 @@ pub(crate) enum ConvertEnum {
     Bool(Bool),
     Str(Str),
     Struct(Struct),
+    Enum(Enum),
     VecBase(VecBase),
     VecDefault(VecDefault),
+    VecNested(VecNested),
     VecStruct(VecStruct),
+    VecEnum(VecEnum),
+    Optional(Optional),
+    Map(Map),
+    Tuple(Tuple),
+    Newtype(Newtype),
     Callback(Callback),
+    Buffer(Buffer),
+    Cancellation(Cancellation),
+    Object(Object),
 }
 
 impl<'a> ConvertEnum {
@@ -36,10 +56,20 @@ impl<'a> ConvertEnum {
             ConvertEnum::Bool(c) => f(c),
             ConvertEnum::Str(c) => f(c),
             ConvertEnum::Struct(c) => f(c),
+            ConvertEnum::Enum(c) => f(c),
             ConvertEnum::VecBase(c) => f(c),
             ConvertEnum::VecDefault(c) => f(c),
+            ConvertEnum::VecNested(c) => f(c),
             ConvertEnum::VecStruct(c) => f(c),
+            ConvertEnum::VecEnum(c) => f(c),
+            ConvertEnum::Optional(c) => f(c),
+            ConvertEnum::Map(c) => f(c),
+            ConvertEnum::Tuple(c) => f(c),
+            ConvertEnum::Newtype(c) => f(c),
             ConvertEnum::Callback(c) => f(c),
+            ConvertEnum::Buffer(c) => f(c),
+            ConvertEnum::Cancellation(c) => f(c),
+            ConvertEnum::Object(c) => f(c),
         }
     }
 }
@@ -57,7 +87,8 @@ impl<'a> SwiftConvert {
             | AstType::Short(_)
             | AstType::Long(_)
             | AstType::Float(_)
-            | AstType::Double(_) => ConvertEnum::Basic(Basic { ty: ty.clone() }),
+            | AstType::Double(_)
+            | AstType::BigInt(_) => ConvertEnum::Basic(Basic { ty: ty.clone() }),
             AstType::Boolean => ConvertEnum::Bool(Bool {}),
             AstType::String => ConvertEnum::Str(Str {}),
             AstType::Vec(AstBaseType::Byte(_))
@@ -71,9 +102,23 @@ impl<'a> SwiftConvert {
             AstType::Vec(AstBaseType::Struct(_)) => {
                 ConvertEnum::VecStruct(VecStruct { ty: ty.clone() })
             }
+            AstType::Vec(AstBaseType::Enum(_)) => ConvertEnum::VecEnum(VecEnum { ty: ty.clone() }),
+            AstType::Vec(AstBaseType::Vec(_)) | AstType::Vec(AstBaseType::Optional(_)) => {
+                ConvertEnum::VecNested(VecNested { ty: ty.clone() })
+            }
             AstType::Vec(_) => ConvertEnum::VecDefault(VecDefault { ty: ty.clone() }),
+            AstType::Optional(_) => ConvertEnum::Optional(Optional { ty: ty.clone() }),
+            AstType::Map(_, _, _) => ConvertEnum::Map(Map { ty: ty.clone() }),
+            // Errors are passed by an out pointer, so only the value on success is converted.
+            AstType::Result(_, _) => self.get_convert(&ty.ok_type()),
             AstType::Callback(_) => ConvertEnum::Callback(Callback { ty: ty.clone() }),
             AstType::Struct(_) => ConvertEnum::Struct(Struct { ty: ty.clone() }),
+            AstType::Enum(_) => ConvertEnum::Enum(Enum { ty: ty.clone() }),
+            AstType::Tuple(_) => ConvertEnum::Tuple(Tuple { ty: ty.clone() }),
+            AstType::Newtype(_, _) => ConvertEnum::Newtype(Newtype { ty: ty.clone() }),
+            AstType::Buffer(_) => ConvertEnum::Buffer(Buffer {}),
+            AstType::Cancellation(_) => ConvertEnum::Cancellation(Cancellation {}),
+            AstType::Object(_) => ConvertEnum::Object(Object { ty: ty.clone() }),
         }
     }
 
@@ -110,6 +155,14 @@ impl<'a> Convertible<Swift<'a>> for SwiftConvert {
         self.handle(|c| c.transferable_to_rust(origin.clone(), direction.clone()))
     }
 
+    fn transferable_to_rust_borrowed(
+        &self,
+        origin: TokenStream,
+        name: &str,
+    ) -> Option<TokenStream> {
+        self.handle(|c| c.transferable_to_rust_borrowed(origin.clone(), name))
+    }
+
     fn native_type(&self) -> Swift<'a> {
         self.handle(|c| c.native_type())
     }
@@ -0,0 +1,80 @@
+use rstgen::swift::Swift;
+use rstgen::{IntoTokens, Tokens};
+
+use crate::errors::*;
+use crate::swift::types::to_swift_file;
+
+pub(crate) const DISPATCHER_CLASS: &str = "RustDispatcher";
+
+///
+/// Generate the registry of the dispatch queues running the callback methods with a `dispatch` policy.
+/// Methods whose dispatcher is not registered run inline on the rust thread.
+///
+pub(crate) struct DispatcherGen {}
+
+impl DispatcherGen {
+    pub(crate) fn gen(&self) -> Result<String> {
+        let mut class: Tokens<Swift> = Tokens::new();
+        push!(class, "import Dispatch");
+        push!(class, "import Foundation");
+        push_f!(class, "public enum {} {{", DISPATCHER_CLASS);
+        class.nested({
+            let mut members: Vec<Tokens<Swift>> = vec![];
+            let mut fields = Tokens::new();
+            push!(fields, "private static let lock = NSLock()");
+            push!(
+                fields,
+                "private static var queues : [String: DispatchQueue] = [:]"
+            );
+            members.push(fields);
+
+            let mut register = Tokens::new();
+            push!(
+                register,
+                "public static func register(_ name : String, queue : DispatchQueue) {"
+            );
+            nested!(register, "lock.lock()");
+            nested!(register, "defer { lock.unlock() }");
+            nested!(register, "queues[name] = queue");
+            push!(register, "}");
+            members.push(register);
+
+            let mut unregister = Tokens::new();
+            push!(
+                unregister,
+                "public static func unregister(_ name : String) {"
+            );
+            nested!(unregister, "lock.lock()");
+            nested!(unregister, "defer { lock.unlock() }");
+            nested!(unregister, "queues[name] = nil");
+            push!(unregister, "}");
+            members.push(unregister);
+
+            // Called by the callbacks with the converted arguments captured by the task.
+            let mut execute = Tokens::new();
+            push!(
+                execute,
+                "internal static func execute(_ name : String, _ task : @escaping () -> ()) {"
+            );
+            execute.nested({
+                let mut body: Tokens<Swift> = Tokens::new();
+                push!(body, "lock.lock()");
+                push!(body, "let queue = queues[name]");
+                push!(body, "lock.unlock()");
+                push!(body, "if let queue = queue {");
+                nested!(body, "queue.async(execute: task)");
+                push!(body, "} else {");
+                nested!(body, "task()");
+                push!(body, "}");
+                body
+            });
+            push!(execute, "}");
+            members.push(execute);
+
+            members.into_tokens().join_line_spacing()
+        });
+        push!(class, "}");
+
+        to_swift_file(class)
+    }
+}
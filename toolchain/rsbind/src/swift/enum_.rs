@@ -0,0 +1,201 @@
+use heck::{ToLowerCamelCase, ToSnakeCase};
+use rstgen::swift::Swift;
+use rstgen::Tokens;
+
+use crate::ast::contract::desc::{EnumDesc, VariantDesc};
+use crate::base::lang::{Convertible, Direction};
+use crate::errors::*;
+use crate::swift::converter::SwiftConvert;
+use crate::swift::types::{to_swift_file, SwiftType};
+
+pub(crate) struct EnumGen<'a> {
+    pub desc: &'a EnumDesc,
+}
+
+impl<'a> EnumGen<'a> {
+    pub fn gen(&self) -> Result<String> {
+        if !self.desc.is_c_like() {
+            return self.gen_data_enum();
+        }
+
+        // rstgen can't declare the raw type of an enum, so we write it by hand.
+        let mut enum_: Tokens<Swift> = Tokens::new();
+        push_f!(enum_, "public enum {} : Int32, Codable {{", &self.desc.name);
+        for variant in self.desc.variants.iter() {
+            nested_f!(
+                enum_,
+                "case {} = {}",
+                variant.name.to_lower_camel_case(),
+                variant.value
+            );
+        }
+        push!(enum_, "}");
+
+        to_swift_file(enum_)
+    }
+
+    ///
+    /// Enums with data are generated with associated values, unnamed fields stay unlabeled.
+    ///
+    fn gen_data_enum(&self) -> Result<String> {
+        let mut enum_: Tokens<Swift> = Tokens::new();
+        push_f!(enum_, "public enum {} : Codable {{", &self.desc.name);
+        let mut cases: Tokens<Swift> = Tokens::new();
+        for variant in self.desc.variants.iter() {
+            let case_name = variant.name.to_lower_camel_case();
+            if variant.fields.is_empty() {
+                push_f!(cases, "case {}", case_name);
+                continue;
+            }
+
+            let fields = variant
+                .fields
+                .iter()
+                .map(|field| {
+                    let ty = format!("{}", toks!(Swift::from(SwiftType::new(field.ty.clone()))));
+                    if variant.is_tuple {
+                        ty
+                    } else {
+                        format!("{}: {}", &field.name, ty)
+                    }
+                })
+                .collect::<Vec<String>>();
+            push_f!(cases, "case {}({})", case_name, fields.join(", "));
+        }
+
+        let mut body: Tokens<Swift> = Tokens::new();
+        body.push(cases);
+        body.push(self.create_proxy_constructor());
+        body.push(self.create_into_proxy_fn());
+        enum_.nested(body.join_line_spacing());
+        push!(enum_, "}");
+
+        to_swift_file(enum_)
+    }
+
+    fn create_proxy_constructor(&self) -> Tokens<'static, Swift<'static>> {
+        let mut body: Tokens<Swift> = Tokens::new();
+        push_f!(body, "internal init(proxy: Proxy{}) {{", &self.desc.name);
+        nested_f!(body, |t| {
+            push!(t, "switch proxy.tag {");
+            for variant in self.desc.variants.iter() {
+                let case_name = variant.name.to_lower_camel_case();
+                push_f!(t, "case {}:", variant.value);
+                if variant.fields.is_empty() {
+                    nested_f!(t, "self = .{}", case_name);
+                    continue;
+                }
+
+                let payload_name = variant.name.to_snake_case();
+                nested_f!(t, "let payload = proxy.{}.pointee", &payload_name);
+                nested_f!(
+                    t,
+                    "(proxy.free_ptr)(proxy.tag, UnsafeMutableRawPointer(proxy.{}))",
+                    &payload_name
+                );
+                let values = variant
+                    .fields
+                    .iter()
+                    .map(|field| {
+                        let convert = SwiftConvert {
+                            ty: field.ty.clone(),
+                        }
+                        .transferable_to_native(
+                            format!("payload.{}", &field.name),
+                            Direction::Down,
+                        );
+                        self.quote_value(variant, &field.name, format!("{}", convert))
+                    })
+                    .collect::<Vec<String>>();
+                nested_f!(t, "self = .{}({})", case_name, values.join(", "));
+            }
+            push!(t, "default:");
+            nested_f!(
+                t,
+                "fatalError(\"Unknown tag \\(proxy.tag) for enum {}.\")",
+                &self.desc.name
+            );
+            push!(t, "}");
+        });
+        push!(body, "}");
+        body
+    }
+
+    fn create_into_proxy_fn(&self) -> Tokens<'static, Swift<'static>> {
+        let proxy_name = format!("Proxy{}", &self.desc.name);
+        let mut body: Tokens<Swift> = Tokens::new();
+        push_f!(body, "internal func intoProxy() -> {} {{", &proxy_name);
+        nested_f!(body, |t| {
+            push!(
+                t,
+                "let free_ptr : @convention(c) (Int32, UnsafeMutableRawPointer?) -> () = { (tag, ptr) in"
+            );
+            nested!(t, "ptr?.deallocate()");
+            push!(t, "}");
+
+            let mut proxy_args = vec!["tag: 0".to_string()];
+            for variant in self.desc.variants.iter() {
+                if !variant.fields.is_empty() {
+                    proxy_args.push(format!("{}: nil", variant.name.to_snake_case()));
+                }
+            }
+            proxy_args.push("free_ptr: free_ptr".to_string());
+            push_f!(t, "var proxy = {}({})", &proxy_name, proxy_args.join(", "));
+
+            push!(t, "switch self {");
+            for variant in self.desc.variants.iter() {
+                let case_name = variant.name.to_lower_camel_case();
+                if variant.fields.is_empty() {
+                    push_f!(t, "case .{}:", case_name);
+                    nested_f!(t, "proxy.tag = {}", variant.value);
+                    continue;
+                }
+
+                let names = variant
+                    .fields
+                    .iter()
+                    .map(|field| field.name.clone())
+                    .collect::<Vec<String>>();
+                push_f!(t, "case let .{}({}):", case_name, names.join(", "));
+                let payload_name = variant.name.to_snake_case();
+                let payload_ty = format!("{}{}", &proxy_name, &variant.name);
+                let values = variant
+                    .fields
+                    .iter()
+                    .map(|field| {
+                        let convert = SwiftConvert {
+                            ty: field.ty.clone(),
+                        }
+                        .native_to_transferable(field.name.clone(), Direction::Down);
+                        format!("{}: {}", &field.name, convert)
+                    })
+                    .collect::<Vec<String>>();
+                nested_f!(
+                    t,
+                    "let payload = UnsafeMutablePointer<{}>.allocate(capacity: 1)",
+                    &payload_ty
+                );
+                nested_f!(
+                    t,
+                    "payload.initialize(to: {}({}))",
+                    &payload_ty,
+                    values.join(", ")
+                );
+                nested_f!(t, "proxy.tag = {}", variant.value);
+                nested_f!(t, "proxy.{} = payload", &payload_name);
+            }
+            push!(t, "}");
+            push!(t, "return proxy");
+        });
+        push!(body, "}");
+        body
+    }
+
+    fn quote_value(&self, variant: &VariantDesc, name: &str, value: String) -> String {
+        if variant.is_tuple {
+            value
+        } else {
+            format!("{}: {}", name, value)
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use rstgen::swift::{self, *};
+use rstgen::IntoTokens;
+
+use crate::ast::types::AstBaseType;
+use crate::errors::*;
+use crate::swift::types::to_swift_file;
+
+///
+/// Generate the `Error` type thrown for the error of `Result<T, E>`.
+/// Structs conform to `Error` directly, and String errors are wrapped into `RustError`.
+///
+pub(crate) struct ErrorGen<'a> {
+    pub err: &'a AstBaseType,
+}
+
+impl<'a> ErrorGen<'a> {
+    pub fn gen(&self) -> Result<(String, String)> {
+        match self.err {
+            AstBaseType::String => {
+                let mut struct_ = swift::Struct::new("RustError");
+                struct_.modifiers = vec![Modifier::Public];
+                struct_.implements.push(local("Error"));
+
+                let mut field = Field::new(local("String"), "message");
+                field.modifiers = vec![Modifier::Public];
+                struct_.fields.push(field);
+
+                let mut constructor = Constructor::new();
+                constructor.modifiers = vec![Modifier::Public];
+                constructor
+                    .arguments
+                    .push(Argument::new(local("String"), "message"));
+                push!(constructor.body, "self.message = message");
+                struct_.constructors.push(constructor);
+
+                Ok((
+                    "RustError".to_string(),
+                    to_swift_file(struct_.into_tokens())?,
+                ))
+            }
+            _ => {
+                let mut extension = Extension::new(local(self.err.origin()));
+                extension.modifiers = vec![];
+                extension.implements.push(local("Error"));
+                Ok((
+                    format!("{}+Error", self.err.origin()),
+                    to_swift_file(extension.into_tokens())?,
+                ))
+            }
+        }
+    }
+}
@@ -3,9 +3,11 @@ use rstgen::swift::{self, *};
 use rstgen::{IntoTokens, Tokens};
 
 use crate::ast::contract::desc::{MethodDesc, TraitDesc};
+use crate::ast::types::{AstBaseType, AstType};
 use crate::base::lang::{Convertible, Direction};
 use crate::errors::*;
 use crate::swift::converter::SwiftConvert;
+use crate::swift::panic::PANIC_ERROR;
 use crate::swift::types::to_swift_file;
 
 pub(crate) struct TraitGen<'a> {
@@ -22,8 +24,14 @@ impl<'a> TraitGen<'a> {
         let mut tokens = toks!();
         self.fill_global_block(&mut tokens)?;
 
-        // let mut sel_callbacks = vec![];
-        for method in self.desc.methods.iter() {
+        // Async methods pass the completion callback to rust instead of returning the result.
+        let bridge_methods = self
+            .desc
+            .methods
+            .iter()
+            .map(|method| method.to_bridge(self.desc))
+            .collect::<Vec<MethodDesc>>();
+        for method in bridge_methods.iter() {
             println!("generate swift codes for {}", &method.name);
             // Method signature
             let mut m = self.fill_method_sig(method)?;
@@ -52,9 +60,16 @@ impl<'a> TraitGen<'a> {
         Ok(())
     }
 
-    fn fill_method_sig(&self, method: &MethodDesc) -> Result<Method> {
+    pub(crate) fn fill_method_sig(&self, method: &MethodDesc) -> Result<Method> {
         let mut m = Method::new(method.name.to_lower_camel_case());
-        m.modifiers = vec![Modifier::Internal, Modifier::Static];
+        // Methods of objects are called on the instance holding the handle.
+        m.modifiers = if self.desc.is_object {
+            vec![Modifier::Public]
+        } else {
+            vec![Modifier::Internal, Modifier::Static]
+        };
+        // Every call throws `RustPanicError` when rust panics.
+        m.throws = true;
         m.returns(
             SwiftConvert {
                 ty: method.return_type.clone(),
@@ -74,10 +89,10 @@ impl<'a> TraitGen<'a> {
         Ok(m)
     }
 
-    fn fill_arg_convert<'b>(
-        &'a self,
-        method_body: &'b mut Tokens<'a, Swift<'a>>,
-        method: &'a MethodDesc,
+    pub(crate) fn fill_arg_convert(
+        &self,
+        method_body: &mut Tokens<Swift>,
+        method: &MethodDesc,
     ) -> Result<()> {
         for arg in method.args.iter() {
             // Argument convert
@@ -89,7 +104,7 @@ impl<'a> TraitGen<'a> {
         Ok(())
     }
 
-    fn fill_call_native_method(
+    pub(crate) fn fill_call_native_method(
         &self,
         method_body: &mut Tokens<Swift>,
         method: &MethodDesc,
@@ -99,26 +114,138 @@ impl<'a> TraitGen<'a> {
             &self.desc.mod_name, &self.desc.name, &method.name
         );
         println!("quote method call for {}", method_name);
-        push_f!(method_body, "let result = {}(", method_name);
 
-        for (index, item) in method.args.clone().into_iter().enumerate() {
-            let converted = format!("s_{}", &item.name);
-            if index == method.args.len() - 1 {
-                method_body.append(toks!(converted));
+        let mut converted = method
+            .args
+            .iter()
+            .map(|item| format!("s_{}", &item.name))
+            .collect::<Vec<String>>();
+        if self.desc.is_object && !method.is_static {
+            converted.insert(0, "handle".to_string());
+        }
+
+        // Panics are reported by the status of the call, which is checked before the results are read.
+        let mut check_status: Tokens<Swift> = Tokens::new();
+        push!(check_status, "if rust_status.pointee.code != 0 {");
+        nested!(check_status, "let message = rust_status.pointee.message");
+        let message = SwiftConvert {
+            ty: AstType::String,
+        }
+        .transferable_to_native("message".to_string(), Direction::Down);
+        nested!(
+            check_status,
+            "throw ",
+            PANIC_ERROR,
+            "(message: ",
+            message,
+            ")"
+        );
+        push!(check_status, "}");
+
+        if let Some(err) = method.return_type.err_type() {
+            // Result is returned by out pointers, and the return value is the status.
+            let ok_ty = SwiftConvert {
+                ty: method.return_type.ok_type(),
+            };
+            if method.return_type.ok_type() != AstType::Void {
+                push!(
+                    method_body,
+                    "let ok_out = UnsafeMutablePointer<",
+                    ok_ty.native_transferable_type(Direction::Down),
+                    ">.allocate(capacity: 1)"
+                );
+                converted.push("ok_out".to_string());
+            }
+            let err_ty = SwiftConvert {
+                ty: AstType::from(err.clone()),
+            };
+            push!(
+                method_body,
+                "let err_out = UnsafeMutablePointer<",
+                err_ty.native_transferable_type(Direction::Down),
+                ">.allocate(capacity: 1)"
+            );
+            converted.push("err_out".to_string());
+            push!(
+                method_body,
+                "let rust_status = UnsafeMutablePointer<RustStatus>.allocate(capacity: 1)"
+            );
+            converted.push("rust_status".to_string());
+            push!(method_body, "defer {");
+            if method.return_type.ok_type() != AstType::Void {
+                nested!(method_body, "ok_out.deallocate()");
+            }
+            nested!(method_body, "err_out.deallocate()");
+            nested!(method_body, "rust_status.deallocate()");
+            push!(method_body, "}");
+
+            push_f!(
+                method_body,
+                "let status = {}({})",
+                method_name,
+                converted.join(", ")
+            );
+            method_body.push(check_status);
+            push!(method_body, "if status != 0 {");
+            nested!(method_body, "let error = err_out.pointee");
+            let convert = err_ty.transferable_to_native("error".to_string(), Direction::Down);
+            match err {
+                AstBaseType::String => {
+                    nested!(method_body, "throw RustError(message: ", convert, ")");
+                }
+                _ => {
+                    nested!(method_body, "throw ", convert);
+                }
+            }
+            push!(method_body, "}");
+            if method.return_type.ok_type() != AstType::Void {
+                push!(method_body, "let result = ok_out.pointee");
             } else {
-                method_body.append(toks!(converted, ", "));
+                push!(method_body, "let result = ()");
             }
+            return Ok(());
+        }
+
+        // Values are returned by an out pointer, which is not written when rust panics.
+        let ok_ty = SwiftConvert {
+            ty: method.return_type.clone(),
+        };
+        if method.return_type != AstType::Void {
+            push!(
+                method_body,
+                "let ok_out = UnsafeMutablePointer<",
+                ok_ty.native_transferable_type(Direction::Down),
+                ">.allocate(capacity: 1)"
+            );
+            converted.push("ok_out".to_string());
+        }
+        push!(
+            method_body,
+            "let rust_status = UnsafeMutablePointer<RustStatus>.allocate(capacity: 1)"
+        );
+        converted.push("rust_status".to_string());
+        push!(method_body, "defer {");
+        if method.return_type != AstType::Void {
+            nested!(method_body, "ok_out.deallocate()");
         }
+        nested!(method_body, "rust_status.deallocate()");
+        push!(method_body, "}");
 
-        method_body.append(")");
+        push_f!(method_body, "{}({})", method_name, converted.join(", "));
+        method_body.push(check_status);
+        if method.return_type != AstType::Void {
+            push!(method_body, "let result = ok_out.pointee");
+        } else {
+            push!(method_body, "let result = ()");
+        }
         Ok(())
     }
 
-    fn fill_return_type_convert(
+    pub(crate) fn fill_return_type_convert(
         &self,
-        method_body: &mut Tokens<'a, Swift<'a>>,
-        method: &'a MethodDesc,
-        _callbacks: &'a [TraitDesc],
+        method_body: &mut Tokens<Swift>,
+        method: &MethodDesc,
+        _callbacks: &[TraitDesc],
     ) -> Result<()> {
         let convert = SwiftConvert {
             ty: method.return_type.clone(),
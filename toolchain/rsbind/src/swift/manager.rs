@@ -1,7 +1,10 @@
 use rstgen::swift::{Class, Method, Modifier, Swift};
 use rstgen::{swift, IntoTokens, Tokens};
 
+use crate::ast::types::AstType;
+use crate::base::lang::{Convertible, Direction};
 use crate::errors::*;
+use crate::swift::converter::SwiftConvert;
 use crate::swift::types::to_swift_file;
 use crate::AstResult;
 
@@ -16,7 +19,7 @@ impl<'a> ManagerGen<'a> {
         for desc in self.ast.traits.iter() {
             let descs = desc.1;
             for each in descs.iter() {
-                if !each.is_callback {
+                if each.is_service() {
                     let method_name = format!("new{}", &each.name);
                     let mut method = Method::new(method_name.clone());
                     method.modifiers = vec![Modifier::Public, Modifier::Static];
@@ -29,6 +32,18 @@ impl<'a> ManagerGen<'a> {
             }
         }
 
+        // Json of the callbacks implemented by rust and alive in native code, for finding leaks.
+        let mut method = Method::new("callbackStats");
+        method.modifiers = vec![Modifier::Public, Modifier::Static];
+        method.returns = Some(swift::local("String"));
+        let convert = SwiftConvert {
+            ty: AstType::String,
+        }
+        .transferable_to_native("result".to_string(), Direction::Down);
+        push!(method.body, "let result = rsbind_callback_stats()");
+        push_f!(method.body, "return {}", convert);
+        class.methods.push(method);
+
         to_swift_file(class.into_tokens())
     }
 }
@@ -4,24 +4,42 @@ use crate::base::lang::{LangGen, LangImp, ModContext};
 use crate::errors::*;
 use crate::swift::artifact::SwiftCodeGen;
 use crate::{ident, AstResult};
-use bridge::SwiftImp;
+use bridge::{quote_result_return_convert, SwiftImp};
 use rstgen::swift::Swift;
 use std::path::Path;
 mod artifact;
 mod bridge;
 mod callback;
+mod cancellation;
+mod constants;
 mod converter;
+mod dispatcher;
+mod enum_;
+mod error;
 mod internal;
 mod manager;
+mod object;
+mod panic;
 mod protocol;
 mod struct_;
 mod ty;
 mod types;
 mod wrapper;
 
+/// Leading comment of the generated C header, describing how panics are reported.
+pub(crate) const C_HEADER: &str = "\
+/*
+ * The functions of the contract traits take a `RustStatus *status` as the last argument.
+ * `code` is 0 when the call succeeds and 1 when rust panics, `message` then holds the panic
+ * message and is released with `free_str`. `status` may be NULL to ignore the panic.
+ * Values are returned through the `ok_out` pointer before `status`, which is left untouched
+ * when rust panics. Other functions abort the process when rust panics.
+ */";
+
 pub(crate) struct SwiftGen {
     pub(crate) crate_name: String,
     pub(crate) ast: AstResult,
+    pub(crate) executor: Option<String>,
 }
 
 impl LangGen for SwiftGen {
@@ -42,6 +60,10 @@ impl LangGen for SwiftGen {
             .trait_generator
             .trait_method_generator
             .quote_method_return_convert = Box::new(move |ctx| {
+            if ctx.method.return_type.err_type().is_some() {
+                return quote_result_return_convert(ctx);
+            }
+
             let obtain_index = if let AstType::Callback(_) = ctx.method.return_type.clone() {
                 quote! {
                     let callback_index = {
@@ -77,11 +99,32 @@ impl LangGen for SwiftGen {
                 quote! {}
             };
 
+            // Values are written to the out pointer, so nothing is returned when rust panics.
+            let write_result = if ctx.method.return_type == AstType::Void {
+                quote! {r_result}
+            } else {
+                quote! {unsafe { ok_out.write(r_result) };}
+            };
+
             Ok(quote! {
                 #obtain_index
                 #return_convert
                 #insert_callback
-                r_result
+                #write_result
+            })
+        });
+
+        generator
+            .bridge_file_generator
+            .bridge_code_generator
+            .trait_generator
+            .trait_method_generator
+            .quote_method_body = Box::new(|_ctx, body| {
+            Ok(quote! {
+                crate::common::catch_panic(status, || {
+                    #body
+                })
+                .unwrap_or_default()
             })
         });
 
@@ -91,6 +134,7 @@ impl LangGen for SwiftGen {
             bridge_dir: path,
             crate_name: self.crate_name.clone(),
             lang_imp: Box::new(SwiftImp {}),
+            executor: self.executor.clone(),
             extra: (),
             generator,
         }
@@ -100,7 +144,7 @@ impl LangGen for SwiftGen {
     fn gen_native(&self, path: &Path) -> Result<()> {
         SwiftCodeGen {
             swift_gen_dir: &path.to_path_buf(),
-            ast: &self.ast,
+            ast: &self.ast.without_newtypes(),
         }
         .gen_files()
     }
@@ -0,0 +1,183 @@
+use rstgen::swift::{self, *};
+use rstgen::{IntoTokens, Tokens};
+
+use crate::ast::contract::desc::{MethodDesc, SequenceKind, TraitDesc};
+use crate::ast::types::AstType;
+use crate::base::lang::Convertible;
+use crate::errors::*;
+use crate::swift::converter::SwiftConvert;
+use crate::swift::internal::TraitGen;
+use crate::swift::types::to_swift_file;
+
+///
+/// Generate the class for an object trait implemented by rust.
+/// The class holds the handle of the boxed trait object, which is freed in `deinit`.
+/// Instance services are created by public initializers instead of being returned from rust.
+/// Objects pulling the items of an iterator or a stream are also a `Sequence` or an `AsyncSequence`.
+///
+pub(crate) struct ObjectGen<'a> {
+    pub desc: &'a TraitDesc,
+}
+
+impl<'a> ObjectGen<'a> {
+    pub fn gen(&self) -> Result<String> {
+        let mut class = Class::new(self.desc.name.clone());
+        class.modifiers = vec![Modifier::Public];
+
+        let handle = Field::new(swift::local("Int64"), "handle");
+        class.fields.push(handle);
+
+        if !self.desc.has_constructor() {
+            let mut constructor = Constructor::new();
+            constructor.modifiers = vec![Modifier::Internal];
+            constructor
+                .arguments
+                .push(swift::Argument::new(swift::local("Int64"), "handle"));
+            push!(constructor.body, "self.handle = handle");
+            class.constructors.push(constructor);
+        }
+
+        let callbacks = vec![];
+        let inner = TraitGen {
+            desc: self.desc,
+            callbacks: &callbacks,
+        };
+        // Async methods pass the completion callback to rust instead of returning the result.
+        let bridge_methods = self
+            .desc
+            .methods
+            .iter()
+            .map(|method| method.to_bridge(self.desc))
+            .collect::<Vec<MethodDesc>>();
+        for method in bridge_methods.iter() {
+            if self.desc.is_constructor(method) {
+                let sig = inner.fill_method_sig(method)?;
+                let mut constructor = Constructor::new();
+                constructor.arguments = sig.arguments;
+                constructor.throws = sig.throws;
+                inner.fill_arg_convert(&mut constructor.body, method)?;
+                inner.fill_call_native_method(&mut constructor.body, method)?;
+                push!(constructor.body, "self.handle = result");
+                class.constructors.push(constructor);
+                continue;
+            }
+
+            let mut m = inner.fill_method_sig(method)?;
+            let mut body: Tokens<Swift> = Tokens::new();
+            inner.fill_arg_convert(&mut body, method)?;
+            inner.fill_call_native_method(&mut body, method)?;
+            inner.fill_return_type_convert(&mut body, method, &callbacks)?;
+            m.body = body;
+            class.methods.push(m);
+        }
+
+        let mut body: Tokens<Swift> = Tokens::new();
+        body.push("deinit {");
+        nested_f!(
+            body,
+            "{}_{}_free_object(handle)",
+            &self.desc.mod_name,
+            &self.desc.name
+        );
+        body.push("}");
+        class.body = body;
+
+        let mut tokens: Vec<Tokens<Swift>> = vec![class.into_tokens()];
+        match (self.desc.sequence, self.desc.sequence_item()) {
+            (Some(SequenceKind::Iterator), Some(item)) => tokens.push(self.quote_sequence(item)),
+            (Some(SequenceKind::Stream), Some(item)) => {
+                tokens.push(self.quote_async_sequence(item))
+            }
+            _ => (),
+        }
+
+        to_swift_file(tokens.into_tokens().join_line_spacing())
+    }
+
+    ///
+    /// Iterators are their own iterator, so the items are pulled once.
+    /// `next()` of a `Sequence` can't throw, so a panic pulling an item is a fatal error.
+    ///
+    fn quote_sequence(&self, item: AstType) -> Tokens<'static, Swift<'static>> {
+        let item = SwiftConvert { ty: item }.native_type();
+        let mut extension = Tokens::new();
+        push_f!(
+            extension,
+            "extension {} : Sequence, IteratorProtocol {{",
+            &self.desc.name
+        );
+        extension.nested({
+            let mut next = Tokens::new();
+            push!(next, "public func next() -> ", item, "? {");
+            nested!(next, "return try! nextItem()");
+            push!(next, "}");
+            next
+        });
+        push!(extension, "}");
+        extension
+    }
+
+    ///
+    /// Streams are their own async iterator, each `next()` pulls one item from rust.
+    ///
+    fn quote_async_sequence(&self, item: AstType) -> Tokens<'static, Swift<'static>> {
+        let item = SwiftConvert { ty: item }.native_type();
+        let completion = self
+            .desc
+            .methods
+            .first()
+            .map(|method| method.completion_name(self.desc))
+            .unwrap_or_default();
+
+        let mut functions: Vec<Tokens<Swift>> = vec![];
+        functions.push(toks!("public typealias Element = ", item.clone()));
+
+        let mut function = Tokens::new();
+        push_f!(
+            function,
+            "public func makeAsyncIterator() -> {} {{",
+            &self.desc.name
+        );
+        nested!(function, "return self");
+        push!(function, "}");
+        functions.push(function);
+
+        let mut function = Tokens::new();
+        push!(function, "public func next() async throws -> ", item, "? {");
+        function.nested({
+            let mut body = Tokens::new();
+            push!(
+                body,
+                "return try await withCheckedThrowingContinuation { continuation in"
+            );
+            body.nested({
+                let mut call = Tokens::new();
+                push!(call, "do {");
+                nested_f!(
+                    call,
+                    "try self.nextItem(completion : {}Handler(handler : {{ result in continuation.resume(with: result) }}))",
+                    completion
+                );
+                push!(call, "} catch {");
+                nested!(call, "continuation.resume(throwing: error)");
+                push!(call, "}");
+                call
+            });
+            push!(body, "}");
+            body
+        });
+        push!(function, "}");
+        functions.push(function);
+
+        let mut extension = Tokens::new();
+        push!(extension, "@available(iOS 13.0, macOS 10.15, *)");
+        push_f!(
+            extension,
+            "extension {} : AsyncSequence, AsyncIteratorProtocol {{",
+            &self.desc.name
+        );
+        extension.nested(functions.into_tokens().join_line_spacing());
+        push!(extension, "}");
+        extension
+    }
+}
@@ -0,0 +1,35 @@
+use rstgen::swift::{self, *};
+use rstgen::IntoTokens;
+
+use crate::errors::*;
+use crate::swift::types::to_swift_file;
+
+pub(crate) const PANIC_ERROR: &str = "RustPanicError";
+
+///
+/// Generate the `Error` thrown by the methods of the traits implemented by rust when rust panics,
+/// with the panic message reported by the status of the call.
+///
+pub(crate) struct PanicGen {}
+
+impl PanicGen {
+    pub(crate) fn gen(&self) -> Result<String> {
+        let mut struct_ = swift::Struct::new(PANIC_ERROR);
+        struct_.modifiers = vec![Modifier::Public];
+        struct_.implements.push(local("Error"));
+
+        let mut field = Field::new(local("String"), "message");
+        field.modifiers = vec![Modifier::Public];
+        struct_.fields.push(field);
+
+        let mut constructor = Constructor::new();
+        constructor.modifiers = vec![Modifier::Public];
+        constructor
+            .arguments
+            .push(Argument::new(local("String"), "message"));
+        push!(constructor.body, "self.message = message");
+        struct_.constructors.push(constructor);
+
+        to_swift_file(struct_.into_tokens())
+    }
+}
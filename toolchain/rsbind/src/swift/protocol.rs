@@ -1,12 +1,13 @@
 use heck::ToLowerCamelCase;
 use rstgen::swift::{self, *};
-use rstgen::IntoTokens;
+use rstgen::{IntoTokens, Tokens};
 
 use crate::ast::contract::desc::{MethodDesc, TraitDesc};
+use crate::ast::types::AstType;
 use crate::base::lang::Convertible;
 use crate::errors::*;
 use crate::swift::converter::SwiftConvert;
-use crate::swift::types::to_swift_file;
+use crate::swift::types::{completion_handler, to_swift_file};
 
 pub(crate) struct ProtocolGen<'a> {
     pub desc: &'a TraitDesc,
@@ -16,11 +17,19 @@ impl<'a> ProtocolGen<'a> {
     pub fn gen(&self) -> Result<String> {
         let mut class = Protocol::new(self.desc.name.clone());
         class.modifiers = vec![Modifier::Public];
+        for supertrait in self.desc.supertraits.iter() {
+            class.extends.append(swift::local(supertrait.clone()));
+        }
 
         let mut tokens = toks!();
 
-        // let mut sel_callbacks = vec![];
-        for method in self.desc.methods.iter() {
+        // Inherited methods are declared by the protocols of the supertraits.
+        for method in self
+            .desc
+            .methods
+            .iter()
+            .filter(|method| method.supertrait.is_none())
+        {
             println!("generate swift protocol method for {}", &method.name);
             // Method signature
             let m = self.fill_method_sig(method)?;
@@ -28,6 +37,7 @@ impl<'a> ProtocolGen<'a> {
         }
 
         tokens.push(class.into_tokens());
+        self.fill_async_extension(&mut tokens);
 
         to_swift_file(tokens)
     }
@@ -35,12 +45,16 @@ impl<'a> ProtocolGen<'a> {
     fn fill_method_sig(&self, method: &MethodDesc) -> Result<Method> {
         let mut m = Method::new(method.name.to_lower_camel_case());
         m.modifiers = vec![];
-        m.returns(
-            SwiftConvert {
-                ty: method.return_type.clone(),
-            }
-            .native_type(),
-        );
+        // The calls throw `RustPanicError` when rust panics.
+        m.throws = true;
+        if !method.is_async {
+            m.returns(
+                SwiftConvert {
+                    ty: method.return_type.clone(),
+                }
+                .native_type(),
+            );
+        }
 
         let args = method.args.clone();
         for arg in args.iter() {
@@ -50,7 +64,130 @@ impl<'a> ProtocolGen<'a> {
             );
             m.arguments.push(argument);
         }
+        // Async methods are required with a completion handler, the async variants are extensions.
+        if method.is_async {
+            m.arguments.push(completion_argument(method));
+        }
 
         Ok(m)
     }
+
+    ///
+    /// The `async` variants of the async methods, which wait for the completion handler.
+    ///
+    fn fill_async_extension(&self, tokens: &mut Tokens<Swift>) {
+        let methods = self
+            .desc
+            .methods
+            .iter()
+            .filter(|method| method.is_async && method.supertrait.is_none())
+            .collect::<Vec<&MethodDesc>>();
+        if methods.is_empty() {
+            return;
+        }
+
+        let mut extension = Tokens::new();
+        push!(extension, "@available(iOS 13.0, macOS 10.15, *)");
+        push_f!(extension, "public extension {} {{", &self.desc.name);
+        let mut functions: Vec<Tokens<Swift>> = vec![];
+        for method in methods.into_iter() {
+            let ok_type = method.return_type.ok_type();
+            let args = method
+                .args
+                .iter()
+                .map(|arg| {
+                    let ty = SwiftConvert { ty: arg.ty.clone() }.native_type();
+                    format!("{} : {}", &arg.name, toks!(ty))
+                })
+                .collect::<Vec<String>>();
+            let mut sig = format!(
+                "func {}({}) async throws",
+                method.name.to_lower_camel_case(),
+                args.join(", ")
+            );
+            if ok_type != AstType::Void {
+                let ty = SwiftConvert {
+                    ty: ok_type.clone(),
+                }
+                .native_type();
+                sig.push_str(&format!(" -> {}", toks!(ty)));
+            }
+
+            let mut call_args = method
+                .args
+                .iter()
+                .map(|arg| format!("{} : {}, ", &arg.name, &arg.name))
+                .collect::<String>();
+            call_args.push_str("completion : ");
+
+            // The panic of starting the method is thrown like the error of the result.
+            let mut body = Tokens::new();
+            push!(
+                body,
+                "return try await withCheckedThrowingContinuation { continuation in"
+            );
+            body.nested({
+                let mut call = Tokens::new();
+                push!(call, "do {");
+                nested_f!(
+                    call,
+                    "try self.{}({}{})",
+                    method.name.to_lower_camel_case(),
+                    call_args,
+                    "{ result in continuation.resume(with: result) }"
+                );
+                push!(call, "} catch {");
+                nested!(call, "continuation.resume(throwing: error)");
+                push!(call, "}");
+                call
+            });
+            push!(body, "}");
+
+            // Cancelling the task cancels the tokens passed to the rust future.
+            let cancellables = method
+                .args
+                .iter()
+                .filter(|arg| matches!(arg.ty, AstType::Cancellation(_)))
+                .collect::<Vec<_>>();
+            if !cancellables.is_empty() {
+                let mut handler = Tokens::new();
+                push_f!(
+                    handler,
+                    "{}try await withTaskCancellationHandler(operation: {{",
+                    if ok_type != AstType::Void {
+                        "return "
+                    } else {
+                        ""
+                    }
+                );
+                handler.nested(body);
+                push!(handler, "}, onCancel: {");
+                for arg in cancellables.into_iter() {
+                    nested_f!(handler, "{}.cancel()", &arg.name);
+                }
+                push!(handler, "})");
+                body = handler;
+            }
+
+            let mut function = Tokens::new();
+            push_f!(function, "{} {{", sig);
+            function.nested(body);
+            push!(function, "}");
+            functions.push(function);
+        }
+        extension.nested(functions.into_tokens().join_line_spacing());
+        push!(extension, "}");
+        tokens.push(extension);
+    }
+}
+
+///
+/// The escaping completion handler receiving the result of an async method.
+///
+pub(crate) fn completion_argument(method: &MethodDesc) -> swift::Argument<'static> {
+    let handler = completion_handler(method.return_type.ok_type());
+    swift::Argument::new(
+        swift::local(format!("@escaping {}", toks!(handler))),
+        "completion",
+    )
 }
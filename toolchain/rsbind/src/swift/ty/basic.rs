@@ -5,6 +5,7 @@ use rstgen::{swift, Tokens};
 use crate::ast::types::AstType;
 use crate::base::lang::{Convertible, Direction};
 use crate::ident;
+use crate::swift::types::integer_name;
 
 pub(crate) struct Bool {}
 
@@ -72,10 +73,11 @@ impl Basic {
     fn native_type_str(&self) -> String {
         match self.ty.clone() {
             AstType::Void => "()",
-            AstType::Byte(_) => "Int8",
-            AstType::Short(_) => "Int16",
-            AstType::Int(_) => "Int32",
-            AstType::Long(_) => "Int64",
+            AstType::Byte(ref origin)
+            | AstType::Short(ref origin)
+            | AstType::Int(ref origin)
+            | AstType::Long(ref origin)
+            | AstType::BigInt(ref origin) => integer_name(origin),
             AstType::Float(_) => "Float32",
             AstType::Double(_) => "Float64",
             _ => panic!("Error type found."),
@@ -125,10 +127,16 @@ impl<'a> Convertible<Swift<'a>> for Basic {
 
     fn rust_transferable_type(&self, _direction: Direction) -> TokenStream {
         match self.ty.clone() {
-            AstType::Byte(_) => quote!(i8),
-            AstType::Short(_) => quote!(i16),
-            AstType::Int(_) => quote!(i32),
-            AstType::Long(_) => quote!(i64),
+            // Integers are transferred as they are, cbindgen turns them into the C integers
+            // like `uint32_t` and `uintptr_t`, which are the native integers in swift.
+            AstType::Byte(ref origin)
+            | AstType::Short(ref origin)
+            | AstType::Int(ref origin)
+            | AstType::Long(ref origin)
+            | AstType::BigInt(ref origin) => {
+                let origin_ident = ident!(origin);
+                quote!(#origin_ident)
+            }
             AstType::Float(_) => quote!(f32),
             AstType::Double(_) => quote!(f64),
             _ => quote! {},
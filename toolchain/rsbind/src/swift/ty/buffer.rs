@@ -0,0 +1,86 @@
+use proc_macro2::TokenStream;
+use rstgen::swift::Swift;
+use rstgen::{swift, Tokens};
+
+use crate::base::lang::{Convertible, Direction};
+
+///
+/// `bytes::Bytes` is boxed without copying and transferred with its pointer and length.
+/// The swift `Data` is backed by rust memory and frees the handle in its deallocator.
+///
+pub(crate) struct Buffer {}
+
+impl<'a> Convertible<Swift<'a>> for Buffer {
+    fn native_to_transferable(
+        &self,
+        origin: String,
+        _direction: Direction,
+    ) -> Tokens<'static, Swift<'a>> {
+        toks_f!("{}", origin)
+    }
+
+    fn transferable_to_native(
+        &self,
+        origin: String,
+        _direction: Direction,
+    ) -> Tokens<'static, Swift<'a>> {
+        let mut body = Tokens::new();
+        body.append(toks!("{ () -> Data in"));
+        nested_f!(body, |t| {
+            push_f!(t, "let buffer = {}", origin);
+            push_f!(t, "guard let ptr = buffer.ptr, buffer.len > 0 else {");
+            nested_f!(t, "buffer.free_handle(buffer.handle)");
+            nested_f!(t, "return Data()");
+            push_f!(t, "}");
+            push_f!(
+                t,
+                "return Data(bytesNoCopy: UnsafeMutableRawPointer(mutating: ptr), count: Int(buffer.len), deallocator: .custom({ _, _ in buffer.free_handle(buffer.handle) }))"
+            );
+        });
+        push_f!(body, "}()");
+        body
+    }
+
+    fn rust_to_transferable(&self, origin: TokenStream, _direction: Direction) -> TokenStream {
+        // The concrete type is erased, so `free_buffer` releases it without naming it.
+        quote! {{
+                let buffer: Box<Box<dyn AsRef<[u8]> + Send>> = Box::new(Box::new(#origin));
+                let slice = AsRef::<[u8]>::as_ref(&**buffer);
+                let (ptr, len) = (slice.as_ptr(), slice.len() as i64);
+                CBuffer {
+                    ptr,
+                    len,
+                    handle: Box::into_raw(buffer) as (*mut std::os::raw::c_void),
+                    free_handle: free_buffer
+                }
+        }}
+    }
+
+    fn transferable_to_rust(&self, _origin: TokenStream, _direction: Direction) -> TokenStream {
+        quote! {}
+    }
+
+    fn native_type(&self) -> Swift<'a> {
+        swift::local("Data")
+    }
+
+    fn native_transferable_type(&self, _direction: Direction) -> Swift<'a> {
+        swift::local("CBuffer")
+    }
+
+    fn rust_transferable_type(&self, _direction: Direction) -> TokenStream {
+        quote!(CBuffer)
+    }
+
+    fn quote_common_in_bridge(&self) -> TokenStream {
+        quote! {}
+    }
+
+    fn quote_common_in_native(&self) -> Tokens<'static, Swift<'a>> {
+        Tokens::new()
+    }
+
+    fn quote_in_common_rs(&self) -> TokenStream {
+        quote! {}
+    }
+}
@@ -0,0 +1,78 @@
+use proc_macro2::TokenStream;
+use rstgen::swift::Swift;
+use rstgen::{swift, Tokens};
+
+use crate::ast::types::AstType;
+use crate::base::lang::{Convertible, Direction};
+use crate::ident;
+
+///
+/// Enums are transferred by their raw values.
+///
+pub(crate) struct Enum {
+    pub(crate) ty: AstType,
+}
+
+impl<'a> Convertible<Swift<'a>> for Enum {
+    fn native_to_transferable(
+        &self,
+        origin: String,
+        _direction: Direction,
+    ) -> Tokens<'static, Swift<'a>> {
+        toks_f!("{}.rawValue", origin)
+    }
+
+    fn transferable_to_native(
+        &self,
+        origin: String,
+        _direction: Direction,
+    ) -> Tokens<'static, Swift<'a>> {
+        toks!(self.native_type(), "(rawValue: ", origin, ")!")
+    }
+
+    fn rust_to_transferable(&self, origin: TokenStream, _direction: Direction) -> TokenStream {
+        quote! {
+            #origin as i32
+        }
+    }
+
+    fn transferable_to_rust(&self, origin: TokenStream, _direction: Direction) -> TokenStream {
+        match self.ty.clone() {
+            AstType::Enum(ref base) => {
+                let enum_name = ident!(&base.origin);
+                let proxy_enum = ident!(&format!("Proxy{}", &base.origin));
+                quote! {
+                    #enum_name::from(#proxy_enum(#origin))
+                }
+            }
+            _ => quote! {},
+        }
+    }
+
+    fn native_type(&self) -> Swift<'a> {
+        match self.ty.clone() {
+            AstType::Enum(origin) => swift::local(origin.origin),
+            _ => swift::local(""),
+        }
+    }
+
+    fn native_transferable_type(&self, _direction: Direction) -> Swift<'a> {
+        swift::local("Int32")
+    }
+
+    fn rust_transferable_type(&self, _direction: Direction) -> TokenStream {
+        quote! {i32}
+    }
+
+    fn quote_common_in_bridge(&self) -> TokenStream {
+        quote! {}
+    }
+
+    fn quote_common_in_native(&self) -> Tokens<'static, Swift<'a>> {
+        Tokens::new()
+    }
+
+    fn quote_in_common_rs(&self) -> TokenStream {
+        quote! {}
+    }
+}
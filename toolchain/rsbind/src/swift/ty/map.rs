@@ -0,0 +1,168 @@
+use proc_macro2::TokenStream;
+use rstgen::swift::Swift;
+use rstgen::{swift, Tokens};
+
+use crate::ast::types::{AstBaseType, AstType};
+use crate::base::lang::{Convertible, Direction};
+use crate::swift::converter::SwiftConvert;
+use crate::swift::types::{c_generic_name, SwiftType};
+
+///
+/// Maps are transferred as the array of keys and the array of values in the same order,
+/// each array is converted like a `Vec`.
+///
+pub(crate) struct Map {
+    pub(crate) ty: AstType,
+}
+
+impl Map {
+    fn key_value(&self) -> (AstBaseType, AstBaseType) {
+        match self.ty.clone() {
+            AstType::Map(_, key, value) => (key, value),
+            _ => (AstBaseType::Void, AstBaseType::Void),
+        }
+    }
+
+    fn keys_convert(&self) -> SwiftConvert {
+        SwiftConvert {
+            ty: AstType::Vec(self.key_value().0),
+        }
+    }
+
+    fn values_convert(&self) -> SwiftConvert {
+        SwiftConvert {
+            ty: AstType::Vec(self.key_value().1),
+        }
+    }
+
+    ///
+    /// The name of `CMap<K, V>` in the C header generated by cbindgen.
+    ///
+    fn c_map_name(&self) -> String {
+        c_generic_name(&self.rust_transferable_type(Direction::Down))
+    }
+}
+
+impl<'a> Convertible<Swift<'a>> for Map {
+    fn native_to_transferable(
+        &self,
+        origin: String,
+        direction: Direction,
+    ) -> Tokens<'static, Swift<'a>> {
+        let (key, value) = self.key_value();
+        let mut body = Tokens::new();
+        body.append(toks_f!("{{ () -> {} in", self.c_map_name()));
+        nested_f!(
+            body,
+            "let keys: [{}] = Array({}.keys)",
+            toks!(Swift::from(SwiftType::new(AstType::from(key)))),
+            origin
+        );
+        nested_f!(
+            body,
+            "let values: [{}] = keys.map {{ each in {}[each]! }}",
+            toks!(Swift::from(SwiftType::new(AstType::from(value)))),
+            origin
+        );
+        nested_f!(
+            body,
+            "return {}(keys: {}, values: {})",
+            self.c_map_name(),
+            self.keys_convert()
+                .native_to_transferable("keys".to_string(), direction.clone()),
+            self.values_convert()
+                .native_to_transferable("values".to_string(), direction)
+        );
+        push_f!(body, "}()");
+        body
+    }
+
+    fn transferable_to_native(
+        &self,
+        origin: String,
+        direction: Direction,
+    ) -> Tokens<'static, Swift<'a>> {
+        let mut body = Tokens::new();
+        body.append(toks!("{ () -> ", self.native_type(), " in"));
+        nested_f!(
+            body,
+            "let keys = {}",
+            self.keys_convert()
+                .transferable_to_native(format!("{}.keys", origin), direction.clone())
+        );
+        nested_f!(
+            body,
+            "let values = {}",
+            self.values_convert()
+                .transferable_to_native(format!("{}.values", origin), direction)
+        );
+        nested!(
+            body,
+            "return Dictionary(uniqueKeysWithValues: zip(keys, values))"
+        );
+        push_f!(body, "}()");
+        body
+    }
+
+    fn rust_to_transferable(&self, origin: TokenStream, direction: Direction) -> TokenStream {
+        let keys = self
+            .keys_convert()
+            .rust_to_transferable(quote! {keys}, direction.clone());
+        let values = self
+            .values_convert()
+            .rust_to_transferable(quote! {values}, direction);
+        quote! {{
+            let (keys, values): (Vec<_>, Vec<_>) = #origin.into_iter().unzip();
+            CMap {
+                keys: #keys,
+                values: #values,
+            }
+        }}
+    }
+
+    fn transferable_to_rust(&self, origin: TokenStream, direction: Direction) -> TokenStream {
+        let keys = self
+            .keys_convert()
+            .transferable_to_rust(quote! {map.keys}, direction.clone());
+        let values = self
+            .values_convert()
+            .transferable_to_rust(quote! {map.values}, direction);
+        let (key, value) = self.key_value();
+        let key_ty = AstType::Vec(key).origin().parse::<TokenStream>().unwrap();
+        let value_ty = AstType::Vec(value).origin().parse::<TokenStream>().unwrap();
+        quote! {{
+            let map = #origin;
+            let keys: #key_ty = #keys;
+            let values: #value_ty = #values;
+            keys.into_iter().zip(values.into_iter()).collect()
+        }}
+    }
+
+    fn native_type(&self) -> Swift<'a> {
+        Swift::from(SwiftType::new(self.ty.clone()))
+    }
+
+    fn native_transferable_type(&self, _direction: Direction) -> Swift<'a> {
+        swift::local(self.c_map_name())
+    }
+
+    fn rust_transferable_type(&self, direction: Direction) -> TokenStream {
+        let keys = self
+            .keys_convert()
+            .rust_transferable_type(direction.clone());
+        let values = self.values_convert().rust_transferable_type(direction);
+        quote!(CMap<#keys, #values>)
+    }
+
+    fn quote_common_in_bridge(&self) -> TokenStream {
+        quote! {}
+    }
+
+    fn quote_common_in_native(&self) -> Tokens<'static, Swift<'a>> {
+        Tokens::new()
+    }
+
+    fn quote_in_common_rs(&self) -> TokenStream {
+        quote! {}
+    }
+}
@@ -0,0 +1,91 @@
+use proc_macro2::TokenStream;
+use rstgen::swift::Swift;
+use rstgen::Tokens;
+
+use crate::ast::types::{AstBaseType, AstType};
+use crate::base::bridge::quote_base_ty;
+use crate::base::lang::{Convertible, Direction};
+use crate::ident;
+use crate::swift::converter::SwiftConvert;
+
+///
+/// Transparent newtypes are transferred as their inner types,
+/// only the rust side wraps and unwraps them.
+///
+pub(crate) struct Newtype {
+    pub(crate) ty: AstType,
+}
+
+impl Newtype {
+    fn inner(&self) -> AstBaseType {
+        match self.ty.clone() {
+            AstType::Newtype(_, inner) => inner,
+            _ => AstBaseType::Void,
+        }
+    }
+
+    fn inner_convert(&self) -> SwiftConvert {
+        SwiftConvert {
+            ty: self.ty.unwrap_newtype(),
+        }
+    }
+}
+
+impl<'a> Convertible<Swift<'a>> for Newtype {
+    fn native_to_transferable(
+        &self,
+        origin: String,
+        direction: Direction,
+    ) -> Tokens<'static, Swift<'a>> {
+        self.inner_convert()
+            .native_to_transferable(origin, direction)
+    }
+
+    fn transferable_to_native(
+        &self,
+        origin: String,
+        direction: Direction,
+    ) -> Tokens<'static, Swift<'a>> {
+        self.inner_convert()
+            .transferable_to_native(origin, direction)
+    }
+
+    fn rust_to_transferable(&self, origin: TokenStream, direction: Direction) -> TokenStream {
+        self.inner_convert()
+            .rust_to_transferable(quote! {#origin.0}, direction)
+    }
+
+    fn transferable_to_rust(&self, origin: TokenStream, direction: Direction) -> TokenStream {
+        let newtype = ident!(&self.ty.origin());
+        let inner_ty = quote_base_ty(&self.inner());
+        let value = self.inner_convert().transferable_to_rust(origin, direction);
+        quote! {{
+            let value: #inner_ty = #value;
+            #newtype(value)
+        }}
+    }
+
+    fn native_type(&self) -> Swift<'a> {
+        self.inner_convert().native_type()
+    }
+
+    fn native_transferable_type(&self, direction: Direction) -> Swift<'a> {
+        self.inner_convert().native_transferable_type(direction)
+    }
+
+    fn rust_transferable_type(&self, direction: Direction) -> TokenStream {
+        self.inner_convert().rust_transferable_type(direction)
+    }
+
+    fn quote_common_in_bridge(&self) -> TokenStream {
+        quote! {}
+    }
+
+    fn quote_common_in_native(&self) -> Tokens<'static, Swift<'a>> {
+        Tokens::new()
+    }
+
+    fn quote_in_common_rs(&self) -> TokenStream {
+        quote! {}
+    }
+}
@@ -0,0 +1,66 @@
+use proc_macro2::TokenStream;
+use rstgen::swift::Swift;
+use rstgen::{swift, Tokens};
+
+use crate::ast::types::AstType;
+use crate::base::lang::{Convertible, Direction};
+
+///
+/// `Box<dyn Trait>` implemented by rust is boxed again and transferred as the handle,
+/// which is held by the generated class of the trait. Objects are only returned from rust.
+///
+pub(crate) struct Object {
+    pub(crate) ty: AstType,
+}
+
+impl<'a> Convertible<Swift<'a>> for Object {
+    fn native_to_transferable(
+        &self,
+        origin: String,
+        _direction: Direction,
+    ) -> Tokens<'static, Swift<'a>> {
+        toks_f!("{}", origin)
+    }
+
+    fn transferable_to_native(
+        &self,
+        origin: String,
+        _direction: Direction,
+    ) -> Tokens<'static, Swift<'a>> {
+        toks!(self.native_type(), "(handle: ", origin, ")")
+    }
+
+    fn rust_to_transferable(&self, origin: TokenStream, _direction: Direction) -> TokenStream {
+        quote! {
+            Box::into_raw(Box::new(#origin)) as i64
+        }
+    }
+
+    fn transferable_to_rust(&self, _origin: TokenStream, _direction: Direction) -> TokenStream {
+        quote! {}
+    }
+
+    fn native_type(&self) -> Swift<'a> {
+        swift::local(self.ty.origin())
+    }
+
+    fn native_transferable_type(&self, _direction: Direction) -> Swift<'a> {
+        swift::local("Int64")
+    }
+
+    fn rust_transferable_type(&self, _direction: Direction) -> TokenStream {
+        quote!(i64)
+    }
+
+    fn quote_common_in_bridge(&self) -> TokenStream {
+        quote! {}
+    }
+
+    fn quote_common_in_native(&self) -> Tokens<'static, Swift<'a>> {
+        Tokens::new()
+    }
+
+    fn quote_in_common_rs(&self) -> TokenStream {
+        quote! {}
+    }
+}
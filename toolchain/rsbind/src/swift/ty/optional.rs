@@ -0,0 +1,94 @@
+use proc_macro2::TokenStream;
+use rstgen::swift::Swift;
+use rstgen::{swift, Tokens};
+
+use crate::ast::types::AstType;
+use crate::base::bridge::quote_base_ty;
+use crate::base::lang::{Convertible, Direction};
+use crate::swift::converter::SwiftConvert;
+use crate::swift::types::SwiftType;
+
+///
+/// Optional values are transferred as arrays holding zero or one element,
+/// so we can reuse the array converters.
+///
+pub(crate) struct Optional {
+    pub(crate) ty: AstType,
+}
+
+impl Optional {
+    fn vec_convert(&self) -> SwiftConvert {
+        match self.ty.clone() {
+            AstType::Optional(base) => SwiftConvert {
+                ty: AstType::Vec(base),
+            },
+            _ => SwiftConvert { ty: AstType::Void },
+        }
+    }
+}
+
+impl<'a> Convertible<Swift<'a>> for Optional {
+    fn native_to_transferable(
+        &self,
+        origin: String,
+        direction: Direction,
+    ) -> Tokens<'static, Swift<'a>> {
+        let array = format!("({}.map {{ [$0] }} ?? [])", origin);
+        self.vec_convert().native_to_transferable(array, direction)
+    }
+
+    fn transferable_to_native(
+        &self,
+        origin: String,
+        direction: Direction,
+    ) -> Tokens<'static, Swift<'a>> {
+        let array = self.vec_convert().transferable_to_native(origin, direction);
+        toks!(array, ".first")
+    }
+
+    fn rust_to_transferable(&self, origin: TokenStream, direction: Direction) -> TokenStream {
+        self.vec_convert()
+            .rust_to_transferable(quote! {#origin.into_iter().collect::<Vec<_>>()}, direction)
+    }
+
+    fn transferable_to_rust(&self, origin: TokenStream, direction: Direction) -> TokenStream {
+        match self.ty.clone() {
+            AstType::Optional(base) => {
+                let base_ident = quote_base_ty(&base);
+                let convert = self.vec_convert().transferable_to_rust(origin, direction);
+                quote! {{
+                    let vec: Vec<#base_ident> = #convert;
+                    vec.into_iter().next()
+                }}
+            }
+            _ => quote! {},
+        }
+    }
+
+    fn native_type(&self) -> Swift<'a> {
+        match self.ty.clone() {
+            AstType::Optional(base) => SwiftType::new(AstType::from(base)).to_optional(),
+            _ => swift::local(""),
+        }
+    }
+
+    fn native_transferable_type(&self, direction: Direction) -> Swift<'a> {
+        self.vec_convert().native_transferable_type(direction)
+    }
+
+    fn rust_transferable_type(&self, direction: Direction) -> TokenStream {
+        self.vec_convert().rust_transferable_type(direction)
+    }
+
+    fn quote_common_in_bridge(&self) -> TokenStream {
+        quote! {}
+    }
+
+    fn quote_common_in_native(&self) -> Tokens<'static, Swift<'a>> {
+        Tokens::new()
+    }
+
+    fn quote_in_common_rs(&self) -> TokenStream {
+        quote! {}
+    }
+}
@@ -3,6 +3,7 @@ use rstgen::swift::Swift;
 use rstgen::{swift, Tokens};
 
 use crate::base::lang::{Convertible, Direction};
+use crate::ident;
 use crate::swift::ty::basic::quote_free_swift_ptr;
 
 pub(crate) struct Str {}
@@ -94,6 +95,21 @@ impl<'a> Convertible<Swift<'a>> for Str {
         }}
     }
 
+    fn transferable_to_rust_borrowed(
+        &self,
+        origin: TokenStream,
+        name: &str,
+    ) -> Option<TokenStream> {
+        let free_name = ident!(&format!("free_{}", name));
+        let name = ident!(name);
+        Some(quote! {
+            let #name = unsafe { std::slice::from_raw_parts(#origin.ptr as (*const u8), #origin.len as usize) };
+            let #name = unsafe { CStr::from_bytes_with_nul_unchecked(#name) }.to_string_lossy();
+            let #name: &str = &#name;
+            let #free_name = FreeOnDrop(move || (#origin.free_ptr)(#origin.ptr as (*mut i8), #origin.len, #origin.len));
+        })
+    }
+
     fn native_type(&self) -> Swift<'a> {
         swift::local("String")
     }
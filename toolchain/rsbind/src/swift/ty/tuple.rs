@@ -0,0 +1,155 @@
+use proc_macro2::{Ident, TokenStream};
+use rstgen::swift::Swift;
+use rstgen::{swift, Tokens};
+
+use crate::ast::types::{AstBaseType, AstType};
+use crate::base::lang::{Convertible, Direction};
+use crate::ident;
+use crate::swift::converter::SwiftConvert;
+use crate::swift::types::{c_generic_name, SwiftType};
+
+///
+/// Tuples are transferred as `CTuple2<T0, T1>` and so on, whose fields are the transferable
+/// types of the elements, and they are native tuples in swift.
+///
+pub(crate) struct Tuple {
+    pub(crate) ty: AstType,
+}
+
+impl Tuple {
+    fn elem_converts(&self) -> Vec<SwiftConvert> {
+        match self.ty.clone() {
+            AstType::Tuple(elems) => elems
+                .into_iter()
+                .map(|elem: AstBaseType| SwiftConvert {
+                    ty: AstType::from(elem),
+                })
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    fn c_tuple_name(&self) -> String {
+        c_generic_name(&self.rust_transferable_type(Direction::Down))
+    }
+}
+
+impl<'a> Convertible<Swift<'a>> for Tuple {
+    fn native_to_transferable(
+        &self,
+        origin: String,
+        direction: Direction,
+    ) -> Tokens<'static, Swift<'a>> {
+        let values = self
+            .elem_converts()
+            .iter()
+            .enumerate()
+            .map(|(index, convert)| {
+                let value =
+                    convert.native_to_transferable(format!("tuple.{}", index), direction.clone());
+                format!("_{}: {}", index, toks!(value))
+            })
+            .collect::<Vec<String>>();
+        let mut body = Tokens::new();
+        body.append(toks_f!("{{ () -> {} in", self.c_tuple_name()));
+        nested_f!(body, "let tuple = {}", origin);
+        nested_f!(
+            body,
+            "return {}({})",
+            self.c_tuple_name(),
+            values.join(", ")
+        );
+        push_f!(body, "}()");
+        body
+    }
+
+    fn transferable_to_native(
+        &self,
+        origin: String,
+        direction: Direction,
+    ) -> Tokens<'static, Swift<'a>> {
+        let values = self
+            .elem_converts()
+            .iter()
+            .enumerate()
+            .map(|(index, convert)| {
+                let value =
+                    convert.transferable_to_native(format!("tuple._{}", index), direction.clone());
+                format!("{}", toks!(value))
+            })
+            .collect::<Vec<String>>();
+        let mut body = Tokens::new();
+        body.append(toks_f!("{{ () -> {} in", toks!(self.native_type())));
+        nested_f!(body, "let tuple = {}", origin);
+        nested_f!(body, "return ({})", values.join(", "));
+        push_f!(body, "}()");
+        body
+    }
+
+    fn rust_to_transferable(&self, origin: TokenStream, direction: Direction) -> TokenStream {
+        let converts = self.elem_converts();
+        let c_tuple = ident!(&format!("CTuple{}", converts.len()));
+        let names = (0..converts.len())
+            .map(|index| ident!(&format!("elem_{}", index)))
+            .collect::<Vec<Ident>>();
+        let fields = (0..converts.len())
+            .map(|index| ident!(&format!("_{}", index)))
+            .collect::<Vec<Ident>>();
+        let values = converts
+            .iter()
+            .zip(names.iter())
+            .map(|(convert, name)| convert.rust_to_transferable(quote! {#name}, direction.clone()))
+            .collect::<Vec<TokenStream>>();
+        quote! {{
+            let (#(#names,)*) = #origin;
+            #c_tuple {
+                #(#fields: #values),*
+            }
+        }}
+    }
+
+    fn transferable_to_rust(&self, origin: TokenStream, direction: Direction) -> TokenStream {
+        let values = self
+            .elem_converts()
+            .iter()
+            .enumerate()
+            .map(|(index, convert)| {
+                let field = ident!(&format!("_{}", index));
+                convert.transferable_to_rust(quote! {tuple.#field}, direction.clone())
+            })
+            .collect::<Vec<TokenStream>>();
+        quote! {{
+            let tuple = #origin;
+            (#(#values,)*)
+        }}
+    }
+
+    fn native_type(&self) -> Swift<'a> {
+        Swift::from(SwiftType::new(self.ty.clone()))
+    }
+
+    fn native_transferable_type(&self, _direction: Direction) -> Swift<'a> {
+        swift::local(self.c_tuple_name())
+    }
+
+    fn rust_transferable_type(&self, direction: Direction) -> TokenStream {
+        let converts = self.elem_converts();
+        let c_tuple = ident!(&format!("CTuple{}", converts.len()));
+        let tys = converts
+            .iter()
+            .map(|convert| convert.rust_transferable_type(direction.clone()));
+        quote!(#c_tuple<#(#tys),*>)
+    }
+
+    fn quote_common_in_bridge(&self) -> TokenStream {
+        quote! {}
+    }
+
+    fn quote_common_in_native(&self) -> Tokens<'static, Swift<'a>> {
+        Tokens::new()
+    }
+
+    fn quote_in_common_rs(&self) -> TokenStream {
+        quote! {}
+    }
+}
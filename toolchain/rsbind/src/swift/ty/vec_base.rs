@@ -6,6 +6,7 @@ use crate::ast::types::{AstBaseType, AstType};
 use crate::base::lang::{Convertible, Direction};
 use crate::ident;
 use crate::swift::ty::basic::quote_free_swift_ptr;
+use crate::swift::types::integer_name;
 
 pub(crate) struct VecBase {
     pub(crate) ty: AstType,
@@ -19,8 +20,20 @@ impl<'a> Convertible<Swift<'a>> for VecBase {
     ) -> Tokens<'static, Swift<'a>> {
         let mut body = Tokens::new();
         let transfer_ty = self.native_transferable_type(direction);
-        let base_ty = self.native_base_type_str();
+        let base_ty = self.transfer_base_type_str();
         body.append(toks!("{ () -> ", transfer_ty.clone(), " in"));
+        // The bits of unsigned integers are kept in the signed c arrays.
+        let origin = if base_ty != self.native_base_type_str() {
+            nested_f!(
+                body,
+                "let signed = {}.map {{ {}(truncatingIfNeeded: $0) }}",
+                origin,
+                base_ty
+            );
+            "signed".to_owned()
+        } else {
+            origin
+        };
         nested_f!(
             body,
             "let tmp_ptr = UnsafeMutablePointer<{}>.allocate(capacity: {}.count)",
@@ -56,16 +69,28 @@ impl<'a> Convertible<Swift<'a>> for VecBase {
         _direction: Direction,
     ) -> Tokens<'static, Swift<'a>> {
         let mut body = Tokens::new();
-        let ty = format!("[{}]", self.native_base_type_str());
+        let native_base_ty = self.native_base_type_str();
+        let transfer_base_ty = self.transfer_base_type_str();
+        let ty = format!("[{}]", native_base_ty);
         body.append(toks_f!("{{ () -> {} in", ty));
         nested_f!(body, |t| {
-            nested_f!(
-                t,
-                "let array = {}(UnsafeBufferPointer(start: {}.ptr, count: Int({}.len)))",
-                ty,
-                origin,
-                origin
-            );
+            if native_base_ty == transfer_base_ty {
+                nested_f!(
+                    t,
+                    "let array = {}(UnsafeBufferPointer(start: {}.ptr, count: Int({}.len)))",
+                    ty,
+                    origin,
+                    origin
+                );
+            } else {
+                nested_f!(
+                    t,
+                    "let array = UnsafeBufferPointer(start: {}.ptr, count: Int({}.len)).map {{ {}(truncatingIfNeeded: $0) }}",
+                    origin,
+                    origin,
+                    native_base_ty
+                );
+            }
             nested_f!(
                 t,
                 "({}.free_ptr)(UnsafeMutablePointer(mutating: {}.ptr), {}.len, {}.cap)",
@@ -143,6 +168,24 @@ impl<'a> Convertible<Swift<'a>> for VecBase {
         }
     }
 
+    fn transferable_to_rust_borrowed(
+        &self,
+        origin: TokenStream,
+        name: &str,
+    ) -> Option<TokenStream> {
+        let base = match self.ty {
+            AstType::Vec(ref base) => ident!(&base.origin()),
+            _ => return None,
+        };
+        let transfer_ty = self.rust_base_transfer_type();
+        let free_name = ident!(&format!("free_{}", name));
+        let name = ident!(name);
+        Some(quote! {
+            let #name: &[#base] = unsafe { std::slice::from_raw_parts(#origin.ptr as (*const #base), #origin.len as usize) };
+            let #free_name = FreeOnDrop(move || (#origin.free_ptr)(#origin.ptr as (*mut #transfer_ty), #origin.len, #origin.cap));
+        })
+    }
+
     fn native_type(&self) -> Swift<'a> {
         swift::local(format!("[{}]", self.native_base_type_str()))
     }
@@ -186,6 +229,17 @@ impl<'a> Convertible<Swift<'a>> for VecBase {
 
 impl VecBase {
     fn native_base_type_str(&self) -> String {
+        match self.ty.clone() {
+            AstType::Vec(AstBaseType::Byte(ref origin))
+            | AstType::Vec(AstBaseType::Short(ref origin))
+            | AstType::Vec(AstBaseType::Int(ref origin))
+            | AstType::Vec(AstBaseType::Long(ref origin)) => integer_name(origin),
+            _ => return self.transfer_base_type_str(),
+        }
+        .to_string()
+    }
+
+    fn transfer_base_type_str(&self) -> String {
         match self.ty.clone() {
             AstType::Vec(AstBaseType::Byte(_)) => "Int8",
             AstType::Vec(AstBaseType::Short(_)) => "Int16",
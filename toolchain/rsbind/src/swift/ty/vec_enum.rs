@@ -0,0 +1,91 @@
+use proc_macro2::TokenStream;
+use rstgen::swift::Swift;
+use rstgen::{swift, Tokens};
+
+use crate::ast::types::{AstBaseType, AstType};
+use crate::base::lang::{Convertible, Direction};
+use crate::ident;
+use crate::swift::ty::vec_base::VecBase;
+
+///
+/// Enum arrays are transferred as arrays of raw values.
+///
+pub(crate) struct VecEnum {
+    pub(crate) ty: AstType,
+}
+
+impl VecEnum {
+    fn raw_convert(&self) -> VecBase {
+        VecBase {
+            ty: AstType::Vec(AstBaseType::Int("i32".to_string())),
+        }
+    }
+
+    fn enum_name(&self) -> String {
+        match self.ty.clone() {
+            AstType::Vec(AstBaseType::Enum(ref base)) => base.origin.clone(),
+            _ => "".to_string(),
+        }
+    }
+}
+
+impl<'a> Convertible<Swift<'a>> for VecEnum {
+    fn native_to_transferable(
+        &self,
+        origin: String,
+        direction: Direction,
+    ) -> Tokens<'static, Swift<'a>> {
+        let raw_values = format!("{}.map {{ $0.rawValue }}", origin);
+        self.raw_convert()
+            .native_to_transferable(raw_values, direction)
+    }
+
+    fn transferable_to_native(
+        &self,
+        origin: String,
+        direction: Direction,
+    ) -> Tokens<'static, Swift<'a>> {
+        let raw_values = self.raw_convert().transferable_to_native(origin, direction);
+        toks!(raw_values, ".map { ", self.enum_name(), "(rawValue: $0)! }")
+    }
+
+    fn rust_to_transferable(&self, origin: TokenStream, direction: Direction) -> TokenStream {
+        self.raw_convert().rust_to_transferable(
+            quote! {#origin.into_iter().map(|each| each as i32).collect::<Vec<i32>>()},
+            direction,
+        )
+    }
+
+    fn transferable_to_rust(&self, origin: TokenStream, direction: Direction) -> TokenStream {
+        let enum_name = ident!(&self.enum_name());
+        let proxy_enum = ident!(&format!("Proxy{}", &self.enum_name()));
+        let raw_values = self.raw_convert().transferable_to_rust(origin, direction);
+        quote! {
+            #raw_values.into_iter().map(|each| #enum_name::from(#proxy_enum(each))).collect::<Vec<#enum_name>>()
+        }
+    }
+
+    fn native_type(&self) -> Swift<'a> {
+        swift::local(format!("[{}]", self.enum_name()))
+    }
+
+    fn native_transferable_type(&self, direction: Direction) -> Swift<'a> {
+        self.raw_convert().native_transferable_type(direction)
+    }
+
+    fn rust_transferable_type(&self, direction: Direction) -> TokenStream {
+        self.raw_convert().rust_transferable_type(direction)
+    }
+
+    fn quote_common_in_bridge(&self) -> TokenStream {
+        quote! {}
+    }
+
+    fn quote_common_in_native(&self) -> Tokens<'static, Swift<'a>> {
+        Tokens::new()
+    }
+
+    fn quote_in_common_rs(&self) -> TokenStream {
+        quote! {}
+    }
+}
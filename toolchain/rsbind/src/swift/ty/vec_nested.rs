@@ -0,0 +1,206 @@
+use proc_macro2::TokenStream;
+use rstgen::swift::Swift;
+use rstgen::{swift, Tokens};
+
+use crate::ast::types::{AstBaseType, AstType};
+use crate::base::bridge::quote_base_ty;
+use crate::base::lang::{Convertible, Direction};
+use crate::swift::converter::SwiftConvert;
+use crate::swift::ty::basic::quote_free_swift_ptr;
+use crate::swift::types::SwiftType;
+
+///
+/// Vectors of vectors or optionals are transferred as `CArray<T>`,
+/// whose elements are the transferable types of the inner collections.
+///
+pub(crate) struct VecNested {
+    pub(crate) ty: AstType,
+}
+
+impl VecNested {
+    fn inner(&self) -> AstBaseType {
+        match self.ty.clone() {
+            AstType::Vec(base) => base,
+            _ => AstBaseType::Void,
+        }
+    }
+
+    fn inner_convert(&self) -> SwiftConvert {
+        SwiftConvert {
+            ty: AstType::from(self.inner()),
+        }
+    }
+
+    fn inner_transferable_str(&self) -> String {
+        format!(
+            "{}",
+            toks!(self
+                .inner_convert()
+                .native_transferable_type(Direction::Down))
+        )
+    }
+
+    fn inner_native_str(&self) -> String {
+        format!(
+            "{}",
+            toks!(Swift::from(SwiftType::new(AstType::from(self.inner()))))
+        )
+    }
+
+    ///
+    /// The name of `CArray<T>` in the C header generated by cbindgen.
+    ///
+    fn c_array_name(&self) -> String {
+        format!("CArray_{}", self.inner_transferable_str())
+    }
+}
+
+impl<'a> Convertible<Swift<'a>> for VecNested {
+    fn native_to_transferable(
+        &self,
+        origin: String,
+        direction: Direction,
+    ) -> Tokens<'static, Swift<'a>> {
+        let inner_ty = self.inner_transferable_str();
+        let mut body = Tokens::new();
+        body.append(toks_f!("{{ () -> {} in", self.c_array_name()));
+        nested_f!(
+            body,
+            "let array = {}.map {{ each -> {} in",
+            origin,
+            inner_ty
+        );
+        nested_f!(body, |t| {
+            nested_f!(
+                t,
+                "return {}",
+                self.inner_convert()
+                    .native_to_transferable("each".to_string(), direction)
+            )
+        });
+        nested_f!(body, "}");
+        nested_f!(
+            body,
+            "let buffer = UnsafeMutablePointer<{}>.allocate(capacity: array.count)",
+            inner_ty
+        );
+        nested_f!(body, "array.withUnsafeBufferPointer { inner in");
+        nested_f!(body, |t| {
+            nested_f!(
+                t,
+                "buffer.initialize(from: inner.baseAddress!, count: inner.count)"
+            )
+        });
+        nested_f!(body, "}");
+        nested_f!(body, quote_free_swift_ptr(&inner_ty));
+        nested_f!(
+            body,
+            "return {}(ptr: buffer, len: Int32(array.count), cap: Int32(array.count), free_ptr: free_ptr)",
+            self.c_array_name()
+        );
+        push_f!(body, "}()");
+        body
+    }
+
+    fn transferable_to_native(
+        &self,
+        origin: String,
+        direction: Direction,
+    ) -> Tokens<'static, Swift<'a>> {
+        let inner_ty = self.inner_native_str();
+        let mut body = Tokens::new();
+        body.append(toks_f!("{{ () -> [{}] in", inner_ty));
+        nested_f!(
+            body,
+            "let array = Array(UnsafeBufferPointer(start: {}.ptr, count: Int({}.len)))",
+            origin,
+            origin
+        );
+        nested_f!(body, "let result = array.map {{ each -> {} in", inner_ty);
+        nested_f!(body, |t| {
+            nested_f!(
+                t,
+                "return {}",
+                self.inner_convert()
+                    .transferable_to_native("each".to_string(), direction)
+            )
+        });
+        nested_f!(body, "}");
+        nested_f!(
+            body,
+            "({}.free_ptr)(UnsafeMutablePointer(mutating: {}.ptr), {}.len, {}.cap)",
+            origin,
+            origin,
+            origin,
+            origin
+        );
+        nested_f!(body, "return result");
+        push_f!(body, "}()");
+        body
+    }
+
+    fn rust_to_transferable(&self, origin: TokenStream, direction: Direction) -> TokenStream {
+        let inner_ty = self
+            .inner_convert()
+            .rust_transferable_type(direction.clone());
+        let each = self
+            .inner_convert()
+            .rust_to_transferable(quote! {each}, direction);
+        quote! {{
+            let tmp_vec = #origin.into_iter().map(|each| #each).collect::<Vec<#inner_ty>>();
+            let ptr = tmp_vec.as_ptr();
+            let len = tmp_vec.len();
+            let cap = tmp_vec.capacity();
+            std::mem::forget(tmp_vec);
+            CArray {
+                ptr,
+                len: len as i32,
+                cap: cap as i32,
+                free_ptr: free_c_array::<#inner_ty>
+            }
+        }}
+    }
+
+    fn transferable_to_rust(&self, origin: TokenStream, direction: Direction) -> TokenStream {
+        let inner_ty = self
+            .inner_convert()
+            .rust_transferable_type(direction.clone());
+        let inner_origin_ty = quote_base_ty(&self.inner());
+        let each = self
+            .inner_convert()
+            .transferable_to_rust(quote! {each}, direction);
+        quote! {{
+            let array = #origin;
+            let tmp_vec: Vec<#inner_ty> = (0..array.len as usize)
+                .map(|index| unsafe { std::ptr::read(array.ptr.add(index)) })
+                .collect();
+            (array.free_ptr)(array.ptr as (*mut #inner_ty), array.len, array.cap);
+            tmp_vec.into_iter().map(|each| -> #inner_origin_ty { #each }).collect::<Vec<#inner_origin_ty>>()
+        }}
+    }
+
+    fn native_type(&self) -> Swift<'a> {
+        Swift::from(SwiftType::new(self.ty.clone()))
+    }
+
+    fn native_transferable_type(&self, _direction: Direction) -> Swift<'a> {
+        swift::local(self.c_array_name())
+    }
+
+    fn rust_transferable_type(&self, direction: Direction) -> TokenStream {
+        let inner_ty = self.inner_convert().rust_transferable_type(direction);
+        quote!(CArray<#inner_ty>)
+    }
+
+    fn quote_common_in_bridge(&self) -> TokenStream {
+        quote! {}
+    }
+
+    fn quote_common_in_native(&self) -> Tokens<'static, Swift<'a>> {
+        Tokens::new()
+    }
+
+    fn quote_in_common_rs(&self) -> TokenStream {
+        quote! {}
+    }
+}
@@ -80,7 +80,8 @@ impl<'a> Convertible<Swift<'a>> for VecStruct {
         );
         nested_f!(
             body,
-            "let struct_arg = proxy_array.map { proxy in DemoStruct(proxy: proxy) }"
+            "let struct_arg = proxy_array.map {{ proxy in {}(proxy: proxy) }}",
+            self.struct_name()
         );
         nested_f!(
             body,
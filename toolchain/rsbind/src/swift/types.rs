@@ -1,8 +1,10 @@
+use proc_macro2::TokenStream;
 use rstgen::swift::Swift;
 use rstgen::{swift, Custom, Formatter, Tokens};
 
 use crate::ast::types::{AstBaseType, AstType};
 use crate::errors::*;
+use crate::swift::cancellation::CANCELLATION_CLASS;
 
 #[derive(Clone)]
 pub(crate) struct SwiftType {
@@ -19,6 +21,34 @@ impl SwiftType {
         self.to_swift_array(base_name)
     }
 
+    pub fn to_optional(&self) -> Swift<'static> {
+        let mut base_str = String::new();
+        {
+            let mut formatter = Formatter::new(&mut base_str);
+            let _ = Swift::from(self.clone()).format(&mut formatter, &mut (), 0);
+        }
+        swift::local(format!("{}?", base_str))
+    }
+
+    ///
+    /// The native tuple like `(Int32, String)`.
+    ///
+    pub fn to_tuple(elems: &[AstBaseType]) -> Swift<'static> {
+        let elem_strs = elems
+            .iter()
+            .map(|elem| {
+                let mut elem_str = String::new();
+                {
+                    let mut formatter = Formatter::new(&mut elem_str);
+                    let swift = Swift::from(SwiftType::new(AstType::from(elem.clone())));
+                    let _ = swift.format(&mut formatter, &mut (), 0);
+                }
+                elem_str
+            })
+            .collect::<Vec<String>>();
+        swift::local(format!("({})", elem_strs.join(", ")))
+    }
+
     fn to_swift_array(&self, swift: Swift<'static>) -> Swift<'static> {
         Swift::Array {
             inner: Box::new(swift),
@@ -30,10 +60,11 @@ impl From<SwiftType> for Swift<'static> {
     fn from(item: SwiftType) -> Self {
         match item.ast_type {
             AstType::Void => swift::VOID,
-            AstType::Byte(_) => swift::BYTE,
-            AstType::Short(_) => swift::SHORT,
-            AstType::Int(_) => swift::INTEGER,
-            AstType::Long(_) => swift::LONG,
+            AstType::Byte(ref origin)
+            | AstType::Short(ref origin)
+            | AstType::Int(ref origin)
+            | AstType::Long(ref origin)
+            | AstType::BigInt(ref origin) => swift::local(integer_name(origin)),
             AstType::Float(_) => swift::FLOAT,
             AstType::Double(_) => swift::DOUBLE,
             AstType::Boolean => swift::BOOLEAN,
@@ -43,11 +74,106 @@ impl From<SwiftType> for Swift<'static> {
                 AstBaseType::Byte(_) => SwiftType::new(AstType::from(base)).to_array(),
                 _ => SwiftType::new(AstType::from(base)).to_array(),
             },
-            AstType::Callback(origin) | AstType::Struct(origin) => swift::local(origin.origin),
+            AstType::Optional(base) => SwiftType::new(AstType::from(base)).to_optional(),
+            AstType::Map(_, key, value) => Swift::Map {
+                key: Box::new(Swift::from(SwiftType::new(AstType::from(key)))),
+                value: Box::new(Swift::from(SwiftType::new(AstType::from(value)))),
+            },
+            AstType::Result(_, _) => Swift::from(SwiftType::new(item.ast_type.ok_type())),
+            AstType::Tuple(ref elems) => SwiftType::to_tuple(elems),
+            AstType::Newtype(_, _) => Swift::from(SwiftType::new(item.ast_type.unwrap_newtype())),
+            AstType::Callback(origin)
+            | AstType::Struct(origin)
+            | AstType::Enum(origin)
+            | AstType::Object(origin) => swift::local(origin.origin),
+            AstType::Buffer(_) => swift::local("Data"),
+            AstType::Cancellation(_) => swift::local(CANCELLATION_CLASS),
         }
     }
 }
 
+///
+/// The swift integer for a rust integer, swift has unsigned and pointer-sized integers natively.
+///
+pub(crate) fn integer_name(origin: &str) -> &'static str {
+    match origin {
+        "u8" => "UInt8",
+        "i16" => "Int16",
+        "u16" => "UInt16",
+        "i32" => "Int32",
+        "u32" => "UInt32",
+        "i64" => "Int64",
+        "u64" => "UInt64",
+        "isize" => "Int",
+        "usize" => "UInt",
+        _ => "Int8",
+    }
+}
+
+///
+/// The name of a generic type in the C header generated by cbindgen,
+/// like `CMap_CInt8Array__CArray_i32` for `CMap<CInt8Array, CArray<i32>>`.
+///
+pub(crate) fn c_generic_name(ty: &TokenStream) -> String {
+    match syn::parse2::<syn::Type>(ty.clone()) {
+        Ok(ty) => mangle_c_type(&ty, true),
+        Err(_) => ty.to_string(),
+    }
+}
+
+///
+/// The same as the mangling of cbindgen, the closing of generics is skipped for the last argument.
+///
+fn mangle_c_type(ty: &syn::Type, last: bool) -> String {
+    match ty {
+        syn::Type::Path(ref type_path) => {
+            let segments = &type_path.path.segments;
+            let segment = &segments[segments.len() - 1];
+            let mut name = segment.ident.to_string();
+            if let syn::PathArguments::AngleBracketed(ref generics) = segment.arguments {
+                let args = generics
+                    .args
+                    .iter()
+                    .filter_map(|arg| match arg {
+                        syn::GenericArgument::Type(ref ty) => Some(ty),
+                        _ => None,
+                    })
+                    .collect::<Vec<&syn::Type>>();
+                name.push('_');
+                for (index, arg) in args.iter().enumerate() {
+                    if index > 0 {
+                        name.push_str("__");
+                    }
+                    name.push_str(&mangle_c_type(arg, last && index == args.len() - 1));
+                }
+                if !last {
+                    name.push_str("___");
+                }
+            }
+            name
+        }
+        syn::Type::Ptr(ref ptr) => {
+            let prefix = if ptr.const_token.is_some() {
+                "_____"
+            } else {
+                "____"
+            };
+            format!("{}{}", prefix, mangle_c_type(&ptr.elem, last))
+        }
+        _ => String::new(),
+    }
+}
+
+///
+/// The closure receiving the result of an async method, like `(Result<Inner, Error>) -> ()`.
+/// The failure is the error of a `Result` return, String errors wrapped into `RustError`,
+/// or `RustPanicError` if the future panics.
+///
+pub(crate) fn completion_handler(ok: AstType) -> Swift<'static> {
+    let ok = format!("{}", toks!(Swift::from(SwiftType::new(ok))));
+    swift::local(format!("(Result<{}, Error>) -> ()", ok))
+}
+
 pub(crate) fn to_swift_file(tokens: Tokens<Swift>) -> Result<String> {
     let mut buf = String::new();
     {
@@ -55,5 +181,56 @@ pub(crate) fn to_swift_file(tokens: Tokens<Swift>) -> Result<String> {
         let mut extra = ();
         swift::Swift::write_file(tokens, &mut formatter, &mut extra, 0)?;
     }
-    Ok(buf)
+    // rstgen writes `throws` after the return type, but swift wants it before the arrow.
+    let mut content = buf
+        .lines()
+        .map(move_throws)
+        .collect::<Vec<String>>()
+        .join("\n");
+    if buf.ends_with('\n') {
+        content.push('\n');
+    }
+    Ok(content)
+}
+
+fn move_throws(line: &str) -> String {
+    if !line.contains("func ") {
+        return line.to_owned();
+    }
+    for suffix in [" {", ";"] {
+        if let Some(sig) = line.strip_suffix(&format!(" throws{}", suffix)) {
+            // Closures in the arguments have arrows too, the return type follows the arguments.
+            let args_end = args_end(sig).unwrap_or(0);
+            if let Some(index) = sig[args_end..].find(" -> ").map(|index| index + args_end) {
+                return format!(
+                    "{} throws -> {}{}",
+                    &sig[..index],
+                    &sig[index + 4..],
+                    suffix
+                );
+            }
+        }
+    }
+    line.to_owned()
+}
+
+///
+/// The index after the parenthesis closing the arguments of a function signature.
+///
+fn args_end(sig: &str) -> Option<usize> {
+    let start = sig.find("func ")?;
+    let mut depth = 0;
+    for (index, char) in sig[start..].char_indices() {
+        match char {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(start + index + 1);
+                }
+            }
+            _ => (),
+        }
+    }
+    None
 }
@@ -7,6 +7,7 @@ use crate::ast::types::AstType;
 use crate::base::lang::Convertible;
 use crate::errors::*;
 use crate::swift::converter::SwiftConvert;
+use crate::swift::protocol::completion_argument;
 use crate::swift::types::to_swift_file;
 
 pub(crate) struct WrapperGen<'a> {
@@ -42,12 +43,16 @@ impl<'a> WrapperGen<'a> {
     fn fill_method_sig(&self, method: &MethodDesc) -> Result<Method> {
         let mut m = Method::new(method.name.to_lower_camel_case());
         m.modifiers = vec![Modifier::Public];
-        m.returns(
-            SwiftConvert {
-                ty: method.return_type.clone(),
-            }
-            .native_type(),
-        );
+        // The calls throw `RustPanicError` when rust panics.
+        m.throws = true;
+        if !method.is_async {
+            m.returns(
+                SwiftConvert {
+                    ty: method.return_type.clone(),
+                }
+                .native_type(),
+            );
+        }
 
         let args = method.args.clone();
         for arg in args.iter() {
@@ -57,6 +62,9 @@ impl<'a> WrapperGen<'a> {
             );
             m.arguments.push(argument);
         }
+        if method.is_async {
+            m.arguments.push(completion_argument(method));
+        }
 
         Ok(m)
     }
@@ -67,11 +75,29 @@ impl<'a> WrapperGen<'a> {
         method_body: &mut Tokens<Swift>,
         method: &MethodDesc,
     ) -> Result<()> {
-        match method.return_type.clone() {
+        if method.is_async {
+            // The handler calls the closure when rust completes the future.
+            let args = method
+                .args
+                .iter()
+                .map(|item| format!("{} : {}, ", item.name, item.name))
+                .collect::<String>();
+            push_f!(
+                method_body,
+                "try {}.{}({}completion : {}Handler(handler : completion))",
+                inner_cls_name,
+                method.name.to_lower_camel_case(),
+                args,
+                method.completion_name(self.desc)
+            );
+            return Ok(());
+        }
+
+        match method.return_type.ok_type() {
             AstType::Void => {
                 push_f!(
                     method_body,
-                    "{}.{}(",
+                    "try {}.{}(",
                     inner_cls_name,
                     method.name.to_lower_camel_case(),
                 );
@@ -79,7 +105,7 @@ impl<'a> WrapperGen<'a> {
             _ => {
                 push_f!(
                     method_body,
-                    "return {}.{}(",
+                    "return try {}.{}(",
                     inner_cls_name,
                     method.name.to_lower_camel_case(),
                 );
@@ -1,5 +1,43 @@
 #[cfg(test)]
 mod tests {
+    use crate::ast::types::AstBaseType;
+    use crate::java::ty::proxy::{has_proxy, quote_from_proxy, quote_into_proxy, quote_proxy_ty};
+
     #[test]
     fn gen_jni_works() {}
+
+    #[test]
+    fn gen_jni_unsigned_proxy_works() {
+        let nested = AstBaseType::Vec(Box::new(AstBaseType::Vec(Box::new(AstBaseType::Byte(
+            "u8".to_owned(),
+        )))));
+        assert!(has_proxy(&nested));
+        assert_eq!(quote_proxy_ty(&nested).to_string(), "Vec < Vec < i8 > >");
+        assert_eq!(
+            quote_into_proxy(quote! {value}, &nested).to_string(),
+            quote! {value.into_iter().map(|each| each.into_iter().map(|each| each as i8).collect::<Vec<_>>()).collect::<Vec<_>>()}.to_string()
+        );
+        assert_eq!(
+            quote_from_proxy(quote! {proxy}, &nested).to_string(),
+            quote! {proxy.into_iter().map(|each| each.into_iter().map(|each| each as u8).collect::<Vec<_>>()).collect::<Vec<_>>()}.to_string()
+        );
+
+        // Gson writes the bytes above 127 signed, the bridge reads them back through the proxy.
+        let proxy: Vec<Vec<i8>> = serde_json::from_str("[[-56,0,127],[-1]]").unwrap();
+        let value = proxy
+            .into_iter()
+            .map(|each| each.into_iter().map(|each| each as u8).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert_eq!(value, vec![vec![200u8, 0, 127], vec![255]]);
+        let proxy = value
+            .into_iter()
+            .map(|each| each.into_iter().map(|each| each as i8).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert_eq!(serde_json::to_string(&proxy).unwrap(), "[[-56,0,127],[-1]]");
+
+        let widened = AstBaseType::Vec(Box::new(AstBaseType::Int("u16".to_owned())));
+        let signed = AstBaseType::Vec(Box::new(AstBaseType::Byte("i8".to_owned())));
+        assert!(!has_proxy(&widened));
+        assert!(!has_proxy(&signed));
+    }
 }